@@ -5,9 +5,14 @@ use crossterm::{
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
 use ratatui::{Terminal, backend::CrosstermBackend};
-use std::{error::Error, io};
+use serde::Serialize;
+use std::{error::Error, io, path::PathBuf, sync::Arc, time::Duration};
 
-use lazyslurm::slurm::check_slurm_available;
+use lazyslurm::models::{Job, JobList};
+use lazyslurm::slurm::{
+    Backend, CommandLimits, CommandWrapper, DEFAULT_REST_API_VERSION, SlurmExecutor, SlurmProcess,
+    SlurmRecorder, SlurmRest, SlurmSimulator, check_slurm_available,
+};
 use lazyslurm::ui::{App, events};
 
 #[derive(Parser, Debug)]
@@ -34,10 +39,23 @@ use lazyslurm::ui::{App, events};
   q: quit
   ↑/↓ or j/k: navigate jobs
   r: refresh jobs
+  tab or 1/2: switch between the Jobs and History tabs
   c: cancel selected job
+  m: choose which cluster(s) to show
 
 Notes:
   - SLURM tools required for normal operation: squeue, scontrol, scancel.
+  - The History tab additionally needs sacct (Slurm accounting).
+  - With --backend rest no local tools are needed; get a token with
+    `scontrol token` and export it as SLURM_JWT.
+  - With --remote the tools (and job logs) are reached through the given
+    command instead, e.g. --remote 'ssh -o BatchMode=yes login1 --'.
+  - Slurm commands are killed after --command-timeout seconds and retried
+    with backoff when the controller is busy.
+  - --demo runs against a simulated cluster, handy for trying lazyslurm
+    without access to one.
+  - --record DIR saves every response as a test fixture (see SlurmFixture);
+    attach the directory to bug reports.
 "#
 )]
 struct Cli {
@@ -55,11 +73,184 @@ struct Cli {
     )]
     partition: Option<String>,
 
+    #[arg(
+        short = 'M',
+        long = "clusters",
+        value_name = "CLUSTERS",
+        help = "Clusters to query: a name, a comma separated list, or 'all'"
+    )]
+    clusters: Option<String>,
+
     #[arg(
         long = "json",
-        help = "Fetch jobs once, print as JSON to stdout, and exit (headless mode)"
+        help = "Fetch jobs once, print them as JSON to stdout, and exit (headless mode)"
     )]
     json: bool,
+
+    #[arg(
+        long = "history",
+        requires = "json",
+        help = "With --json, also print jobs finished within --history-window and their efficiency (runs sacct)"
+    )]
+    history: bool,
+
+    #[arg(
+        long = "history-window",
+        value_name = "DURATION",
+        default_value = "24h",
+        value_parser = parse_window,
+        help = "How far back the History tab looks, e.g. 90m, 24h or 7d"
+    )]
+    history_window: Duration,
+
+    #[arg(
+        long = "backend",
+        value_enum,
+        default_value_t = Backend::Cli,
+        help = "Where to get Slurm data from: the local CLI tools or slurmrestd"
+    )]
+    backend: Backend,
+
+    #[arg(
+        long = "rest-url",
+        env = "SLURMRESTD_URL",
+        value_name = "URL",
+        help = "slurmrestd base URL for --backend rest, e.g. http://slurmctld:6820"
+    )]
+    rest_url: Option<String>,
+
+    #[arg(
+        long = "rest-token",
+        env = "SLURM_JWT",
+        value_name = "JWT",
+        hide_env_values = true,
+        help = "JWT sent to slurmrestd (see `scontrol token`)"
+    )]
+    rest_token: Option<String>,
+
+    #[arg(
+        long = "rest-user",
+        env = "SLURMRESTD_USER",
+        value_name = "USER",
+        help = "User for slurmrestd to act as, with a token issued to a privileged user"
+    )]
+    rest_user: Option<String>,
+
+    #[arg(
+        long = "rest-api-version",
+        value_name = "VERSION",
+        default_value = DEFAULT_REST_API_VERSION,
+        help = "slurmrestd API version to request"
+    )]
+    rest_api_version: String,
+
+    #[arg(
+        long = "remote",
+        env = "LAZYSLURM_REMOTE",
+        value_name = "COMMAND",
+        value_parser = parse_remote,
+        conflicts_with = "backend",
+        help = "Run Slurm commands through this prefix, e.g. 'ssh -o BatchMode=yes login1 --'"
+    )]
+    remote: Option<CommandWrapper>,
+
+    #[arg(
+        long = "command-timeout",
+        value_name = "SECONDS",
+        default_value_t = 30,
+        help = "Kill a Slurm command that has not finished after this many seconds"
+    )]
+    command_timeout: u64,
+
+    #[arg(
+        long = "retries",
+        value_name = "COUNT",
+        default_value_t = 2,
+        help = "Retry a Slurm command this many times when the controller is slow"
+    )]
+    retries: u32,
+
+    #[arg(
+        long = "record",
+        value_name = "DIR",
+        help = "Save every Slurm response under DIR as a fixture that can be replayed in tests"
+    )]
+    record: Option<PathBuf>,
+
+    #[arg(
+        long = "demo",
+        conflicts_with_all = ["backend", "remote"],
+        help = "Run against a built-in simulated cluster; no Slurm installation needed"
+    )]
+    demo: bool,
+}
+
+fn parse_remote(s: &str) -> Result<CommandWrapper, String> {
+    CommandWrapper::parse(s).map_err(|e| e.to_string())
+}
+
+fn build_executor(cli: &Cli) -> Result<Arc<dyn SlurmExecutor>, String> {
+    let executor = build_backend(cli)?;
+    match &cli.record {
+        Some(dir) => SlurmRecorder::new(executor, dir)
+            .map(|recorder| Arc::new(recorder) as Arc<dyn SlurmExecutor>)
+            .map_err(|e| format!("{e:#}")),
+        None => Ok(executor),
+    }
+}
+
+fn build_backend(cli: &Cli) -> Result<Arc<dyn SlurmExecutor>, String> {
+    if cli.demo {
+        let user = std::env::var("USER").unwrap_or_else(|_| "demo".to_string());
+        return SlurmSimulator::demo(&user)
+            .map(|sim| Arc::new(sim) as Arc<dyn SlurmExecutor>)
+            .map_err(|e| format!("{e:#}"));
+    }
+
+    match cli.backend {
+        Backend::Cli => {
+            let process = match &cli.remote {
+                Some(wrapper) => SlurmProcess::wrapped(wrapper.clone()),
+                None => SlurmProcess::new(),
+            };
+            let limits = CommandLimits {
+                timeout: Duration::from_secs(cli.command_timeout.max(1)),
+                retries: cli.retries,
+                ..CommandLimits::default()
+            };
+            Ok(Arc::new(process.with_limits(limits)))
+        }
+        Backend::Rest => {
+            let url = cli
+                .rest_url
+                .clone()
+                .ok_or("--backend rest needs --rest-url or SLURMRESTD_URL")?;
+            Ok(Arc::new(
+                SlurmRest::new(url, cli.rest_token.clone())
+                    .with_api_version(cli.rest_api_version.clone())
+                    .with_user_name(cli.rest_user.clone()),
+            ))
+        }
+    }
+}
+
+/// Parse a history window such as "90m", "24h" or "7d". A bare number is
+/// taken as hours.
+fn parse_window(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let (number, unit_secs) = match s.char_indices().last() {
+        Some((i, 'm')) => (&s[..i], 60),
+        Some((i, 'h')) => (&s[..i], 60 * 60),
+        Some((i, 'd')) => (&s[..i], 24 * 60 * 60),
+        _ => (s, 60 * 60),
+    };
+    let count: u64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration '{s}', expected e.g. 90m, 24h or 7d"))?;
+    if count == 0 {
+        return Err("duration must be greater than zero".to_string());
+    }
+    Ok(Duration::from_secs(count * unit_secs))
 }
 
 #[tokio::main]
@@ -68,7 +259,18 @@ async fn main() -> Result<(), Box<dyn Error>> {
     let cli = Cli::parse();
 
     // Check if SLURM is available
-    if !check_slurm_available() {
+    if cli.demo {
+        // The simulator needs nothing installed
+    } else if let Some(wrapper) = &cli.remote {
+        if !wrapper.check_available() {
+            eprintln!(
+                "Error: slurm commands not found through --remote '{}'.",
+                wrapper.prefix().join(" ")
+            );
+            eprintln!("Required commands on the remote side: squeue, scontrol, scancel");
+            std::process::exit(1);
+        }
+    } else if !check_slurm_available(cli.backend) {
         eprintln!(
             "Error: slurm commands not found. Please make sure slurm is installed and available in PATH."
         );
@@ -76,8 +278,24 @@ async fn main() -> Result<(), Box<dyn Error>> {
         std::process::exit(1);
     }
 
+    let executor = match build_executor(&cli) {
+        Ok(executor) => executor,
+        Err(err) => {
+            eprintln!("Error: {err}");
+            std::process::exit(1);
+        }
+    };
+
     if cli.json {
-        return run_headless(cli.user, cli.partition).await;
+        let history_window = cli.history.then_some(cli.history_window);
+        return run_headless(
+            executor,
+            cli.user,
+            cli.partition,
+            cli.clusters,
+            history_window,
+        )
+        .await;
     }
 
     // Setup terminal
@@ -88,7 +306,9 @@ async fn main() -> Result<(), Box<dyn Error>> {
     let mut terminal = Terminal::new(backend)?;
 
     // Create app and run
-    let mut app = App::with_cli(cli.user, cli.partition);
+    let mut app = App::with_cli(executor, cli.user, cli.partition);
+    app.current_clusters = cli.clusters;
+    app.history_window = cli.history_window;
     let result = run_app(&mut terminal, &mut app).await;
 
     // Restore terminal
@@ -108,10 +328,14 @@ async fn main() -> Result<(), Box<dyn Error>> {
 }
 
 async fn run_headless(
+    executor: Arc<dyn SlurmExecutor>,
     user: Option<String>,
     partition: Option<String>,
+    clusters: Option<String>,
+    history_window: Option<Duration>,
 ) -> Result<(), Box<dyn Error>> {
-    let mut app = App::with_cli(user, partition);
+    let mut app = App::with_cli(executor, user, partition);
+    app.current_clusters = clusters;
     app.refresh_jobs().await?;
 
     if let Some(err) = &app.error_message {
@@ -119,17 +343,40 @@ async fn run_headless(
         std::process::exit(1);
     }
 
-    let json = serde_json::to_string_pretty(&app.job_list)?;
+    // sacct may be missing (e.g. over REST); the queue is still worth printing
+    let mut history = None;
+    if let Some(window) = history_window {
+        app.history_window = window;
+        app.refresh_history().await?;
+        history = app
+            .error_message
+            .is_none()
+            .then_some(&app.history_list.jobs);
+    }
+
+    let json = serde_json::to_string_pretty(&HeadlessOutput {
+        job_list: &app.job_list,
+        history,
+    })?;
     println!("{json}");
     Ok(())
 }
 
+#[derive(Serialize)]
+struct HeadlessOutput<'a> {
+    #[serde(flatten)]
+    job_list: &'a JobList,
+    /// Finished jobs from sacct, with their efficiency.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    history: Option<&'a Vec<Job>>,
+}
+
 async fn run_app(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     app: &mut App,
 ) -> Result<(), Box<dyn Error>> {
-    // Initial refresh
-    app.refresh_jobs().await?;
+    // Initial refresh; the UI draws while it runs
+    app.start_refresh();
 
     events::run_event_loop(app, terminal).await?;
 
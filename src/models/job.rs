@@ -12,6 +12,7 @@ pub enum JobState {
     Timeout,
     NodeFail,
     Preempted,
+    OutOfMemory,
     Unknown(String),
 }
 
@@ -26,6 +27,7 @@ impl fmt::Display for JobState {
             JobState::Timeout => write!(f, "TO"),
             JobState::NodeFail => write!(f, "NF"),
             JobState::Preempted => write!(f, "PR"),
+            JobState::OutOfMemory => write!(f, "OOM"),
             JobState::Unknown(s) => write!(f, "{}", s),
         }
     }
@@ -42,11 +44,26 @@ impl From<&str> for JobState {
             "TIMEOUT" | "TO" => JobState::Timeout,
             "NODE_FAIL" | "NF" => JobState::NodeFail,
             "PREEMPTED" | "PR" => JobState::Preempted,
+            "OUT_OF_MEMORY" | "OOM" => JobState::OutOfMemory,
             _ => JobState::Unknown(s.to_string()),
         }
     }
 }
 
+impl JobState {
+    /// Ended in a way worth running again: failed, timed out or ran out of
+    /// memory. Cancelled jobs were stopped on purpose.
+    pub fn is_failure(&self) -> bool {
+        matches!(
+            self,
+            JobState::Failed | JobState::Timeout | JobState::OutOfMemory
+        )
+    }
+}
+
+/// A job's cluster and id, which together identify it.
+pub type JobKey = (Option<String>, String);
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Job {
     pub job_id: String,
@@ -55,21 +72,41 @@ pub struct Job {
     pub name: String,
     pub user: String,
     pub partition: String,
+    /// Set when jobs were listed with `-M`; `None` means the local cluster.
+    pub cluster: Option<String>,
     pub state: JobState,
     pub time_limit: Option<String>,
     pub time_used: Option<String>,
     pub submit_time: Option<DateTime<Utc>>,
     pub start_time: Option<DateTime<Utc>>,
+    /// The scheduler's estimate of when a pending job starts.
+    pub estimated_start: Option<DateTime<Utc>>,
     pub end_time: Option<DateTime<Utc>>,
     pub nodes: Option<u32>,
     pub node_list: Option<String>,
     pub cpus: Option<u32>,
     pub memory: Option<String>,
+    /// Memory per CPU, for jobs that asked for it that way instead.
+    pub memory_per_cpu: Option<String>,
+    pub num_tasks: Option<u32>,
+    pub cpus_per_task: Option<u32>,
+    /// Generic resources per node in sbatch's `--gres` form, e.g. "gpu:1".
+    pub gres: Option<String>,
+    pub account: Option<String>,
+    pub qos: Option<String>,
+    /// The batch script as submitted; `None` for srun/salloc and `--wrap`
+    /// jobs.
+    pub command: Option<String>,
+    /// Whether sbatch submitted the job, as scontrol reports it; `None`
+    /// when not known.
+    pub batch: Option<bool>,
     pub working_dir: Option<String>,
     pub std_out: Option<String>,
     pub std_err: Option<String>,
     pub exit_code: Option<i32>,
     pub reason: Option<String>,
+    /// From sacct, for jobs that have finished.
+    pub efficiency: Option<JobEfficiency>,
 }
 
 impl Job {
@@ -81,28 +118,62 @@ impl Job {
             name,
             user,
             partition: "".to_string(),
+            cluster: None,
             state,
             time_limit: None,
             time_used: None,
             submit_time: None,
             start_time: None,
+            estimated_start: None,
             end_time: None,
             nodes: None,
             node_list: None,
             cpus: None,
             memory: None,
+            memory_per_cpu: None,
+            num_tasks: None,
+            cpus_per_task: None,
+            gres: None,
+            account: None,
+            qos: None,
+            command: None,
+            batch: None,
             working_dir: None,
             std_out: None,
             std_err: None,
             exit_code: None,
             reason: None,
+            efficiency: None,
         }
     }
 
+    /// Whether `other` refers to the same job. Job ids are only unique
+    /// within a cluster.
+    pub fn same_job(&self, other: &Job) -> bool {
+        self.job_id == other.job_id && self.cluster == other.cluster
+    }
+
+    pub fn key(&self) -> JobKey {
+        (self.cluster.clone(), self.job_id.clone())
+    }
+
     pub fn is_array_job(&self) -> bool {
         self.array_job_id.is_some()
     }
 
+    /// The array tasks this entry stands for: its own task, or every task
+    /// of a pending range that squeue lists as one line, e.g. `100_[4-9]`.
+    pub fn array_task_ids(&self) -> Vec<u32> {
+        if let Some(task_id) = self.array_task_id {
+            return vec![task_id];
+        }
+        self.job_id
+            .split_once('_')
+            .and_then(|(_, tasks)| tasks.strip_prefix('[')?.strip_suffix(']'))
+            .and_then(parse_task_ranges)
+            .unwrap_or_default()
+    }
+
     pub fn display_id(&self) -> String {
         match (&self.array_job_id, &self.array_task_id) {
             (Some(array_id), Some(task_id)) => format!("{}_{}", array_id, task_id),
@@ -114,19 +185,203 @@ impl Job {
         matches!(self.state, JobState::Running)
     }
 
+    /// Stopped by an administrator with `scontrol suspend`; squeue reports
+    /// it as `S`.
+    pub fn is_suspended(&self) -> bool {
+        matches!(&self.state, JobState::Unknown(state) if state == "S" || state == "SUSPENDED")
+    }
+
+    /// True once the job has left the queue, whatever its final state.
+    pub fn is_finished(&self) -> bool {
+        !matches!(self.state, JobState::Running | JobState::Pending)
+    }
+
+    /// Pending because its owner or an administrator put it on hold.
+    pub fn is_held(&self) -> bool {
+        self.state == JobState::Pending
+            && self
+                .reason
+                .as_deref()
+                .is_some_and(|reason| reason.starts_with("JobHeld"))
+    }
+
     pub fn is_completed(&self) -> bool {
         matches!(
             self.state,
-            JobState::Completed | JobState::Failed | JobState::Cancelled | JobState::Timeout
+            JobState::Completed
+                | JobState::Failed
+                | JobState::Cancelled
+                | JobState::Timeout
+                | JobState::OutOfMemory
         )
     }
 
+    /// Run time so far. For running jobs Slurm reports the *expected* end
+    /// time, and for pending jobs the *expected* start, so neither is used.
     pub fn duration(&self) -> Option<chrono::Duration> {
-        match (&self.start_time, &self.end_time) {
-            (Some(start), Some(end)) => Some(*end - *start),
-            (Some(start), None) => Some(Utc::now() - *start),
+        let start = self.start_time?;
+        match self.state {
+            JobState::Pending => None,
+            JobState::Running => Some(Utc::now() - start),
+            _ => Some(self.end_time.unwrap_or_else(Utc::now) - start),
+        }
+    }
+}
+
+/// Slurm's ceiling for MaxArraySize: no array task id reaches it, so
+/// ranges past it are refused before they are expanded.
+pub const MAX_ARRAY_SIZE: u32 = 4_000_001;
+
+/// Why [`parse_task_ranges_upto`] refused a range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskRangeError {
+    Malformed,
+    /// The first task above the limit.
+    Beyond(u32),
+}
+
+/// Task ids of an array range as sbatch and scancel take it, e.g.
+/// "3-17,20" or "1-9:2", sorted and without repeats. A trailing `%N`
+/// concurrency limit is ignored. `None` if malformed, empty or past
+/// [`MAX_ARRAY_SIZE`].
+pub fn parse_task_ranges(ranges: &str) -> Option<Vec<u32>> {
+    parse_task_ranges_upto(ranges, MAX_ARRAY_SIZE - 1).ok()
+}
+
+/// [`parse_task_ranges`] for tasks up to `max`, which bounds how far the
+/// ranges are expanded.
+pub fn parse_task_ranges_upto(ranges: &str, max: u32) -> Result<Vec<u32>, TaskRangeError> {
+    let ranges = ranges.split_once('%').map_or(ranges, |(ranges, _)| ranges);
+    let mut bounds = Vec::new();
+    for part in ranges.split(',') {
+        let (range, step) = match part.trim().split_once(':') {
+            Some((range, step)) => (
+                range,
+                step.parse::<u32>()
+                    .ok()
+                    .filter(|s| *s > 0)
+                    .ok_or(TaskRangeError::Malformed)?,
+            ),
+            None => (part.trim(), 1),
+        };
+        let (first, last) = range.split_once('-').unwrap_or((range, range));
+        let (Ok(first), Ok(last)) = (first.parse::<u32>(), last.parse::<u32>()) else {
+            return Err(TaskRangeError::Malformed);
+        };
+        if first > last {
+            return Err(TaskRangeError::Malformed);
+        }
+        let last = first + (last - first) / step * step;
+        if last > max {
+            let beyond = if first > max {
+                first
+            } else {
+                first + ((max - first) / step + 1) * step
+            };
+            return Err(TaskRangeError::Beyond(beyond));
+        }
+        bounds.push((first, last, step));
+    }
+    let mut tasks = Vec::new();
+    for (first, last, step) in bounds {
+        tasks.extend((first..=last).step_by(step as usize));
+    }
+    tasks.sort_unstable();
+    tasks.dedup();
+    Ok(tasks)
+}
+
+/// The inverse of [`parse_task_ranges`]: task ids compressed into ranges,
+/// e.g. "1,3-5,9", for sbatch's `--array`.
+pub fn format_task_ranges(tasks: &[u32]) -> String {
+    let mut tasks = tasks.to_vec();
+    tasks.sort_unstable();
+    tasks.dedup();
+    let mut ranges: Vec<(u32, u32)> = Vec::new();
+    for task in tasks {
+        match ranges.last_mut() {
+            Some((_, last)) if *last + 1 == task => *last = task,
+            _ => ranges.push((task, task)),
+        }
+    }
+    ranges
+        .iter()
+        .map(|&(first, last)| match last - first {
+            0 => first.to_string(),
+            1 => format!("{},{}", first, last),
+            _ => format!("{}-{}", first, last),
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// The `%N` limit on how many tasks of an array run at once, as in
+/// "0-999%50". `None` when there is none.
+pub fn parse_task_throttle(ranges: &str) -> Option<u32> {
+    let (_, throttle) = ranges.split_once('%')?;
+    throttle
+        .trim_end_matches(']')
+        .parse()
+        .ok()
+        .filter(|throttle| *throttle > 0)
+}
+
+/// Jobs using less than this percentage of the CPUs or memory they asked
+/// for are flagged.
+pub const WASTEFUL_EFFICIENCY: f64 = 25.0;
+
+/// How much of its allocation a finished job used, as `seff` reports it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobEfficiency {
+    /// CPU time used by all tasks (sacct TotalCPU), in seconds.
+    pub cpu_used_secs: f64,
+    /// Elapsed × AllocCPUS, in seconds.
+    pub cpu_allocated_secs: f64,
+    /// Percentage of the allocated CPU time used.
+    pub cpu_efficiency: Option<f64>,
+    /// Peak MaxRSS over the job's steps, in bytes.
+    pub memory_used: Option<u64>,
+    /// Memory requested per node (ReqMem), in bytes.
+    pub memory_requested: Option<u64>,
+    /// Percentage of the requested memory used at the peak.
+    pub memory_efficiency: Option<f64>,
+}
+
+impl JobEfficiency {
+    pub fn new(
+        cpu_used_secs: f64,
+        cpu_allocated_secs: f64,
+        memory_used: Option<u64>,
+        memory_requested: Option<u64>,
+    ) -> Self {
+        let cpu_efficiency =
+            (cpu_allocated_secs > 0.0).then(|| cpu_used_secs / cpu_allocated_secs * 100.0);
+        let memory_efficiency = match (memory_used, memory_requested) {
+            (Some(used), Some(requested)) if requested > 0 => {
+                Some(used as f64 / requested as f64 * 100.0)
+            }
             _ => None,
+        };
+        Self {
+            cpu_used_secs,
+            cpu_allocated_secs,
+            cpu_efficiency,
+            memory_used,
+            memory_requested,
+            memory_efficiency,
+        }
+    }
+
+    /// Used under [`WASTEFUL_EFFICIENCY`] of its CPUs or memory. Jobs that
+    /// held less than a CPU-hour are not flagged; there is little to waste.
+    pub fn is_wasteful(&self) -> bool {
+        if self.cpu_allocated_secs < 3600.0 {
+            return false;
         }
+        self.cpu_efficiency
+            .into_iter()
+            .chain(self.memory_efficiency)
+            .any(|percent| percent < WASTEFUL_EFFICIENCY)
     }
 }
 
@@ -1,3 +1,15 @@
 pub mod job;
+pub mod node;
+pub mod partition;
+pub mod priority;
+pub mod submit;
+pub mod update;
+pub mod usage;
 
 pub use job::*;
+pub use node::*;
+pub use partition::*;
+pub use priority::*;
+pub use submit::*;
+pub use update::*;
+pub use usage::*;
@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum NodeState {
+    Idle,
+    Mixed,
+    Allocated,
+    Completing,
+    /// Draining but still running jobs.
+    Draining,
+    /// Drained and empty.
+    Drained,
+    Down,
+    Reserved,
+    Maintenance,
+    Unknown(String),
+}
+
+impl fmt::Display for NodeState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NodeState::Idle => write!(f, "idle"),
+            NodeState::Mixed => write!(f, "mix"),
+            NodeState::Allocated => write!(f, "alloc"),
+            NodeState::Completing => write!(f, "comp"),
+            NodeState::Draining => write!(f, "drng"),
+            NodeState::Drained => write!(f, "drain"),
+            NodeState::Down => write!(f, "down"),
+            NodeState::Reserved => write!(f, "resv"),
+            NodeState::Maintenance => write!(f, "maint"),
+            NodeState::Unknown(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+impl From<&str> for NodeState {
+    /// Accepts sinfo's states ("mixed", "drained*", "idle~") as well as
+    /// scontrol's ("MIXED+DRAIN", "IDLE+CLOUD+POWERED_DOWN").
+    fn from(s: &str) -> Self {
+        let mut flags = s.split('+');
+        // sinfo appends one of *~#!%$@^- for not responding, powered off, ...
+        let base = flags
+            .next()
+            .unwrap_or("")
+            .trim_end_matches(|c: char| !c.is_ascii_alphanumeric())
+            .to_lowercase();
+        let draining = flags.any(|flag| flag.eq_ignore_ascii_case("DRAIN"));
+
+        let state = match base.as_str() {
+            "idle" => NodeState::Idle,
+            "mix" | "mixed" => NodeState::Mixed,
+            "alloc" | "allocated" => NodeState::Allocated,
+            "comp" | "completing" => NodeState::Completing,
+            "drng" | "draining" => NodeState::Draining,
+            "drain" | "drained" => NodeState::Drained,
+            "down" => NodeState::Down,
+            "resv" | "reserved" => NodeState::Reserved,
+            "maint" => NodeState::Maintenance,
+            _ => NodeState::Unknown(s.to_string()),
+        };
+
+        match state {
+            NodeState::Idle if draining => NodeState::Drained,
+            NodeState::Mixed | NodeState::Allocated | NodeState::Completing if draining => {
+                NodeState::Draining
+            }
+            state => state,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Node {
+    pub name: String,
+    /// Set when nodes were listed with `-M`; `None` means the local cluster.
+    pub cluster: Option<String>,
+    /// Every partition the node belongs to; the default one keeps sinfo's `*`.
+    pub partitions: Vec<String>,
+    pub state: NodeState,
+    pub cpus_alloc: u32,
+    pub cpus_idle: u32,
+    pub cpus_total: u32,
+    /// Memory figures in MB, as Slurm reports them.
+    pub memory: Option<u64>,
+    pub memory_alloc: Option<u64>,
+    pub memory_free: Option<u64>,
+    pub cpu_load: Option<f64>,
+    pub gres: Option<String>,
+    pub features: Vec<String>,
+    pub reason: Option<String>,
+}
+
+impl Node {
+    pub fn new(name: String, state: NodeState) -> Self {
+        Self {
+            name,
+            cluster: None,
+            partitions: Vec::new(),
+            state,
+            cpus_alloc: 0,
+            cpus_idle: 0,
+            cpus_total: 0,
+            memory: None,
+            memory_alloc: None,
+            memory_free: None,
+            cpu_load: None,
+            gres: None,
+            features: Vec::new(),
+            reason: None,
+        }
+    }
+
+    /// Down, drained or draining: the node takes no new work.
+    pub fn is_unavailable(&self) -> bool {
+        matches!(
+            self.state,
+            NodeState::Down | NodeState::Drained | NodeState::Draining
+        )
+    }
+}
@@ -0,0 +1,88 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PartitionState {
+    Up,
+    Down,
+    /// Takes no new jobs; running ones carry on.
+    Drain,
+    Inactive,
+    Unknown(String),
+}
+
+impl fmt::Display for PartitionState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PartitionState::Up => write!(f, "up"),
+            PartitionState::Down => write!(f, "down"),
+            PartitionState::Drain => write!(f, "drain"),
+            PartitionState::Inactive => write!(f, "inact"),
+            PartitionState::Unknown(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+impl From<&str> for PartitionState {
+    fn from(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "up" => PartitionState::Up,
+            "down" => PartitionState::Down,
+            "drain" => PartitionState::Drain,
+            "inact" | "inactive" => PartitionState::Inactive,
+            _ => PartitionState::Unknown(s.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Partition {
+    pub name: String,
+    /// Set when partitions were listed with `-M`; `None` means the local cluster.
+    pub cluster: Option<String>,
+    /// Jobs submitted without `-p` land here.
+    pub is_default: bool,
+    pub state: PartitionState,
+    /// Limits as Slurm prints them, e.g. "2-00:00:00" or "infinite".
+    pub time_limit: Option<String>,
+    pub default_time: Option<String>,
+    /// e.g. "4000M per CPU" or "UNLIMITED".
+    pub default_memory: Option<String>,
+    pub max_memory: Option<String>,
+    pub nodes_alloc: u32,
+    pub nodes_idle: u32,
+    pub nodes_other: u32,
+    pub nodes_total: u32,
+    pub cpus_alloc: u32,
+    pub cpus_idle: u32,
+    pub cpus_other: u32,
+    pub cpus_total: u32,
+    /// Jobs in the partition matching the current user filter.
+    pub running_jobs: u32,
+    pub pending_jobs: u32,
+}
+
+impl Partition {
+    pub fn new(name: String, state: PartitionState) -> Self {
+        Self {
+            name,
+            cluster: None,
+            is_default: false,
+            state,
+            time_limit: None,
+            default_time: None,
+            default_memory: None,
+            max_memory: None,
+            nodes_alloc: 0,
+            nodes_idle: 0,
+            nodes_other: 0,
+            nodes_total: 0,
+            cpus_alloc: 0,
+            cpus_idle: 0,
+            cpus_other: 0,
+            cpus_total: 0,
+            running_jobs: 0,
+            pending_jobs: 0,
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+
+/// A pending job's priority and the weighted factors it is summed from, as
+/// `sprio` reports them. A job pending in several partitions has one per
+/// partition.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobPriority {
+    pub job_id: String,
+    pub user: String,
+    pub partition: String,
+    pub priority: u64,
+    /// Grows the longer the job waits.
+    pub age: u64,
+    /// Shrinks as the user's account uses more than its share.
+    pub fairshare: u64,
+    pub job_size: u64,
+    /// From the partition's PriorityJobFactor.
+    pub partition_factor: u64,
+    pub qos: u64,
+    /// Per-resource factors, e.g. "cpu=10,mem=5"; usually unset.
+    pub tres: Option<String>,
+}
+
+impl JobPriority {
+    pub fn new(job_id: String, user: String, partition: String) -> Self {
+        Self {
+            job_id,
+            user,
+            partition,
+            priority: 0,
+            age: 0,
+            fairshare: 0,
+            job_size: 0,
+            partition_factor: 0,
+            qos: 0,
+            tres: None,
+        }
+    }
+
+    /// The numeric factors by name, in sprio's column order.
+    pub fn factors(&self) -> [(&'static str, u64); 5] {
+        [
+            ("Age", self.age),
+            ("Fairshare", self.fairshare),
+            ("Job size", self.job_size),
+            ("Partition", self.partition_factor),
+            ("QOS", self.qos),
+        ]
+    }
+}
+
+/// A user's standing in the fairshare tree, as `sshare` reports it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FairShare {
+    pub account: String,
+    pub user: String,
+    pub raw_shares: Option<u64>,
+    /// The account's slice of the cluster, 0 to 1.
+    pub norm_shares: Option<f64>,
+    pub raw_usage: Option<u64>,
+    /// The account's slice of recent usage, 0 to 1.
+    pub effective_usage: Option<f64>,
+    /// The fairshare factor, 0 to 1; below 0.5 means more than the fair
+    /// share has been used.
+    pub fairshare: Option<f64>,
+}
+
+impl FairShare {
+    pub fn new(account: String, user: String) -> Self {
+        Self {
+            account,
+            user,
+            raw_shares: None,
+            norm_shares: None,
+            raw_usage: None,
+            effective_usage: None,
+            fairshare: None,
+        }
+    }
+}
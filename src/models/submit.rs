@@ -0,0 +1,271 @@
+use crate::models::{Job, parse_time_limit};
+
+/// An sbatch option the submit form can override, as `--<option>=<value>`
+/// on the command line, which takes precedence over the script's
+/// `#SBATCH` directives.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SubmitField {
+    JobName,
+    Partition,
+    Time,
+    Nodes,
+    Cpus,
+    Memory,
+    Gres,
+    Dependency,
+}
+
+impl SubmitField {
+    /// In the order the submit form lists them.
+    pub const EDITABLE: [SubmitField; 8] = [
+        SubmitField::JobName,
+        SubmitField::Partition,
+        SubmitField::Time,
+        SubmitField::Nodes,
+        SubmitField::Cpus,
+        SubmitField::Memory,
+        SubmitField::Gres,
+        SubmitField::Dependency,
+    ];
+
+    /// The long option name, without the leading dashes.
+    pub fn option(&self) -> &'static str {
+        match self {
+            SubmitField::JobName => "job-name",
+            SubmitField::Partition => "partition",
+            SubmitField::Time => "time",
+            SubmitField::Nodes => "nodes",
+            SubmitField::Cpus => "cpus-per-task",
+            SubmitField::Memory => "mem",
+            SubmitField::Gres => "gres",
+            SubmitField::Dependency => "dependency",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SubmitField::JobName => "Job name",
+            SubmitField::Partition => "Partition",
+            SubmitField::Time => "Time limit",
+            SubmitField::Nodes => "Nodes",
+            SubmitField::Cpus => "CPUs per task",
+            SubmitField::Memory => "Memory",
+            SubmitField::Gres => "GRES",
+            SubmitField::Dependency => "Dependency",
+        }
+    }
+
+    /// What `job` asked for, to submit it again. `None` where it is not
+    /// known or not in a form sbatch takes back; dependencies are never
+    /// carried over.
+    pub fn value_of(&self, job: &Job) -> Option<String> {
+        let value = match self {
+            SubmitField::JobName => Some(job.name.clone()),
+            SubmitField::Partition => Some(job.partition.clone()),
+            SubmitField::Time => job.time_limit.clone(),
+            SubmitField::Nodes => job.nodes.map(|nodes| nodes.to_string()),
+            SubmitField::Cpus => job.cpus_per_task.map(|cpus| cpus.to_string()),
+            SubmitField::Memory => job.memory.clone(),
+            SubmitField::Gres => job.gres.clone(),
+            SubmitField::Dependency => None,
+        }?;
+        self.validate(&value).is_ok().then_some(value)
+    }
+
+    /// Check `value` before it is handed to sbatch, which has the last
+    /// word on limits.
+    pub fn validate(&self, value: &str) -> Result<(), String> {
+        if value.is_empty() {
+            return Err("cannot be unset here; edit the script instead".to_string());
+        }
+        match self {
+            SubmitField::JobName => Ok(()),
+            SubmitField::Partition | SubmitField::Gres => {
+                if value.contains(char::is_whitespace) {
+                    Err("expected a value without spaces".to_string())
+                } else {
+                    Ok(())
+                }
+            }
+            SubmitField::Time => {
+                if value.eq_ignore_ascii_case("UNLIMITED") || parse_time_limit(value).is_some() {
+                    Ok(())
+                } else {
+                    Err("expected minutes, [D-]HH:MM:SS or UNLIMITED".to_string())
+                }
+            }
+            SubmitField::Nodes => {
+                let (min, max) = value.split_once('-').unwrap_or((value, value));
+                match (min.parse::<u32>(), max.parse::<u32>()) {
+                    (Ok(min), Ok(max)) if 0 < min && min <= max => Ok(()),
+                    _ => Err("expected a node count or a range like 2-4".to_string()),
+                }
+            }
+            SubmitField::Cpus => match value.parse::<u32>() {
+                Ok(cpus) if cpus > 0 => Ok(()),
+                _ => Err("expected a positive number".to_string()),
+            },
+            SubmitField::Memory => {
+                if parse_memory_mb(value).is_some() {
+                    Ok(())
+                } else {
+                    Err("expected a size like 4000, 500M or 16G".to_string())
+                }
+            }
+            SubmitField::Dependency => {
+                let valid = value.split([',', '?']).all(|dependency| {
+                    dependency == "singleton"
+                        || dependency.split_once(':').is_some_and(|(kind, ids)| {
+                            !kind.is_empty()
+                                && ids.split(':').all(|id| {
+                                    let id = id.split_once('+').map_or(id, |(id, _)| id);
+                                    !id.is_empty()
+                                        && id.bytes().all(|b| b.is_ascii_digit() || b == b'_')
+                                })
+                        })
+                });
+                if valid {
+                    Ok(())
+                } else {
+                    Err("expected e.g. afterok:123, afterany:123:124 or singleton".to_string())
+                }
+            }
+        }
+    }
+}
+
+/// sbatch options for what `job` asked for beyond the submit form's
+/// fields: its working directory, task count, memory per CPU, account and
+/// QOS. An array task is resubmitted as just that task.
+pub fn resubmit_options(job: &Job) -> Vec<(String, String)> {
+    [
+        ("chdir", job.working_dir.clone()),
+        ("ntasks", job.num_tasks.map(|tasks| tasks.to_string())),
+        ("mem-per-cpu", job.memory_per_cpu.clone()),
+        ("account", job.account.clone()),
+        ("qos", job.qos.clone()),
+        ("array", job.array_task_id.map(|task| task.to_string())),
+    ]
+    .into_iter()
+    .filter_map(|(option, value)| Some((option.to_string(), value?)))
+    .collect()
+}
+
+/// Counts sbatch assumes for a script that does not set them.
+const SBATCH_DEFAULTS: &[(&str, &str)] = &[("nodes", "1"), ("ntasks", "1"), ("cpus-per-task", "1")];
+
+/// Whether a script with `directives` asks for `option=value` by itself,
+/// so submitting it again need not pass the option: its last directive
+/// for `option` says the same, or it has none and sbatch defaults to
+/// `value`.
+pub fn script_requests(directives: &[SbatchDirective], option: &str, value: &str) -> bool {
+    let Some(directive) = directives.iter().rev().find(|d| d.option == option) else {
+        return SBATCH_DEFAULTS.contains(&(option, value));
+    };
+    let Some(requested) = directive.value.as_deref() else {
+        return false;
+    };
+    // scontrol reports some values in another form than they were asked for
+    match option {
+        "time" => {
+            requested == value
+                || parse_time_limit(requested).is_some_and(|t| parse_time_limit(value) == Some(t))
+        }
+        "mem" | "mem-per-cpu" => {
+            requested == value
+                || parse_memory_mb(requested).is_some_and(|mb| parse_memory_mb(value) == Some(mb))
+        }
+        _ => requested == value,
+    }
+}
+
+/// A size as `--mem` takes it, in megabytes: a number with an optional
+/// K, M, G or T suffix, M by default.
+pub fn parse_memory_mb(mem: &str) -> Option<u64> {
+    let split = mem.find(|c: char| !c.is_ascii_digit()).unwrap_or(mem.len());
+    let (number, unit) = mem.split_at(split);
+    let number: u64 = number.parse().ok()?;
+    let mb = match unit.to_ascii_uppercase().as_str() {
+        "" | "M" | "MB" => number,
+        "K" | "KB" => number.div_ceil(1024),
+        "G" | "GB" => number * 1024,
+        "T" | "TB" => number * 1024 * 1024,
+        _ => return None,
+    };
+    Some(mb)
+}
+
+/// One `#SBATCH` line of a batch script.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SbatchDirective {
+    /// 1-based, for the preview.
+    pub line: usize,
+    /// The long option name without dashes, e.g. "time" for `-t`.
+    pub option: String,
+    /// `None` for flags such as `--exclusive`.
+    pub value: Option<String>,
+}
+
+/// Long names of the short options seen in batch scripts.
+const SHORT_OPTIONS: &[(char, &str)] = &[
+    ('A', "account"),
+    ('a', "array"),
+    ('C', "constraint"),
+    ('c', "cpus-per-task"),
+    ('D', "chdir"),
+    ('d', "dependency"),
+    ('e', "error"),
+    ('G', "gpus"),
+    ('J', "job-name"),
+    ('N', "nodes"),
+    ('n', "ntasks"),
+    ('o', "output"),
+    ('p', "partition"),
+    ('q', "qos"),
+    ('t', "time"),
+    ('w', "nodelist"),
+];
+
+/// The `#SBATCH` directives of a batch script, read the way sbatch does:
+/// only up to the first line that is neither blank nor a comment.
+pub fn parse_sbatch_directives(script: &str) -> Vec<SbatchDirective> {
+    let mut directives = Vec::new();
+    for (i, line) in script.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if !line.starts_with('#') {
+            break;
+        }
+        let Some(rest) = line.strip_prefix("#SBATCH") else {
+            continue;
+        };
+        // Anything after a later `#` is a comment
+        let rest = rest.split_once('#').map_or(rest, |(rest, _)| rest).trim();
+        if let Some((option, value)) = parse_option(rest) {
+            directives.push(SbatchDirective {
+                line: i + 1,
+                option,
+                value,
+            });
+        }
+    }
+    directives
+}
+
+/// `--name=value`, `--name value`, `--flag`, `-X value` or `-Xvalue`.
+fn parse_option(text: &str) -> Option<(String, Option<String>)> {
+    let value = |value: &str| {
+        let value = value.trim().trim_matches('"');
+        (!value.is_empty()).then(|| value.to_string())
+    };
+    if let Some(long) = text.strip_prefix("--") {
+        let (name, rest) = long.split_once(['=', ' ', '\t']).unwrap_or((long, ""));
+        return (!name.is_empty()).then(|| (name.to_string(), value(rest)));
+    }
+    let short = text.strip_prefix('-')?;
+    let flag = short.chars().next()?;
+    let (_, name) = SHORT_OPTIONS.iter().find(|(c, _)| *c == flag)?;
+    Some((name.to_string(), value(&short[flag.len_utf8()..])))
+}
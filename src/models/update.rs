@@ -0,0 +1,123 @@
+use std::time::Duration;
+
+/// A job parameter that `scontrol update` can change, as offered by the
+/// edit form.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum JobField {
+    TimeLimit,
+    Partition,
+    Qos,
+    NumNodes,
+    Name,
+    Comment,
+    Nice,
+}
+
+impl JobField {
+    /// In the order the edit form lists them.
+    pub const EDITABLE: [JobField; 7] = [
+        JobField::TimeLimit,
+        JobField::Partition,
+        JobField::Qos,
+        JobField::NumNodes,
+        JobField::Name,
+        JobField::Comment,
+        JobField::Nice,
+    ];
+
+    /// The keyword used by both `scontrol show job` and `scontrol update`.
+    pub fn key(&self) -> &'static str {
+        match self {
+            JobField::TimeLimit => "TimeLimit",
+            JobField::Partition => "Partition",
+            JobField::Qos => "QOS",
+            JobField::NumNodes => "NumNodes",
+            JobField::Name => "JobName",
+            JobField::Comment => "Comment",
+            JobField::Nice => "Nice",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            JobField::TimeLimit => "Time limit",
+            JobField::Partition => "Partition",
+            JobField::Qos => "QOS",
+            JobField::NumNodes => "Nodes",
+            JobField::Name => "Name",
+            JobField::Comment => "Comment",
+            JobField::Nice => "Nice",
+        }
+    }
+
+    /// Check `value` before it is sent to the controller, which has the
+    /// last word on limits and permissions.
+    pub fn validate(&self, value: &str) -> Result<(), String> {
+        match self {
+            JobField::TimeLimit => {
+                if value.eq_ignore_ascii_case("UNLIMITED") || parse_time_limit(value).is_some() {
+                    Ok(())
+                } else {
+                    Err("expected minutes, [D-]HH:MM:SS or UNLIMITED".to_string())
+                }
+            }
+            JobField::Partition | JobField::Qos => {
+                if value.is_empty() || value.contains(char::is_whitespace) {
+                    Err("expected a name without spaces".to_string())
+                } else {
+                    Ok(())
+                }
+            }
+            JobField::NumNodes => {
+                let (min, max) = value.split_once('-').unwrap_or((value, value));
+                match (min.parse::<u32>(), max.parse::<u32>()) {
+                    (Ok(min), Ok(max)) if 0 < min && min <= max => Ok(()),
+                    _ => Err("expected a node count or a range like 2-4".to_string()),
+                }
+            }
+            JobField::Name => {
+                if value.is_empty() {
+                    Err("must not be empty".to_string())
+                } else {
+                    Ok(())
+                }
+            }
+            JobField::Comment => Ok(()),
+            JobField::Nice => match value.parse::<i64>() {
+                Ok(nice) if nice.abs() <= MAX_NICE => Ok(()),
+                _ => Err(format!("expected a number from -{0} to {0}", MAX_NICE)),
+            },
+        }
+    }
+}
+
+/// Largest adjustment `scontrol update Nice=` accepts, either way.
+const MAX_NICE: i64 = 2_147_483_645;
+
+/// A time limit in any format sbatch accepts: "minutes", "minutes:seconds",
+/// "hours:minutes:seconds", "days-hours", "days-hours:minutes" or
+/// "days-hours:minutes:seconds". `None` if malformed or unlimited.
+pub fn parse_time_limit(limit: &str) -> Option<Duration> {
+    let number = |part: &str| -> Option<u64> {
+        if part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        part.parse().ok()
+    };
+    let (days, rest) = match limit.split_once('-') {
+        Some((days, rest)) => (Some(number(days)?), rest),
+        None => (None, limit),
+    };
+    let parts = rest.split(':').map(number).collect::<Option<Vec<u64>>>()?;
+
+    let secs = match (days, parts.as_slice()) {
+        (Some(days), [hours]) => (days * 24 + hours) * 3600,
+        (Some(days), [hours, mins]) => ((days * 24 + hours) * 60 + mins) * 60,
+        (Some(days), [hours, mins, secs]) => ((days * 24 + hours) * 60 + mins) * 60 + secs,
+        (None, [mins]) => mins * 60,
+        (None, [mins, secs]) => mins * 60 + secs,
+        (None, [hours, mins, secs]) => (hours * 60 + mins) * 60 + secs,
+        _ => return None,
+    };
+    Some(Duration::from_secs(secs))
+}
@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+
+/// Live resource usage of a running job, as reported by `sstat`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobUsage {
+    pub job_id: String,
+    pub steps: Vec<StepUsage>,
+}
+
+/// Usage of one job step (`batch`, `extern`, `0`, ...). Sizes are in bytes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepUsage {
+    pub step_id: String,
+    /// Average CPU time per task, e.g. "00:12:03".
+    pub ave_cpu: Option<String>,
+    pub ave_rss: Option<u64>,
+    /// Peak resident memory of the largest task.
+    pub max_rss: Option<u64>,
+    pub max_rss_node: Option<String>,
+    pub max_vm_size: Option<u64>,
+    pub disk_read: Option<u64>,
+    pub disk_write: Option<u64>,
+}
+
+impl StepUsage {
+    pub fn new(step_id: String) -> Self {
+        Self {
+            step_id,
+            ave_cpu: None,
+            ave_rss: None,
+            max_rss: None,
+            max_rss_node: None,
+            max_vm_size: None,
+            disk_read: None,
+            disk_write: None,
+        }
+    }
+}
+
+impl JobUsage {
+    /// The highest MaxRSS of any step, the figure to hold against the
+    /// job's memory request.
+    pub fn peak_rss(&self) -> Option<u64> {
+        self.steps.iter().filter_map(|step| step.max_rss).max()
+    }
+}
@@ -1,71 +1,550 @@
 use anyhow::{Context, Result};
 use async_trait::async_trait;
 use std::process::Command;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tokio::process::Command as TokioCommand;
 
-use crate::slurm::executor::SlurmExecutor;
+use crate::slurm::executor::{Backend, CancelOptions, SignalScope, SlurmExecutor};
+use crate::slurm::remote::CommandWrapper;
 
-pub struct SlurmProcess;
-
-#[async_trait]
-impl SlurmExecutor for SlurmProcess {
-    async fn squeue(&self, user: Option<&str>, partition: Option<&str>) -> Result<String> {
-        let mut cmd = TokioCommand::new("squeue");
+/// How long a Slurm command may take and how often it is retried.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CommandLimits {
+    /// The child is killed once this expires.
+    pub timeout: Duration,
+    /// Extra attempts after a transient failure.
+    pub retries: u32,
+    /// Wait before the first retry; doubled for each one after that.
+    pub backoff: Duration,
+}
 
-        if let Some(user) = user {
-            cmd.arg("-u").arg(user);
+impl Default for CommandLimits {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(30),
+            retries: 2,
+            backoff: Duration::from_secs(1),
         }
+    }
+}
 
-        if let Some(partition) = partition {
-            cmd.arg("-p").arg(partition);
+/// Errors worth retrying: the controller is busy rather than the request
+/// being wrong.
+const TRANSIENT_ERRORS: &[&str] = &[
+    "timed out",
+    "Unable to contact slurm controller",
+    "Slurm backup controller in standby mode",
+];
+
+fn is_transient(err: &anyhow::Error) -> bool {
+    let message = err.to_string();
+    TRANSIENT_ERRORS
+        .iter()
+        .any(|pattern| message.contains(pattern))
+}
+
+/// How a Slurm without working `--json` output fails: the option is not
+/// known, or the serializer plugin behind it is missing.
+const JSON_UNSUPPORTED_ERRORS: &[&str] = &[
+    "unrecognized option",
+    "invalid option",
+    "serializer",
+    "data_parser",
+];
+
+/// True when `--json` output is unusable, rather than the request failing:
+/// the error is one of [`JSON_UNSUPPORTED_ERRORS`] or the output did not
+/// parse.
+pub fn json_unsupported(err: &anyhow::Error) -> bool {
+    if err.downcast_ref::<serde_json::Error>().is_some() {
+        return true;
+    }
+    let message = format!("{:#}", err);
+    JSON_UNSUPPORTED_ERRORS
+        .iter()
+        .any(|pattern| message.contains(pattern))
+}
+
+/// Runs the Slurm CLI tools, either locally or through a [`CommandWrapper`]
+/// such as ssh to a login node.
+#[derive(Debug, Clone, Default)]
+pub struct SlurmProcess {
+    wrapper: Option<CommandWrapper>,
+    limits: CommandLimits,
+    /// Notices of the calls still retrying, keyed by call, latest last.
+    /// Calls run concurrently, so one finishing must not clear another's.
+    status: Arc<Mutex<Vec<(u64, String)>>>,
+    next_call: Arc<AtomicU64>,
+}
+
+impl SlurmProcess {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run every Slurm command, and read job logs, through `wrapper`.
+    pub fn wrapped(wrapper: CommandWrapper) -> Self {
+        Self {
+            wrapper: Some(wrapper),
+            ..Self::default()
         }
+    }
 
-        cmd.arg("--format=%i,%j,%u,%t,%M,%N,%P");
+    pub fn with_limits(mut self, limits: CommandLimits) -> Self {
+        self.limits = limits;
+        self
+    }
 
-        let output = cmd.output().await.context("Failed to execute squeue")?;
+    fn set_status(&self, call: u64, status: Option<String>) {
+        let mut notices = self.status.lock().unwrap();
+        notices.retain(|(id, _)| *id != call);
+        if let Some(status) = status {
+            notices.push((call, status));
+        }
+    }
 
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            anyhow::bail!("squeue failed: {}", stderr);
+    fn command(&self, program: &str, args: &[String]) -> TokioCommand {
+        match &self.wrapper {
+            Some(wrapper) => wrapper.command(program, args),
+            None => {
+                let mut cmd = TokioCommand::new(program);
+                cmd.args(args);
+                cmd
+            }
         }
+    }
 
-        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    /// Run `program args...` and return its stdout, retrying transient
+    /// failures with exponential backoff. `what` names the command in error
+    /// messages, e.g. "scontrol show job".
+    async fn run(&self, program: &str, args: &[String], what: &str) -> Result<String> {
+        let call = self.next_call.fetch_add(1, Ordering::Relaxed);
+        let mut attempt = 0;
+        loop {
+            let result = self.run_once(program, args, what).await;
+            match result {
+                Err(err) if attempt < self.limits.retries && is_transient(&err) => {
+                    let delay = self.limits.backoff * 2u32.pow(attempt);
+                    attempt += 1;
+                    self.set_status(
+                        call,
+                        Some(format!(
+                            "controller slow / retrying in {}s ({}/{})",
+                            delay.as_secs_f32().ceil(),
+                            attempt,
+                            self.limits.retries
+                        )),
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                result => {
+                    self.set_status(call, None);
+                    return result;
+                }
+            }
+        }
     }
 
-    async fn scontrol_show_job(&self, job_id: &str) -> Result<String> {
-        let output = TokioCommand::new("scontrol")
-            .arg("show")
-            .arg("job")
-            .arg(job_id)
-            .output()
+    async fn run_once(&self, program: &str, args: &[String], what: &str) -> Result<String> {
+        let mut command = self.command(program, args);
+        // Dropping the output future on timeout then kills the child
+        command.kill_on_drop(true);
+
+        let output = tokio::time::timeout(self.limits.timeout, command.output())
             .await
-            .context("Failed to execute scontrol")?;
+            .map_err(|_| {
+                anyhow::anyhow!(
+                    "{} timed out after {}s",
+                    what,
+                    self.limits.timeout.as_secs_f32()
+                )
+            })?
+            .with_context(|| format!("Failed to execute {}", program))?;
 
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
-            anyhow::bail!("scontrol show job failed: {}", stderr);
+            anyhow::bail!("{} failed: {}", what, stderr);
         }
 
         Ok(String::from_utf8_lossy(&output.stdout).to_string())
     }
+}
 
-    async fn scancel(&self, job_id: &str) -> Result<()> {
-        let output = TokioCommand::new("scancel")
-            .arg(job_id)
-            .output()
-            .await
-            .context("Failed to execute scancel")?;
+fn filter_args(user: Option<&str>, partition: Option<&str>, clusters: Option<&str>) -> Vec<String> {
+    let mut args = cluster_args(clusters);
 
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            anyhow::bail!("scancel failed: {}", stderr);
+    if let Some(user) = user {
+        args.extend(["-u".to_string(), user.to_string()]);
+    }
+
+    if let Some(partition) = partition {
+        args.extend(["-p".to_string(), partition.to_string()]);
+    }
+
+    args
+}
+
+fn cluster_args(clusters: Option<&str>) -> Vec<String> {
+    match clusters {
+        Some(clusters) => vec!["-M".to_string(), clusters.to_string()],
+        None => Vec::new(),
+    }
+}
+
+/// `srun` arguments for [`SlurmExecutor::shell_command`]. `--overlap` lets
+/// the shell share the CPUs the job is already using.
+pub(crate) fn shell_args(job_id: &str, cluster: Option<&str>, node: Option<&str>) -> Vec<String> {
+    let mut args = cluster_args(cluster);
+    args.extend([format!("--jobid={}", job_id), "--overlap".to_string()]);
+    if let Some(node) = node {
+        args.extend(["-w".to_string(), node.to_string()]);
+    }
+    args.extend(["--pty".to_string(), "bash".to_string()]);
+    args
+}
+
+#[async_trait]
+impl SlurmExecutor for SlurmProcess {
+    async fn squeue(
+        &self,
+        user: Option<&str>,
+        partition: Option<&str>,
+        clusters: Option<&str>,
+    ) -> Result<String> {
+        let mut args = filter_args(user, partition, clusters);
+        args.push("--format=%i,%j,%u,%t,%M,%N,%P".to_string());
+        self.run("squeue", &args, "squeue").await
+    }
+
+    async fn scontrol_show_job(&self, job_id: &str, cluster: Option<&str>) -> Result<String> {
+        let mut args = cluster_args(cluster);
+        args.extend(["show", "job", job_id].map(String::from));
+        self.run("scontrol", &args, "scontrol show job").await
+    }
+
+    async fn scontrol_show_jobs(&self, cluster: Option<&str>) -> Result<String> {
+        let mut args = cluster_args(cluster);
+        args.extend(["show", "job"].map(String::from));
+        self.run("scontrol", &args, "scontrol show job").await
+    }
+
+    async fn squeue_json(
+        &self,
+        user: Option<&str>,
+        partition: Option<&str>,
+        clusters: Option<&str>,
+    ) -> Result<String> {
+        let mut args = filter_args(user, partition, clusters);
+        args.push("--json".to_string());
+        self.run("squeue", &args, "squeue --json").await
+    }
+
+    async fn squeue_start(
+        &self,
+        user: Option<&str>,
+        partition: Option<&str>,
+        clusters: Option<&str>,
+    ) -> Result<String> {
+        let mut args = filter_args(user, partition, clusters);
+        args.push("--start".to_string());
+        args.push("--format=%i|%u|%P|%S".to_string());
+        self.run("squeue", &args, "squeue --start").await
+    }
+
+    async fn version(&self) -> Result<String> {
+        let args = ["--version".to_string()];
+        self.run("squeue", &args, "squeue --version").await
+    }
+
+    async fn scancel(
+        &self,
+        job_id: &str,
+        cluster: Option<&str>,
+        options: &CancelOptions,
+    ) -> Result<()> {
+        let mut args = cluster_args(cluster);
+        if let Some(signal) = &options.signal {
+            args.push(format!("--signal={}", signal));
+        }
+        match options.scope {
+            SignalScope::Batch => args.push("--batch".to_string()),
+            SignalScope::Full => args.push("--full".to_string()),
+            SignalScope::Steps | SignalScope::Step(_) => {}
         }
+        args.push(options.target(job_id));
+        self.run("scancel", &args, "scancel").await?;
+        Ok(())
+    }
 
+    async fn hold(&self, job_id: &str, cluster: Option<&str>) -> Result<()> {
+        let mut args = cluster_args(cluster);
+        args.extend(["hold", job_id].map(String::from));
+        self.run("scontrol", &args, "scontrol hold").await?;
         Ok(())
     }
+
+    async fn release(&self, job_id: &str, cluster: Option<&str>) -> Result<()> {
+        let mut args = cluster_args(cluster);
+        args.extend(["release", job_id].map(String::from));
+        self.run("scontrol", &args, "scontrol release").await?;
+        Ok(())
+    }
+
+    async fn requeue(&self, job_id: &str, cluster: Option<&str>) -> Result<()> {
+        let mut args = cluster_args(cluster);
+        args.extend(["requeue", job_id].map(String::from));
+        self.run("scontrol", &args, "scontrol requeue").await?;
+        Ok(())
+    }
+
+    async fn update_job(
+        &self,
+        job_id: &str,
+        cluster: Option<&str>,
+        changes: &[(String, String)],
+    ) -> Result<()> {
+        let mut args = cluster_args(cluster);
+        args.push("update".to_string());
+        args.push(format!("JobId={}", job_id));
+        args.extend(
+            changes
+                .iter()
+                .map(|(key, value)| format!("{}={}", key, value)),
+        );
+        self.run("scontrol", &args, "scontrol update").await?;
+        Ok(())
+    }
+
+    async fn sbatch(
+        &self,
+        script: &str,
+        cluster: Option<&str>,
+        options: &[(String, String)],
+    ) -> Result<String> {
+        let mut args = cluster_args(cluster);
+        args.push("--parsable".to_string());
+        args.extend(
+            options
+                .iter()
+                .map(|(option, value)| format!("--{}={}", option, value)),
+        );
+        args.push(script.to_string());
+        // Not retried: a submission that timed out may still have gone through
+        let output = self.run_once("sbatch", &args, "sbatch").await?;
+        // "<job_id>" or "<job_id>;<cluster>"
+        let job_id = output.trim().split(';').next().unwrap_or_default();
+        if job_id.is_empty() {
+            anyhow::bail!("sbatch did not report a job id");
+        }
+        Ok(job_id.to_string())
+    }
+
+    async fn sacct(
+        &self,
+        user: Option<&str>,
+        partition: Option<&str>,
+        clusters: Option<&str>,
+        window: Duration,
+    ) -> Result<String> {
+        let mut args = cluster_args(clusters);
+
+        match user {
+            Some(user) => args.extend(["-u".to_string(), user.to_string()]),
+            None => args.push("--allusers".to_string()),
+        }
+
+        if let Some(partition) = partition {
+            args.extend(["-r".to_string(), partition.to_string()]);
+        }
+
+        args.extend([
+            format!("--starttime=now-{}seconds", window.as_secs()),
+            "--parsable2".to_string(),
+            format!("--format={}", SACCT_FORMAT),
+        ]);
+
+        self.run("sacct", &args, "sacct").await
+    }
+
+    async fn sacct_job(&self, job_id: &str, cluster: Option<&str>) -> Result<String> {
+        // With -j, sacct looks back to the epoch rather than to midnight
+        let mut args = cluster_args(cluster);
+        args.extend([
+            "-j".to_string(),
+            job_id.to_string(),
+            "--parsable2".to_string(),
+            format!("--format={}", SACCT_FORMAT),
+        ]);
+        self.run("sacct", &args, "sacct -j").await
+    }
+
+    async fn sstat(&self, job_id: &str, cluster: Option<&str>) -> Result<String> {
+        let mut args = cluster_args(cluster);
+        args.extend([
+            "--allsteps".to_string(),
+            "--parsable2".to_string(),
+            format!("--format={}", SSTAT_FORMAT),
+            "-j".to_string(),
+            job_id.to_string(),
+        ]);
+        self.run("sstat", &args, "sstat").await
+    }
+
+    async fn sinfo(&self, partition: Option<&str>, clusters: Option<&str>) -> Result<String> {
+        let mut args = cluster_args(clusters);
+        args.push("--Node".to_string());
+        if let Some(partition) = partition {
+            args.extend(["-p".to_string(), partition.to_string()]);
+        }
+        args.push(format!("--format={}", SINFO_FORMAT));
+        self.run("sinfo", &args, "sinfo").await
+    }
+
+    async fn scontrol_show_nodes(&self, cluster: Option<&str>) -> Result<String> {
+        let mut args = cluster_args(cluster);
+        args.extend(["show", "node"].map(String::from));
+        self.run("scontrol", &args, "scontrol show node").await
+    }
+
+    async fn sinfo_partitions(&self, clusters: Option<&str>) -> Result<String> {
+        let mut args = cluster_args(clusters);
+        args.push(format!("--format={}", PARTITION_FORMAT));
+        self.run("sinfo", &args, "sinfo").await
+    }
+
+    async fn scontrol_show_partitions(&self, cluster: Option<&str>) -> Result<String> {
+        let mut args = cluster_args(cluster);
+        args.extend(["show", "partition"].map(String::from));
+        self.run("scontrol", &args, "scontrol show partition").await
+    }
+
+    async fn sprio(&self, partition: Option<&str>, cluster: Option<&str>) -> Result<String> {
+        let mut args = filter_args(None, partition, cluster);
+        args.push("--noheader".to_string());
+        args.push(format!("--format={}", SPRIO_FORMAT));
+        self.run("sprio", &args, "sprio").await
+    }
+
+    async fn sshare(&self, user: Option<&str>, cluster: Option<&str>) -> Result<String> {
+        let mut args = cluster_args(cluster);
+        match user {
+            Some(user) => args.extend(["-u".to_string(), user.to_string()]),
+            None => args.push("-U".to_string()),
+        }
+        args.push("--parsable2".to_string());
+        args.push(format!("--format={}", SSHARE_FORMAT));
+        self.run("sshare", &args, "sshare").await
+    }
+
+    async fn clusters(&self) -> Result<String> {
+        let args = [
+            "--noheader",
+            "--parsable2",
+            "show",
+            "clusters",
+            "format=Cluster",
+        ]
+        .map(String::from);
+        self.run("sacctmgr", &args, "sacctmgr show clusters").await
+    }
+
+    fn status(&self) -> Option<String> {
+        let notices = self.status.lock().unwrap();
+        notices.last().map(|(_, status)| status.clone())
+    }
+
+    fn shell_command(
+        &self,
+        job_id: &str,
+        cluster: Option<&str>,
+        node: Option<&str>,
+    ) -> Result<Vec<String>> {
+        let args = shell_args(job_id, cluster, node);
+        Ok(match &self.wrapper {
+            Some(wrapper) => wrapper.interactive_command("srun", &args),
+            None => std::iter::once("srun".to_string()).chain(args).collect(),
+        })
+    }
+
+    async fn tail_file(&self, path: &str, lines: usize) -> Result<String> {
+        if self.wrapper.is_none() {
+            return read_tail(path, lines).await;
+        }
+
+        let args = [
+            "-n".to_string(),
+            lines.to_string(),
+            "--".to_string(),
+            path.to_string(),
+        ];
+        let tail = self.run("tail", &args, "tail").await?;
+        // Match read_tail, which drops the final newline
+        Ok(tail.trim_end_matches('\n').to_string())
+    }
+
+    fn is_remote(&self) -> bool {
+        self.wrapper.is_some()
+    }
+}
+
+/// Read the last `lines` lines of a local file.
+pub async fn read_tail(path: &str, lines: usize) -> Result<String> {
+    let content = tokio::fs::read_to_string(path)
+        .await
+        .with_context(|| format!("Failed to read {}", path))?;
+    let all: Vec<&str> = content.lines().collect();
+    let start = all.len().saturating_sub(lines);
+    Ok(all[start..].join("\n"))
 }
 
-pub fn check_slurm_available() -> bool {
+/// Columns requested from `sacct`. [`SlurmParser::parse_sacct_output`] maps
+/// them by header name, so the order here is not significant.
+///
+/// [`SlurmParser::parse_sacct_output`]: crate::slurm::SlurmParser::parse_sacct_output
+pub const SACCT_FORMAT: &str = "Cluster,JobID,JobName,User,Partition,State,ExitCode,Elapsed,Timelimit,Submit,Start,End,NNodes,AllocCPUS,NodeList,ReqMem,WorkDir,Reason,TotalCPU,MaxRSS";
+
+/// Columns requested from `sstat`, mapped by header name like
+/// [`SACCT_FORMAT`].
+pub const SSTAT_FORMAT: &str =
+    "JobID,AveCPU,AveRSS,MaxRSS,MaxRSSNode,MaxVMSize,AveDiskRead,AveDiskWrite";
+
+/// Fields requested from `sinfo --Node`, `|` separated because reasons
+/// contain spaces. [`SlurmParser::parse_sinfo_output`] maps the columns by
+/// their headers (NODELIST, PARTITION, STATE, CPUS(A/I/O/T), MEMORY,
+/// FREE_MEM, GRES, AVAIL_FEATURES, REASON).
+///
+/// [`SlurmParser::parse_sinfo_output`]: crate::slurm::SlurmParser::parse_sinfo_output
+pub const SINFO_FORMAT: &str = "%N|%P|%T|%C|%m|%e|%G|%f|%E";
+
+/// Fields requested from `sinfo` for the partition overview; without a
+/// node or state field sinfo prints one line per partition.
+/// [`SlurmParser::parse_sinfo_partitions`] maps the columns by their headers
+/// (PARTITION, AVAIL, TIMELIMIT, DEFAULTTIME, NODES(A/I/O/T), CPUS(A/I/O/T)).
+///
+/// [`SlurmParser::parse_sinfo_partitions`]: crate::slurm::SlurmParser::parse_sinfo_partitions
+pub const PARTITION_FORMAT: &str = "%P|%a|%l|%L|%F|%C";
+
+/// Fields requested from `sprio`. sprio heads both the partition name and
+/// its factor PARTITION, so [`SlurmParser::parse_sprio_output`] reads the
+/// columns by position (JOBID, USER, PARTITION, PRIORITY, AGE, FAIRSHARE,
+/// JOBSIZE, PARTITION, QOS, TRES) and the header is left out.
+///
+/// [`SlurmParser::parse_sprio_output`]: crate::slurm::SlurmParser::parse_sprio_output
+pub const SPRIO_FORMAT: &str = "%i|%u|%r|%Y|%A|%F|%J|%P|%Q|%T";
+
+/// Columns requested from `sshare`, mapped by header name like
+/// [`SACCT_FORMAT`].
+pub const SSHARE_FORMAT: &str = "Account,User,RawShares,NormShares,RawUsage,EffectvUsage,FairShare";
+
+/// Whether the tools `backend` needs are present. The REST backend needs
+/// nothing locally; its connection is checked by the first request.
+pub fn check_slurm_available(backend: Backend) -> bool {
+    if backend == Backend::Rest {
+        return true;
+    }
+
     Command::new("which")
         .arg("squeue")
         .output()
@@ -1,9 +1,199 @@
 use anyhow::Result;
 use async_trait::async_trait;
+use std::time::Duration;
 
+/// Where Slurm data comes from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, clap::ValueEnum)]
+pub enum Backend {
+    /// Run squeue/scontrol/scancel on this machine.
+    #[default]
+    Cli,
+    /// Talk to slurmrestd over HTTP.
+    Rest,
+}
+
+/// Which of a job's processes [`SlurmExecutor::scancel`] signals.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum SignalScope {
+    /// scancel's default: cancelling ends the whole job, while a signal
+    /// only reaches the job's steps, not the batch script.
+    #[default]
+    Steps,
+    /// `--batch`: only the batch script's shell.
+    Batch,
+    /// `--full`: the batch script and every step.
+    Full,
+    /// One step, e.g. "0", signalled as `<job_id>.<step>`.
+    Step(String),
+}
+
+impl SignalScope {
+    pub fn label(&self) -> String {
+        match self {
+            SignalScope::Steps => "job steps".to_string(),
+            SignalScope::Batch => "batch script".to_string(),
+            SignalScope::Full => "batch script and steps".to_string(),
+            SignalScope::Step(step) => format!("step {}", step),
+        }
+    }
+}
+
+/// What [`SlurmExecutor::scancel`] does; the default cancels the job.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CancelOptions {
+    /// A signal name without the SIG prefix, e.g. "USR1".
+    pub signal: Option<String>,
+    pub scope: SignalScope,
+}
+
+impl CancelOptions {
+    pub fn signal(signal: &str, scope: SignalScope) -> Self {
+        Self {
+            signal: Some(signal.to_string()),
+            scope,
+        }
+    }
+
+    /// The job id as scancel takes it, naming the step if there is one.
+    pub fn target(&self, job_id: &str) -> String {
+        match &self.scope {
+            SignalScope::Step(step) => format!("{}.{}", job_id, step),
+            _ => job_id.to_string(),
+        }
+    }
+}
+
+/// `Key=Value` pairs for [`SlurmExecutor::update_job`], e.g. `("TimeLimit",
+/// "30")`.
+pub type JobUpdate = Vec<(String, String)>;
+
+/// Multi-cluster arguments: `clusters` is handed to `-M` as given (a name,
+/// a comma separated list, or "all"), while a single `cluster` routes a call
+/// about one job to the controller that owns it. `None` means the local
+/// cluster.
 #[async_trait]
 pub trait SlurmExecutor: Send + Sync {
-    async fn squeue(&self, user: Option<&str>, partition: Option<&str>) -> Result<String>;
-    async fn scontrol_show_job(&self, job_id: &str) -> Result<String>;
-    async fn scancel(&self, job_id: &str) -> Result<()>;
+    async fn squeue(
+        &self,
+        user: Option<&str>,
+        partition: Option<&str>,
+        clusters: Option<&str>,
+    ) -> Result<String>;
+    async fn scontrol_show_job(&self, job_id: &str, cluster: Option<&str>) -> Result<String>;
+    /// `scontrol show job` for every job on `cluster`, records separated by
+    /// blank lines. scontrol's `-M` takes a single cluster.
+    async fn scontrol_show_jobs(&self, cluster: Option<&str>) -> Result<String>;
+    /// `squeue --json`; only called when [`Self::version`] reports 21.08+.
+    async fn squeue_json(
+        &self,
+        user: Option<&str>,
+        partition: Option<&str>,
+        clusters: Option<&str>,
+    ) -> Result<String>;
+    /// `squeue --start`: pending jobs with the scheduler's expected start,
+    /// in comma separated JOBID, USER, PARTITION and START_TIME columns.
+    async fn squeue_start(
+        &self,
+        user: Option<&str>,
+        partition: Option<&str>,
+        clusters: Option<&str>,
+    ) -> Result<String>;
+    /// Version banner of the installed Slurm, e.g. "slurm 23.02.6".
+    async fn version(&self) -> Result<String>;
+    /// Cancel a job, or with a signal in `options`, send it that signal.
+    async fn scancel(
+        &self,
+        job_id: &str,
+        cluster: Option<&str>,
+        options: &CancelOptions,
+    ) -> Result<()>;
+    /// `scontrol hold`: keep a pending job from starting.
+    async fn hold(&self, job_id: &str, cluster: Option<&str>) -> Result<()>;
+    /// `scontrol release`: let a held job be scheduled again.
+    async fn release(&self, job_id: &str, cluster: Option<&str>) -> Result<()>;
+    /// `scontrol requeue`: put a running or finished batch job back in the
+    /// queue to run again from the start.
+    async fn requeue(&self, job_id: &str, cluster: Option<&str>) -> Result<()>;
+    /// `scontrol update JobId=<job_id> Key=Value ...`. The error carries the
+    /// controller's reason for a rejection.
+    async fn update_job(
+        &self,
+        job_id: &str,
+        cluster: Option<&str>,
+        changes: &[(String, String)],
+    ) -> Result<()>;
+    /// `sbatch --parsable <script>` with `options` as `--<option>=<value>`
+    /// overrides of the script's `#SBATCH` directives. Returns the new job's
+    /// id. The script path is read wherever the Slurm commands run.
+    async fn sbatch(
+        &self,
+        script: &str,
+        cluster: Option<&str>,
+        options: &[(String, String)],
+    ) -> Result<String>;
+    /// Accounting records for jobs that started within the last `window`.
+    async fn sacct(
+        &self,
+        user: Option<&str>,
+        partition: Option<&str>,
+        clusters: Option<&str>,
+        window: Duration,
+    ) -> Result<String>;
+    /// Accounting records for one job whenever it ran, in the same format
+    /// as [`Self::sacct`]. An array job id covers every task of the array.
+    async fn sacct_job(&self, job_id: &str, cluster: Option<&str>) -> Result<String>;
+    /// `sstat --allsteps` for a running job in [`SSTAT_FORMAT`].
+    ///
+    /// [`SSTAT_FORMAT`]: crate::slurm::SSTAT_FORMAT
+    async fn sstat(&self, job_id: &str, cluster: Option<&str>) -> Result<String>;
+    /// `sinfo -N` in [`SINFO_FORMAT`]: one line per node and partition.
+    ///
+    /// [`SINFO_FORMAT`]: crate::slurm::SINFO_FORMAT
+    async fn sinfo(&self, partition: Option<&str>, clusters: Option<&str>) -> Result<String>;
+    /// `scontrol show node` for every node on `cluster`, records separated
+    /// by blank lines.
+    async fn scontrol_show_nodes(&self, cluster: Option<&str>) -> Result<String>;
+    /// `sinfo` in [`PARTITION_FORMAT`]: one line per partition.
+    ///
+    /// [`PARTITION_FORMAT`]: crate::slurm::PARTITION_FORMAT
+    async fn sinfo_partitions(&self, clusters: Option<&str>) -> Result<String>;
+    /// `scontrol show partition` for every partition on `cluster`, records
+    /// separated by blank lines.
+    async fn scontrol_show_partitions(&self, cluster: Option<&str>) -> Result<String>;
+    /// `sprio` in [`SPRIO_FORMAT`], without a header: the priority factors
+    /// of every pending job, optionally in one partition.
+    ///
+    /// [`SPRIO_FORMAT`]: crate::slurm::SPRIO_FORMAT
+    async fn sprio(&self, partition: Option<&str>, cluster: Option<&str>) -> Result<String>;
+    /// `sshare` in [`SSHARE_FORMAT`] for `user`, or the calling user.
+    ///
+    /// [`SSHARE_FORMAT`]: crate::slurm::SSHARE_FORMAT
+    async fn sshare(&self, user: Option<&str>, cluster: Option<&str>) -> Result<String>;
+    /// Cluster names known to accounting, one per line.
+    async fn clusters(&self) -> Result<String>;
+    /// The last `lines` lines of a job log, read wherever the jobs run.
+    async fn tail_file(&self, path: &str, lines: usize) -> Result<String>;
+    /// The command line of an interactive shell inside a running job's
+    /// allocation, `srun --jobid=<job_id> --overlap --pty bash`, on `node`
+    /// if given. It is not run here: the caller hands it the terminal.
+    fn shell_command(
+        &self,
+        job_id: &str,
+        cluster: Option<&str>,
+        node: Option<&str>,
+    ) -> Result<Vec<String>>;
+    /// A note for the status bar while a call is being retried.
+    fn status(&self) -> Option<String> {
+        None
+    }
+    /// Whether Slurm runs on another machine, so the paths it reports,
+    /// such as batch scripts, are not this machine's.
+    fn is_remote(&self) -> bool {
+        false
+    }
+    /// Whether [`Self::scontrol_show_job`] is served. The edit, resubmit
+    /// and array summary popups build on it, so they are hidden without.
+    fn shows_job_details(&self) -> bool {
+        true
+    }
 }
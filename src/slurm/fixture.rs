@@ -1,23 +1,76 @@
 use anyhow::{Context, Result};
 use async_trait::async_trait;
-use std::path::PathBuf;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
 
-use crate::slurm::executor::SlurmExecutor;
+use crate::models::parse_task_ranges;
+use crate::slurm::commands::{read_tail, shell_args};
+use crate::slurm::executor::{CancelOptions, JobUpdate, SlurmExecutor};
 
 /// A fake [`SlurmExecutor`] that reads canned outputs from a fixture directory.
 ///
 /// Layout:
 /// ```text
 /// <fixture_dir>/
-///   squeue.txt              # squeue output for any user/partition filter
+///   squeue.txt              # squeue output; -u/-p filters are applied to it
+///   squeue.<n>.txt          # optional; output for the n-th squeue call
+///   squeue_start.txt        # optional; squeue --start output, filtered like squeue.txt
 ///   scontrol/<job_id>.txt   # one file per job_id
+///   scontrol.txt            # optional; `scontrol show job` for all jobs,
+///                           # otherwise the scontrol/*.txt files joined
+///   sacct.txt               # sacct --parsable2 output for any window
+///   sacct/<job_id>.txt      # optional; sacct -j output, otherwise
+///                           # sacct.txt narrowed to the job
+///   clusters.txt            # optional; sacctmgr show clusters output
+///   sstat/<job_id>.txt      # optional; sstat output for a running job
+///   sinfo.txt               # sinfo --Node output; -p is applied to it
+///   scontrol_nodes.txt      # optional; `scontrol show node` for all nodes
+///   sinfo_partitions.txt    # sinfo output, one line per partition
+///   scontrol_partitions.txt # optional; `scontrol show partition` output
+///   sprio.txt               # optional; sprio output without header; -p is applied
+///   sshare.txt              # optional; sshare --parsable2 output; -u is applied
+///   version.txt             # optional; `squeue --version` output
+///   sbatch.txt              # optional; the job id sbatch reports
+///   squeue.json             # squeue --json output, used when version.txt says 21.08+
+///   squeue.<n>.json         # optional; output for the n-th squeue --json call
+///   <cluster>/              # optional; any of the above for calls naming
+///                           # that cluster (or `-M` list), read in preference
 /// ```
 ///
-/// `scancel` calls are recorded in [`Self::cancelled`] for test assertions.
+/// `lazyslurm --record <dir>` writes this layout from a live cluster; see
+/// [`SlurmRecorder`](crate::slurm::SlurmRecorder).
+///
+/// Numbered snapshots model jobs changing across refreshes: each squeue
+/// call reads the highest-numbered snapshot not past the number of earlier
+/// calls, so the last one sticks. Without any, `squeue.txt` is used.
+///
+/// `scancel` calls are recorded in [`Self::cancelled`] for test assertions,
+/// and cancelled jobs (with all their array tasks, or those of an
+/// `<array>_[<ranges>]` id) disappear from later squeue output, as they do
+/// once Slurm purges them. Signals go to [`Self::signalled`] instead and
+/// leave the job listed. `scontrol hold`, `release` and `requeue` are
+/// recorded the same way but leave the output alone, as are the changes
+/// asked of `scontrol update` in [`Self::updated`] and the scripts handed
+/// to sbatch in [`Self::submitted`]. Shells attached to jobs are noted in
+/// [`Self::attached`], and their srun command line is returned as the CLI
+/// would build it.
 pub struct SlurmFixture {
     pub fixture_dir: PathBuf,
     pub cancelled: Mutex<Vec<String>>,
+    pub signalled: Mutex<Vec<(String, CancelOptions)>>,
+    pub held: Mutex<Vec<String>>,
+    pub released: Mutex<Vec<String>>,
+    pub requeued: Mutex<Vec<String>>,
+    /// Job id and `Key=Value` changes of each `scontrol update`.
+    pub updated: Mutex<Vec<(String, JobUpdate)>>,
+    /// Script path and option overrides of each `sbatch`.
+    pub submitted: Mutex<Vec<(String, JobUpdate)>>,
+    /// Job id and node of each shell attached to a job.
+    pub attached: Mutex<Vec<(String, Option<String>)>>,
+    squeue_calls: AtomicUsize,
 }
 
 impl SlurmFixture {
@@ -25,29 +78,473 @@ impl SlurmFixture {
         Self {
             fixture_dir: fixture_dir.into(),
             cancelled: Mutex::new(Vec::new()),
+            signalled: Mutex::new(Vec::new()),
+            held: Mutex::new(Vec::new()),
+            released: Mutex::new(Vec::new()),
+            requeued: Mutex::new(Vec::new()),
+            updated: Mutex::new(Vec::new()),
+            submitted: Mutex::new(Vec::new()),
+            attached: Mutex::new(Vec::new()),
+            squeue_calls: AtomicUsize::new(0),
+        }
+    }
+
+    /// `file` for a call to `cluster`: in the directory of that name when
+    /// it is there, otherwise at the top.
+    fn path(&self, cluster: Option<&str>, file: &str) -> PathBuf {
+        cluster
+            .map(|cluster| self.fixture_dir.join(cluster).join(file))
+            .filter(|path| path.exists())
+            .unwrap_or_else(|| self.fixture_dir.join(file))
+    }
+
+    /// Read `squeue.<n>.<ext>` for this call, falling back to `squeue.<ext>`,
+    /// from the directory of `clusters` if it has either.
+    fn squeue_snapshot(&self, clusters: Option<&str>, ext: &str) -> Result<String> {
+        let call = self.squeue_calls.fetch_add(1, Ordering::SeqCst);
+        let dir = clusters
+            .map(|clusters| self.fixture_dir.join(clusters))
+            .filter(|dir| {
+                dir.join(format!("squeue.{}", ext)).exists()
+                    || !snapshot_numbers(dir, ext).is_empty()
+            })
+            .unwrap_or_else(|| self.fixture_dir.clone());
+        let path = snapshot_numbers(&dir, ext)
+            .into_iter()
+            .filter(|n| *n <= call)
+            .max()
+            .map(|n| dir.join(format!("squeue.{}.{}", n, ext)))
+            .unwrap_or_else(|| dir.join(format!("squeue.{}", ext)));
+        std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read fixture: {}", path.display()))
+    }
+
+    fn is_cancelled(&self, job_id: &str) -> bool {
+        self.cancelled.lock().unwrap().iter().any(|cancelled| {
+            if job_id == cancelled {
+                return true;
+            }
+            // A bare array id cancels every task, `<array>_[<ranges>]` some
+            let Some((array_id, tasks)) = job_id.split_once('_') else {
+                return false;
+            };
+            match cancelled.split_once('_') {
+                None => array_id == cancelled,
+                Some((cancelled_array, cancelled_tasks)) => {
+                    let tasks = task_ids(tasks);
+                    let cancelled_tasks = task_ids(cancelled_tasks);
+                    array_id == cancelled_array
+                        && !tasks.is_empty()
+                        && tasks.iter().all(|task| cancelled_tasks.contains(task))
+                }
+            }
+        })
+    }
+
+    /// Apply squeue's `-u`/`-p` and drop cancelled jobs from text output.
+    /// Columns are found by header name, per `CLUSTER:` block, split on `|`
+    /// when the header is, as for `squeue --start`, and on `,` otherwise.
+    fn filter_squeue_text(
+        &self,
+        output: &str,
+        user: Option<&str>,
+        partition: Option<&str>,
+    ) -> String {
+        let mut header: Vec<&str> = Vec::new();
+        let mut separator = ',';
+        let mut lines = Vec::new();
+
+        for line in output.lines() {
+            if line.starts_with("JOBID") {
+                separator = if line.contains('|') { '|' } else { ',' };
+                header = line.split(separator).map(str::trim).collect();
+            }
+            let is_job = !header.is_empty()
+                && !line.starts_with("JOBID")
+                && !line.starts_with("CLUSTER:")
+                && !line.trim().is_empty();
+            if !is_job {
+                lines.push(line);
+                continue;
+            }
+
+            let fields: Vec<&str> = line.split(separator).map(str::trim).collect();
+            let column = |name: &str| {
+                header
+                    .iter()
+                    .position(|h| *h == name)
+                    .and_then(|i| fields.get(i).copied())
+                    .unwrap_or("")
+            };
+            let keep = !self.is_cancelled(column("JOBID"))
+                && user.is_none_or(|user| column("USER") == user)
+                && partition
+                    .is_none_or(|partition| column("PARTITION").split(',').any(|p| p == partition));
+            if keep {
+                lines.push(line);
+            }
+        }
+
+        let mut filtered = lines.join("\n");
+        if output.ends_with('\n') {
+            filtered.push('\n');
+        }
+        filtered
+    }
+
+    /// Apply squeue's `-u`/`-p` and drop cancelled jobs from JSON output.
+    fn filter_squeue_json(
+        &self,
+        output: &str,
+        user: Option<&str>,
+        partition: Option<&str>,
+    ) -> Result<String> {
+        let mut response: Value =
+            serde_json::from_str(output).context("Failed to parse squeue.json fixture")?;
+        if let Some(jobs) = response.get_mut("jobs").and_then(Value::as_array_mut) {
+            jobs.retain(|job| {
+                let field = |name: &str| job.get(name).and_then(Value::as_str).unwrap_or("");
+                !self.is_cancelled(&json_job_id(job))
+                    && user.is_none_or(|user| field("user_name") == user)
+                    && partition.is_none_or(|partition| {
+                        field("partition").split(',').any(|p| p == partition)
+                    })
+            });
+        }
+        Ok(response.to_string())
+    }
+}
+
+/// Apply sinfo's `-p` to `--Node` output, by the PARTITION column of each
+/// `CLUSTER:` block. The default partition is listed with a trailing `*`.
+fn filter_sinfo(output: &str, partition: Option<&str>) -> String {
+    let Some(partition) = partition else {
+        return output.to_string();
+    };
+
+    let mut column = None;
+    let mut lines = Vec::new();
+    for line in output.lines() {
+        if line.starts_with("NODELIST") {
+            column = line.split('|').position(|h| h.trim() == "PARTITION");
+        }
+        let is_node = !line.starts_with("NODELIST")
+            && !line.starts_with("CLUSTER:")
+            && !line.trim().is_empty();
+        let keep = !is_node
+            || column
+                .and_then(|i| line.split('|').nth(i))
+                .is_some_and(|p| p.trim().trim_end_matches('*') == partition);
+        if keep {
+            lines.push(line);
         }
     }
+
+    let mut filtered = lines.join("\n");
+    if output.ends_with('\n') {
+        filtered.push('\n');
+    }
+    filtered
+}
+
+/// Keep `CLUSTER:` headers, blank lines, and the lines `keep` accepts.
+fn filter_lines(output: &str, keep: impl Fn(&str) -> bool) -> String {
+    let lines: Vec<&str> = output
+        .lines()
+        .filter(|line| line.starts_with("CLUSTER:") || line.trim().is_empty() || keep(line))
+        .collect();
+    let mut filtered = lines.join("\n");
+    if output.ends_with('\n') {
+        filtered.push('\n');
+    }
+    filtered
+}
+
+/// The tasks after the `_` of an array job id: `5`, or `[4-9]` for a range.
+fn task_ids(tasks: &str) -> Vec<u32> {
+    match tasks
+        .strip_prefix('[')
+        .and_then(|tasks| tasks.strip_suffix(']'))
+    {
+        Some(ranges) => parse_task_ranges(ranges).unwrap_or_default(),
+        None => tasks.parse().into_iter().collect(),
+    }
+}
+
+/// A JSON job's id as squeue prints it, `<array>_<task>` for array tasks.
+fn json_job_id(job: &Value) -> String {
+    // Numbers are plain before 23.02 and {"set", "number"} wrapped after
+    let number = |name: &str| {
+        let value = job.get(name)?;
+        value
+            .as_i64()
+            .or_else(|| value.get("number").and_then(Value::as_i64))
+    };
+    match (number("array_job_id"), number("array_task_id")) {
+        (Some(array_id), Some(task_id)) if array_id != 0 => format!("{}_{}", array_id, task_id),
+        _ => number("job_id").unwrap_or_default().to_string(),
+    }
+}
+
+/// The `<n>` of every `squeue.<n>.<ext>` in `dir`.
+fn snapshot_numbers(dir: &Path, ext: &str) -> Vec<usize> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            name.strip_prefix("squeue.")?
+                .strip_suffix(&format!(".{}", ext))?
+                .parse()
+                .ok()
+        })
+        .collect()
 }
 
 #[async_trait]
 impl SlurmExecutor for SlurmFixture {
-    async fn squeue(&self, _user: Option<&str>, _partition: Option<&str>) -> Result<String> {
-        let path = self.fixture_dir.join("squeue.txt");
+    async fn squeue(
+        &self,
+        user: Option<&str>,
+        partition: Option<&str>,
+        clusters: Option<&str>,
+    ) -> Result<String> {
+        let output = self.squeue_snapshot(clusters, "txt")?;
+        Ok(self.filter_squeue_text(&output, user, partition))
+    }
+
+    async fn squeue_start(
+        &self,
+        user: Option<&str>,
+        partition: Option<&str>,
+        clusters: Option<&str>,
+    ) -> Result<String> {
+        let path = self.path(clusters, "squeue_start.txt");
+        let output = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read fixture: {}", path.display()))?;
+        Ok(self.filter_squeue_text(&output, user, partition))
+    }
+
+    async fn scontrol_show_job(&self, job_id: &str, cluster: Option<&str>) -> Result<String> {
+        let path = self.path(cluster, &format!("scontrol/{}.txt", job_id));
         std::fs::read_to_string(&path)
             .with_context(|| format!("Failed to read fixture: {}", path.display()))
     }
 
-    async fn scontrol_show_job(&self, job_id: &str) -> Result<String> {
-        let path = self
-            .fixture_dir
-            .join("scontrol")
-            .join(format!("{}.txt", job_id));
+    async fn scontrol_show_jobs(&self, cluster: Option<&str>) -> Result<String> {
+        let path = self.path(cluster, "scontrol.txt");
+        if path.exists() {
+            return std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read fixture: {}", path.display()));
+        }
+
+        let dir = self.path(cluster, "scontrol");
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(&dir)
+            .with_context(|| format!("Failed to read fixture: {}", dir.display()))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+            .collect();
+        paths.sort();
+
+        let mut records = Vec::new();
+        for path in paths {
+            records.push(
+                std::fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read fixture: {}", path.display()))?,
+            );
+        }
+        Ok(records.join("\n"))
+    }
+
+    async fn squeue_json(
+        &self,
+        user: Option<&str>,
+        partition: Option<&str>,
+        clusters: Option<&str>,
+    ) -> Result<String> {
+        let output = self.squeue_snapshot(clusters, "json")?;
+        self.filter_squeue_json(&output, user, partition)
+    }
+
+    async fn version(&self) -> Result<String> {
+        let path = self.fixture_dir.join("version.txt");
         std::fs::read_to_string(&path)
             .with_context(|| format!("Failed to read fixture: {}", path.display()))
     }
 
-    async fn scancel(&self, job_id: &str) -> Result<()> {
-        self.cancelled.lock().unwrap().push(job_id.to_string());
+    async fn scancel(
+        &self,
+        job_id: &str,
+        _cluster: Option<&str>,
+        options: &CancelOptions,
+    ) -> Result<()> {
+        if *options == CancelOptions::default() {
+            self.cancelled.lock().unwrap().push(job_id.to_string());
+        } else {
+            self.signalled
+                .lock()
+                .unwrap()
+                .push((job_id.to_string(), options.clone()));
+        }
         Ok(())
     }
+
+    async fn hold(&self, job_id: &str, _cluster: Option<&str>) -> Result<()> {
+        self.held.lock().unwrap().push(job_id.to_string());
+        Ok(())
+    }
+
+    async fn release(&self, job_id: &str, _cluster: Option<&str>) -> Result<()> {
+        self.released.lock().unwrap().push(job_id.to_string());
+        Ok(())
+    }
+
+    async fn requeue(&self, job_id: &str, _cluster: Option<&str>) -> Result<()> {
+        self.requeued.lock().unwrap().push(job_id.to_string());
+        Ok(())
+    }
+
+    async fn update_job(
+        &self,
+        job_id: &str,
+        _cluster: Option<&str>,
+        changes: &[(String, String)],
+    ) -> Result<()> {
+        self.updated
+            .lock()
+            .unwrap()
+            .push((job_id.to_string(), changes.to_vec()));
+        Ok(())
+    }
+
+    async fn sbatch(
+        &self,
+        script: &str,
+        cluster: Option<&str>,
+        options: &[(String, String)],
+    ) -> Result<String> {
+        self.submitted
+            .lock()
+            .unwrap()
+            .push((script.to_string(), options.to_vec()));
+        let path = self.path(cluster, "sbatch.txt");
+        let output = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read fixture: {}", path.display()))?;
+        Ok(output.trim().to_string())
+    }
+
+    async fn sacct(
+        &self,
+        _user: Option<&str>,
+        _partition: Option<&str>,
+        clusters: Option<&str>,
+        _window: Duration,
+    ) -> Result<String> {
+        let path = self.path(clusters, "sacct.txt");
+        std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read fixture: {}", path.display()))
+    }
+
+    async fn sacct_job(&self, job_id: &str, cluster: Option<&str>) -> Result<String> {
+        let path = self.path(cluster, &format!("sacct/{}.txt", job_id));
+        if path.exists() {
+            return std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read fixture: {}", path.display()));
+        }
+        let path = self.path(cluster, "sacct.txt");
+        let output = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read fixture: {}", path.display()))?;
+        let header = output.lines().next().unwrap_or("");
+        let column = header.split('|').position(|h| h == "JobID");
+        // The job, its steps, and for an array id all its tasks
+        Ok(filter_lines(&output, |line| {
+            let id = column.and_then(|i| line.split('|').nth(i)).unwrap_or("");
+            line == header
+                || id == job_id
+                || id
+                    .strip_prefix(job_id)
+                    .is_some_and(|rest| rest.starts_with(['_', '.']))
+        }))
+    }
+
+    async fn sprio(&self, partition: Option<&str>, cluster: Option<&str>) -> Result<String> {
+        let path = self.path(cluster, "sprio.txt");
+        let output = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read fixture: {}", path.display()))?;
+        Ok(filter_lines(&output, |line| {
+            let name = line.split('|').nth(2).unwrap_or("");
+            partition.is_none_or(|partition| name.trim() == partition)
+        }))
+    }
+
+    async fn sshare(&self, user: Option<&str>, cluster: Option<&str>) -> Result<String> {
+        let path = self.path(cluster, "sshare.txt");
+        let output = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read fixture: {}", path.display()))?;
+        let column = output
+            .lines()
+            .find(|line| line.starts_with("Account"))
+            .and_then(|header| header.split('|').position(|h| h == "User"));
+        Ok(filter_lines(&output, |line| {
+            line.starts_with("Account")
+                || user.is_none_or(|user| column.and_then(|i| line.split('|').nth(i)) == Some(user))
+        }))
+    }
+
+    async fn sstat(&self, job_id: &str, cluster: Option<&str>) -> Result<String> {
+        let path = self.path(cluster, &format!("sstat/{}.txt", job_id));
+        std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read fixture: {}", path.display()))
+    }
+
+    async fn sinfo(&self, partition: Option<&str>, clusters: Option<&str>) -> Result<String> {
+        let path = self.path(clusters, "sinfo.txt");
+        let output = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read fixture: {}", path.display()))?;
+        Ok(filter_sinfo(&output, partition))
+    }
+
+    async fn scontrol_show_nodes(&self, cluster: Option<&str>) -> Result<String> {
+        let path = self.path(cluster, "scontrol_nodes.txt");
+        std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read fixture: {}", path.display()))
+    }
+
+    async fn sinfo_partitions(&self, clusters: Option<&str>) -> Result<String> {
+        let path = self.path(clusters, "sinfo_partitions.txt");
+        std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read fixture: {}", path.display()))
+    }
+
+    async fn scontrol_show_partitions(&self, cluster: Option<&str>) -> Result<String> {
+        let path = self.path(cluster, "scontrol_partitions.txt");
+        std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read fixture: {}", path.display()))
+    }
+
+    async fn clusters(&self) -> Result<String> {
+        let path = self.fixture_dir.join("clusters.txt");
+        std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read fixture: {}", path.display()))
+    }
+
+    async fn tail_file(&self, path: &str, lines: usize) -> Result<String> {
+        read_tail(path, lines).await
+    }
+
+    fn shell_command(
+        &self,
+        job_id: &str,
+        cluster: Option<&str>,
+        node: Option<&str>,
+    ) -> Result<Vec<String>> {
+        self.attached
+            .lock()
+            .unwrap()
+            .push((job_id.to_string(), node.map(str::to_string)));
+        let args = shell_args(job_id, cluster, node);
+        Ok(std::iter::once("srun".to_string()).chain(args).collect())
+    }
 }
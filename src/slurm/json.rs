@@ -0,0 +1,309 @@
+//! Serde models for `squeue --json`.
+//!
+//! squeue emits the same job schema as slurmrestd. Only the fields
+//! lazyslurm displays are modelled, and the models accept both the plain
+//! values used up to 22.05 and the `{"set", "infinite", "number"}` wrappers
+//! introduced in 23.02.
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::models::{Job, JobState};
+use crate::slurm::parser::tres_to_gres;
+
+#[derive(Debug, Deserialize)]
+pub struct JobsResponse {
+    #[serde(default)]
+    pub jobs: Vec<JsonJob>,
+    #[serde(default)]
+    pub errors: Vec<JsonError>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct JsonError {
+    #[serde(default)]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+impl JsonError {
+    pub fn message(&self) -> String {
+        self.description
+            .clone()
+            .filter(|d| !d.is_empty())
+            .or_else(|| self.error.clone())
+            .unwrap_or_else(|| "unknown error".to_string())
+    }
+}
+
+/// A number that is either plain (<= 22.05) or wrapped (>= 23.02).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum SlurmNumber {
+    Plain(i64),
+    Wrapped {
+        #[serde(default)]
+        set: bool,
+        #[serde(default)]
+        infinite: bool,
+        #[serde(default)]
+        number: i64,
+    },
+}
+
+impl SlurmNumber {
+    /// The value, or `None` when unset or infinite.
+    pub fn value(&self) -> Option<i64> {
+        match self {
+            SlurmNumber::Plain(n) => Some(*n),
+            SlurmNumber::Wrapped {
+                set: true,
+                infinite: false,
+                number,
+            } => Some(*number),
+            SlurmNumber::Wrapped { .. } => None,
+        }
+    }
+
+    pub fn is_infinite(&self) -> bool {
+        matches!(self, SlurmNumber::Wrapped { infinite: true, .. })
+    }
+}
+
+/// `job_state` is a string before 23.02 and a list of flags afterwards,
+/// e.g. `["PENDING", "REQUEUED"]`; the first entry is the base state.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum JsonJobState {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl JsonJobState {
+    pub fn base(&self) -> &str {
+        match self {
+            JsonJobState::One(s) => s,
+            JsonJobState::Many(v) => v.first().map(String::as_str).unwrap_or(""),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum JsonExitCode {
+    Plain(i64),
+    Verbose {
+        #[serde(default)]
+        return_code: Option<SlurmNumber>,
+    },
+}
+
+impl JsonExitCode {
+    pub fn code(&self) -> Option<i64> {
+        match self {
+            JsonExitCode::Plain(n) => Some(*n),
+            JsonExitCode::Verbose { return_code } => return_code.as_ref()?.value(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct JsonJob {
+    pub job_id: u64,
+    #[serde(default)]
+    pub array_job_id: Option<SlurmNumber>,
+    #[serde(default)]
+    pub array_task_id: Option<SlurmNumber>,
+    /// The tasks of a pending range, e.g. "4-9" or "4-9%5".
+    #[serde(default)]
+    pub array_task_string: Option<String>,
+    /// The array's `%N` throttle; 0 when it has none.
+    #[serde(default)]
+    pub array_max_tasks: Option<SlurmNumber>,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub user_name: String,
+    #[serde(default)]
+    pub partition: String,
+    #[serde(default)]
+    pub cluster: Option<String>,
+    pub job_state: JsonJobState,
+    #[serde(default)]
+    pub time_limit: Option<SlurmNumber>,
+    #[serde(default)]
+    pub submit_time: Option<SlurmNumber>,
+    #[serde(default)]
+    pub start_time: Option<SlurmNumber>,
+    #[serde(default)]
+    pub end_time: Option<SlurmNumber>,
+    #[serde(default)]
+    pub node_count: Option<SlurmNumber>,
+    #[serde(default)]
+    pub nodes: Option<String>,
+    #[serde(default)]
+    pub cpus: Option<SlurmNumber>,
+    #[serde(default)]
+    pub memory_per_node: Option<SlurmNumber>,
+    #[serde(default)]
+    pub memory_per_cpu: Option<SlurmNumber>,
+    #[serde(default)]
+    pub tasks: Option<SlurmNumber>,
+    #[serde(default)]
+    pub cpus_per_task: Option<SlurmNumber>,
+    #[serde(default)]
+    pub tres_per_node: Option<String>,
+    #[serde(default)]
+    pub account: Option<String>,
+    #[serde(default)]
+    pub qos: Option<String>,
+    #[serde(default)]
+    pub batch_flag: Option<bool>,
+    #[serde(default)]
+    pub command: Option<String>,
+    #[serde(default)]
+    pub current_working_directory: Option<String>,
+    #[serde(default)]
+    pub standard_output: Option<String>,
+    #[serde(default)]
+    pub standard_error: Option<String>,
+    #[serde(default)]
+    pub exit_code: Option<JsonExitCode>,
+    #[serde(default)]
+    pub state_reason: Option<String>,
+}
+
+impl JsonJob {
+    pub fn into_job(self) -> Job {
+        let array_job_id = self
+            .array_job_id
+            .as_ref()
+            .and_then(SlurmNumber::value)
+            .filter(|id| *id != 0);
+        let array_task_id = self.array_task_id.as_ref().and_then(SlurmNumber::value);
+        let array_tasks = self
+            .array_task_string
+            .as_deref()
+            .filter(|tasks| !tasks.is_empty());
+
+        // Match squeue's text output, which names array tasks "<array>_<task>"
+        // and a pending range "<array>_[<tasks>]", with the throttle
+        let job_id = match (array_job_id, array_task_id, array_tasks) {
+            (Some(array_id), Some(task_id), _) => format!("{}_{}", array_id, task_id),
+            (Some(array_id), None, Some(tasks)) => {
+                let throttle = self
+                    .array_max_tasks
+                    .as_ref()
+                    .and_then(SlurmNumber::value)
+                    .filter(|max| *max > 0 && !tasks.contains('%'));
+                match throttle {
+                    Some(max) => format!("{}_[{}%{}]", array_id, tasks, max),
+                    None => format!("{}_[{}]", array_id, tasks),
+                }
+            }
+            _ => self.job_id.to_string(),
+        };
+
+        let mut job = Job::new(
+            job_id,
+            self.name,
+            self.user_name,
+            JobState::from(self.job_state.base()),
+        );
+
+        if let Some(array_id) =
+            array_job_id.filter(|_| array_task_id.is_some() || array_tasks.is_some())
+        {
+            job.array_job_id = Some(array_id.to_string());
+            job.array_task_id = array_task_id.and_then(|task_id| u32::try_from(task_id).ok());
+        }
+
+        job.partition = self.partition;
+        job.cluster = self.cluster.filter(|c| !c.is_empty());
+        job.time_limit = self.time_limit.as_ref().and_then(format_time_limit);
+        job.submit_time = self.submit_time.as_ref().and_then(timestamp);
+        // A pending job's start_time is when the scheduler expects to start it
+        let start_time = self.start_time.as_ref().and_then(timestamp);
+        if job.state == JobState::Pending {
+            job.estimated_start = start_time;
+        } else {
+            job.start_time = start_time;
+        }
+        job.end_time = self.end_time.as_ref().and_then(timestamp);
+        job.nodes = self.node_count.as_ref().and_then(small_number);
+        job.node_list = self.nodes.filter(|n| !n.is_empty());
+        job.cpus = self.cpus.as_ref().and_then(small_number);
+        job.memory = self
+            .memory_per_node
+            .as_ref()
+            .and_then(SlurmNumber::value)
+            .filter(|mb| *mb > 0)
+            .map(format_megabytes);
+        job.memory_per_cpu = self
+            .memory_per_cpu
+            .as_ref()
+            .and_then(SlurmNumber::value)
+            .filter(|mb| *mb > 0)
+            .map(format_megabytes);
+        job.num_tasks = self.tasks.as_ref().and_then(small_number);
+        job.cpus_per_task = self.cpus_per_task.as_ref().and_then(small_number);
+        job.gres = self
+            .tres_per_node
+            .filter(|t| !t.is_empty())
+            .map(|t| tres_to_gres(&t));
+        job.account = self.account.filter(|a| !a.is_empty());
+        job.qos = self.qos.filter(|q| !q.is_empty());
+        job.batch = self.batch_flag;
+        // For srun and salloc jobs, command is the program they run
+        if self.batch_flag != Some(false) {
+            job.command = self.command.filter(|c| !c.is_empty());
+        }
+        job.working_dir = self.current_working_directory.filter(|d| !d.is_empty());
+        job.std_out = self.standard_output.filter(|p| !p.is_empty());
+        job.std_err = self.standard_error.filter(|p| !p.is_empty());
+        job.exit_code = self
+            .exit_code
+            .as_ref()
+            .and_then(JsonExitCode::code)
+            .and_then(|code| i32::try_from(code).ok());
+        job.reason = self.state_reason.filter(|r| !r.is_empty());
+
+        job
+    }
+}
+
+/// Unix timestamp to UTC; Slurm uses 0 for "not yet known". Unlike the
+/// text output's wall-clock times, these need no time zone to read.
+fn timestamp(n: &SlurmNumber) -> Option<DateTime<Utc>> {
+    n.value()
+        .filter(|secs| *secs > 0)
+        .and_then(|secs| DateTime::from_timestamp(secs, 0))
+}
+
+fn small_number(n: &SlurmNumber) -> Option<u32> {
+    n.value().and_then(|v| u32::try_from(v).ok())
+}
+
+/// Minutes to the `[D-]HH:MM:SS` form scontrol prints.
+fn format_time_limit(n: &SlurmNumber) -> Option<String> {
+    if n.is_infinite() {
+        return Some("UNLIMITED".to_string());
+    }
+    let minutes = n.value()?;
+    let (days, hours, mins) = (minutes / (24 * 60), (minutes / 60) % 24, minutes % 60);
+    Some(if days > 0 {
+        format!("{}-{:02}:{:02}:00", days, hours, mins)
+    } else {
+        format!("{:02}:{:02}:00", hours, mins)
+    })
+}
+
+/// Megabytes to the `8G` / `1000M` form scontrol prints.
+fn format_megabytes(mb: i64) -> String {
+    if mb % 1024 == 0 {
+        format!("{}G", mb / 1024)
+    } else {
+        format!("{}M", mb)
+    }
+}
@@ -1,9 +1,18 @@
 pub mod commands;
 pub mod executor;
 pub mod fixture;
+pub mod json;
 pub mod parser;
+pub mod recorder;
+pub mod remote;
+pub mod rest;
+pub mod simulator;
 
 pub use commands::*;
 pub use executor::*;
 pub use fixture::*;
 pub use parser::*;
+pub use recorder::*;
+pub use remote::*;
+pub use rest::*;
+pub use simulator::*;
@@ -1,17 +1,46 @@
 use anyhow::Result;
-use chrono::{DateTime, NaiveDateTime, Utc};
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
 use regex::Regex;
 use std::collections::HashMap;
 
-use crate::models::{Job, JobState};
+use crate::models::{
+    FairShare, Job, JobEfficiency, JobPriority, JobState, Node, NodeState, Partition,
+    PartitionState, StepUsage,
+};
+use crate::slurm::json::JobsResponse;
 
 pub struct SlurmParser;
 
+/// A key of scontrol's "Key=Value Key2=Value2 ..." output, at the start of
+/// a line or after whitespace. Keys are capitalised, so "lr=0.1" in a
+/// comment stays part of it; some, such as CPUs/Task, carry a slash or
+/// colon.
+const SCONTROL_KEY: &str = r"(?:^|\s)([A-Z][\w/:]*)=";
+
+/// Keys scontrol prints alone on their line. Their values are paths or a
+/// command line and run to the end of it, e.g.
+/// "Command=/home/u/train.sh LR=0.1".
+const SCONTROL_LINE_KEYS: [&str; 5] = ["Command", "WorkDir", "StdErr", "StdIn", "StdOut"];
+
+/// The most node names a hostlist expands to, well past any real
+/// allocation; a malformed range such as `node[0-99999999]` stops here.
+pub const MAX_HOSTLIST_HOSTS: usize = 65_536;
+
+/// Expected start times from `squeue --start`, keyed by cluster and job id.
+pub type EstimatedStarts = HashMap<(Option<String>, String), DateTime<Utc>>;
+
 impl SlurmParser {
     pub fn parse_squeue_output(output: &str) -> Result<Vec<Job>> {
         let mut jobs = Vec::new();
+        // With -M, each cluster's table is preceded by "CLUSTER: <name>"
+        let mut cluster: Option<String> = None;
 
         for line in output.lines() {
+            if let Some(name) = line.strip_prefix("CLUSTER:") {
+                cluster = Some(name.trim().to_string());
+                continue;
+            }
+
             if line.trim().is_empty() || line.starts_with("JOBID") {
                 continue;
             }
@@ -24,15 +53,9 @@ impl SlurmParser {
                 let state = JobState::from(parts[3].trim());
 
                 let mut job = Job::new(job_id.clone(), name, user, state);
+                job.cluster = cluster.clone();
 
-                // Parse array job ID if present (e.g., "23673084_5" -> array_job_id=23673084, task_id=5)
-                if job_id.contains('_') {
-                    let array_parts: Vec<&str> = job_id.split('_').collect();
-                    if array_parts.len() == 2 {
-                        job.array_job_id = Some(array_parts[0].to_string());
-                        job.array_task_id = array_parts[1].parse().ok();
-                    }
-                }
+                Self::apply_array_id(&mut job);
 
                 // Additional fields if present
                 if parts.len() > 4 {
@@ -52,21 +75,761 @@ impl SlurmParser {
         Ok(jobs)
     }
 
+    /// Parse `squeue --start` output. Jobs the scheduler has not estimated
+    /// yet ("N/A") are left out.
+    pub fn parse_squeue_start(output: &str) -> Result<EstimatedStarts> {
+        let mut starts = HashMap::new();
+        let mut cluster: Option<String> = None;
+        let mut columns: Vec<&str> = Vec::new();
+
+        for line in output.lines() {
+            if let Some(name) = line.strip_prefix("CLUSTER:") {
+                cluster = Some(name.trim().to_string());
+                continue;
+            }
+            if line.starts_with("JOBID") {
+                columns = line.split('|').map(str::trim).collect();
+                continue;
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            // `|`-separated: a job pending in several partitions lists them
+            // with commas
+            let values: Vec<&str> = line.split('|').map(str::trim).collect();
+            let field = |name: &str| {
+                columns
+                    .iter()
+                    .position(|c| *c == name)
+                    .and_then(|i| values.get(i).copied())
+            };
+            let (Some(job_id), Some(start)) = (
+                field("JOBID"),
+                field("START_TIME").and_then(Self::parse_slurm_time),
+            ) else {
+                continue;
+            };
+            starts.insert((cluster.clone(), job_id.to_string()), start);
+        }
+
+        Ok(starts)
+    }
+
+    /// Parse `squeue --json` output. Unlike the text format, the JSON already
+    /// carries the details `scontrol` would add (times, work dir, log paths).
+    pub fn parse_squeue_json(output: &str) -> Result<Vec<Job>> {
+        let response: JobsResponse = serde_json::from_str(output)?;
+        if response.jobs.is_empty()
+            && let Some(error) = response.errors.first()
+        {
+            anyhow::bail!("{}", error.message());
+        }
+        Ok(response
+            .jobs
+            .into_iter()
+            .map(|job| job.into_job())
+            .collect())
+    }
+
+    /// Extract `(major, minor)` from a version banner such as "slurm 23.02.6".
+    pub fn parse_version(output: &str) -> Option<(u32, u32)> {
+        let re = Regex::new(r"(\d+)\.(\d+)").ok()?;
+        let cap = re.captures(output)?;
+        Some((cap[1].parse().ok()?, cap[2].parse().ok()?))
+    }
+
+    /// `squeue --json` arrived in Slurm 21.08.
+    pub fn supports_json(version: (u32, u32)) -> bool {
+        version >= (21, 8)
+    }
+
+    /// Parse `sacct --parsable2` output. Columns are looked up by the header
+    /// row, and job step lines (`12345.batch`, `12345.0`, ...) only add
+    /// their MaxRSS to the job's efficiency, so each job appears once.
+    pub fn parse_sacct_output(output: &str) -> Result<Vec<Job>> {
+        let mut lines = output.lines().filter(|line| !line.trim().is_empty());
+        let Some(header) = lines.next() else {
+            return Ok(Vec::new());
+        };
+        let columns: Vec<&str> = header.split('|').collect();
+
+        let mut jobs = Vec::new();
+        // Peak MaxRSS per job, which sacct only reports on the step lines
+        let mut step_rss: HashMap<String, u64> = HashMap::new();
+        for line in lines {
+            let values: Vec<&str> = line.split('|').collect();
+            let field = |name: &str| {
+                columns
+                    .iter()
+                    .position(|c| *c == name)
+                    .and_then(|i| values.get(i))
+                    .map(|v| v.trim())
+                    .filter(|v| !v.is_empty())
+            };
+
+            let Some(job_id) = field("JobID") else {
+                continue;
+            };
+            if let Some((parent, _step)) = job_id.split_once('.') {
+                if let Some(rss) = field("MaxRSS").and_then(Self::parse_size) {
+                    let peak = step_rss.entry(parent.to_string()).or_default();
+                    *peak = rss.max(*peak);
+                }
+                continue;
+            }
+
+            // "CANCELLED by 1000" -> "CANCELLED"
+            let state = field("State")
+                .and_then(|s| s.split_whitespace().next())
+                .unwrap_or("");
+
+            let mut job = Job::new(
+                job_id.to_string(),
+                field("JobName").unwrap_or("").to_string(),
+                field("User").unwrap_or("").to_string(),
+                JobState::from(state),
+            );
+            Self::apply_array_id(&mut job);
+
+            job.partition = field("Partition").unwrap_or("").to_string();
+            job.cluster = field("Cluster").map(str::to_string);
+            job.time_used = field("Elapsed").map(str::to_string);
+            job.time_limit = field("Timelimit").map(str::to_string);
+            job.submit_time = field("Submit").and_then(Self::parse_slurm_time);
+            job.start_time = field("Start").and_then(Self::parse_slurm_time);
+            job.end_time = field("End").and_then(Self::parse_slurm_time);
+            job.nodes = field("NNodes").and_then(|n| n.parse().ok());
+            job.cpus = field("AllocCPUS").and_then(|n| n.parse().ok());
+            job.node_list = field("NodeList").map(str::to_string);
+            job.memory = field("ReqMem").map(str::to_string);
+            job.working_dir = field("WorkDir").map(str::to_string);
+            job.reason = field("Reason").map(str::to_string);
+            job.exit_code = field("ExitCode")
+                .and_then(|code| code.split(':').next())
+                .and_then(|code| code.parse().ok());
+
+            if job.is_finished() {
+                let elapsed = field("Elapsed").and_then(Self::parse_slurm_duration);
+                let total_cpu = field("TotalCPU").and_then(Self::parse_slurm_duration);
+                if let (Some(elapsed), Some(total_cpu), Some(cpus)) = (elapsed, total_cpu, job.cpus)
+                {
+                    job.efficiency = Some(JobEfficiency::new(
+                        total_cpu,
+                        elapsed * cpus as f64,
+                        field("MaxRSS").and_then(Self::parse_size),
+                        field("ReqMem").and_then(|mem| {
+                            Self::parse_requested_memory(mem, cpus, job.nodes.unwrap_or(1))
+                        }),
+                    ));
+                }
+            }
+
+            jobs.push(job);
+        }
+
+        for job in &mut jobs {
+            if let (Some(efficiency), Some(rss)) = (&job.efficiency, step_rss.get(&job.job_id)) {
+                let used = efficiency.memory_used.unwrap_or(0).max(*rss);
+                job.efficiency = Some(JobEfficiency::new(
+                    efficiency.cpu_used_secs,
+                    efficiency.cpu_allocated_secs,
+                    Some(used),
+                    efficiency.memory_requested,
+                ));
+            }
+        }
+
+        Ok(jobs)
+    }
+
+    /// Parse a sacct duration such as "1-02:03:04", "02:03:04" or
+    /// "45:10.221" (TotalCPU keeps milliseconds) into seconds.
+    fn parse_slurm_duration(duration: &str) -> Option<f64> {
+        let (days, rest) = match duration.split_once('-') {
+            Some((days, rest)) => (days.parse::<f64>().ok()?, rest),
+            None => (0.0, duration),
+        };
+        let mut secs = 0.0;
+        for part in rest.split(':') {
+            secs = secs * 60.0 + part.parse::<f64>().ok()?;
+        }
+        Some(days * 86400.0 + secs)
+    }
+
+    /// Memory per node from ReqMem. Before 21.08 the size carries an `n`
+    /// (per node) or `c` (per CPU) suffix.
+    fn parse_requested_memory(req_mem: &str, cpus: u32, nodes: u32) -> Option<u64> {
+        match req_mem.strip_suffix('c') {
+            Some(per_cpu) => Some(Self::parse_size(per_cpu)? * cpus as u64 / nodes.max(1) as u64),
+            None => Self::parse_size(req_mem.strip_suffix('n').unwrap_or(req_mem)),
+        }
+    }
+
+    /// Parse `sstat --parsable2` output into per-step usage. Columns are
+    /// looked up by the header row, like sacct.
+    pub fn parse_sstat_output(output: &str) -> Result<Vec<StepUsage>> {
+        let mut lines = output.lines().filter(|line| !line.trim().is_empty());
+        let Some(header) = lines.next() else {
+            return Ok(Vec::new());
+        };
+        let columns: Vec<&str> = header.split('|').collect();
+
+        let mut steps = Vec::new();
+        for line in lines {
+            let values: Vec<&str> = line.split('|').collect();
+            let field = |name: &str| {
+                columns
+                    .iter()
+                    .position(|c| *c == name)
+                    .and_then(|i| values.get(i))
+                    .map(|v| v.trim())
+                    .filter(|v| !v.is_empty())
+            };
+
+            // "12345.batch" -> "batch"
+            let Some(job_id) = field("JobID") else {
+                continue;
+            };
+            let step_id = job_id.split_once('.').map_or(job_id, |(_, step)| step);
+
+            let mut step = StepUsage::new(step_id.to_string());
+            step.ave_cpu = field("AveCPU").map(str::to_string);
+            step.ave_rss = field("AveRSS").and_then(Self::parse_size);
+            step.max_rss = field("MaxRSS").and_then(Self::parse_size);
+            step.max_rss_node = field("MaxRSSNode").map(str::to_string);
+            step.max_vm_size = field("MaxVMSize").and_then(Self::parse_size);
+            step.disk_read = field("AveDiskRead").and_then(Self::parse_size);
+            step.disk_write = field("AveDiskWrite").and_then(Self::parse_size);
+
+            steps.push(step);
+        }
+
+        Ok(steps)
+    }
+
+    /// Parse headerless `sprio` output in [`SPRIO_FORMAT`], column by column.
+    ///
+    /// [`SPRIO_FORMAT`]: crate::slurm::SPRIO_FORMAT
+    pub fn parse_sprio_output(output: &str) -> Result<Vec<JobPriority>> {
+        let mut priorities = Vec::new();
+        for line in output.lines() {
+            if line.trim().is_empty() || line.starts_with("CLUSTER:") {
+                continue;
+            }
+            let values: Vec<&str> = line.split('|').map(str::trim).collect();
+            let [job_id, user, partition, ..] = values[..] else {
+                continue;
+            };
+            // Factors are whole numbers unless sprio was asked to normalize
+            let number = |i: usize| {
+                values
+                    .get(i)
+                    .and_then(|v| v.parse::<f64>().ok())
+                    .map_or(0, |v| v.round() as u64)
+            };
+
+            let mut priority =
+                JobPriority::new(job_id.to_string(), user.to_string(), partition.to_string());
+            priority.priority = number(3);
+            priority.age = number(4);
+            priority.fairshare = number(5);
+            priority.job_size = number(6);
+            priority.partition_factor = number(7);
+            priority.qos = number(8);
+            priority.tres = values
+                .get(9)
+                .filter(|v| !v.is_empty())
+                .map(|v| v.to_string());
+
+            priorities.push(priority);
+        }
+        Ok(priorities)
+    }
+
+    /// Parse `sshare --parsable2` output. Rows without a user are the
+    /// accounts above the users in the fairshare tree and are skipped.
+    pub fn parse_sshare_output(output: &str) -> Result<Vec<FairShare>> {
+        let mut columns: Vec<&str> = Vec::new();
+        let mut shares = Vec::new();
+        for line in output.lines() {
+            if line.starts_with("Account") {
+                columns = line.split('|').collect();
+                continue;
+            }
+            if line.trim().is_empty() || line.starts_with("CLUSTER:") {
+                continue;
+            }
+
+            let values: Vec<&str> = line.split('|').collect();
+            let field = |name: &str| {
+                columns
+                    .iter()
+                    .position(|c| *c == name)
+                    .and_then(|i| values.get(i))
+                    .map(|v| v.trim())
+                    .filter(|v| !v.is_empty())
+            };
+            let (Some(account), Some(user)) = (field("Account"), field("User")) else {
+                continue;
+            };
+
+            let mut share = FairShare::new(account.to_string(), user.to_string());
+            share.raw_shares = field("RawShares").and_then(|v| v.parse().ok());
+            share.norm_shares = field("NormShares").and_then(|v| v.parse().ok());
+            share.raw_usage = field("RawUsage").and_then(|v| v.parse().ok());
+            share.effective_usage = field("EffectvUsage").and_then(|v| v.parse().ok());
+            share.fairshare = field("FairShare").and_then(|v| v.parse().ok());
+
+            shares.push(share);
+        }
+        Ok(shares)
+    }
+
+    /// Parse a Slurm size such as "1234K", "2.50G" or "8000M" into bytes.
+    /// Units are binary; a bare number is taken as bytes.
+    pub fn parse_size(size: &str) -> Option<u64> {
+        let size = size.trim();
+        let (number, unit) = match size.char_indices().last()? {
+            (i, c) if c.is_ascii_alphabetic() => (&size[..i], c.to_ascii_uppercase()),
+            _ => (size, 'B'),
+        };
+        let multiplier: u64 = match unit {
+            'B' => 1,
+            'K' => 1 << 10,
+            'M' => 1 << 20,
+            'G' => 1 << 30,
+            'T' => 1 << 40,
+            'P' => 1 << 50,
+            _ => return None,
+        };
+        let number: f64 = number.parse().ok()?;
+        Some((number * multiplier as f64) as u64)
+    }
+
+    /// Parse `sacctmgr --noheader --parsable2 show clusters format=Cluster`.
+    pub fn parse_clusters_output(output: &str) -> Vec<String> {
+        output
+            .lines()
+            .map(|line| line.trim().trim_end_matches('|'))
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
     pub fn parse_scontrol_output(output: &str) -> Result<HashMap<String, String>> {
+        let re = Regex::new(SCONTROL_KEY)?;
+        Ok(Self::scontrol_fields(&re, output))
+    }
+
+    fn scontrol_fields(re: &Regex, output: &str) -> HashMap<String, String> {
         let mut fields = HashMap::new();
 
-        // scontrol output format: "Key=Value Key2=Value2 ..." separated by whitespace.
-        let re = Regex::new(r"(\w+)=(\S+)")?;
+        for line in output.lines() {
+            if let Some((key, value)) = line.trim_start().split_once('=')
+                && SCONTROL_LINE_KEYS.contains(&key)
+            {
+                fields.insert(key.to_string(), value.trim().trim_matches('"').to_string());
+                continue;
+            }
+
+            // A value runs up to the next key, so it may hold spaces, e.g.
+            // "Comment=needs rerun after fix"
+            let keys: Vec<_> = re.captures_iter(line).collect();
+            for (i, cap) in keys.iter().enumerate() {
+                let start = cap.get(0).map_or(0, |m| m.end());
+                let end = keys
+                    .get(i + 1)
+                    .and_then(|next| next.get(0))
+                    .map_or(line.len(), |m| m.start());
+                let value = line[start..end].trim().trim_matches('"').to_string();
+                fields.insert(cap[1].to_string(), value);
+            }
+        }
+
+        fields
+    }
+
+    /// Parse `scontrol show job` output for many jobs into fields keyed by
+    /// job id. Array tasks are keyed the way squeue prints them (`100_3`,
+    /// or `100_[4-9]` for a pending range) as well as by their own job id.
+    pub fn parse_scontrol_multi(output: &str) -> Result<HashMap<String, HashMap<String, String>>> {
+        let mut jobs = HashMap::new();
+
+        for fields in Self::scontrol_records(output)? {
+            let Some(job_id) = fields.get("JobId").cloned() else {
+                continue;
+            };
+
+            if let (Some(array_id), Some(task_id)) =
+                (fields.get("ArrayJobId"), fields.get("ArrayTaskId"))
+            {
+                let key = if task_id.chars().all(|c| c.is_ascii_digit()) {
+                    format!("{}_{}", array_id, task_id)
+                } else {
+                    format!("{}_[{}]", array_id, task_id)
+                };
+                jobs.insert(key, fields.clone());
+            }
+            jobs.insert(job_id, fields);
+        }
+
+        Ok(jobs)
+    }
+
+    /// Parse `scontrol show node` output into fields keyed by node name.
+    pub fn parse_scontrol_nodes(output: &str) -> Result<HashMap<String, HashMap<String, String>>> {
+        Ok(Self::scontrol_records(output)?
+            .into_iter()
+            .filter_map(|fields| Some((fields.get("NodeName")?.clone(), fields)))
+            .collect())
+    }
+
+    /// Split multi-record scontrol output, where records are separated by
+    /// blank lines.
+    fn scontrol_records(output: &str) -> Result<Vec<HashMap<String, String>>> {
+        let re = Regex::new(SCONTROL_KEY)?;
+        let mut records = Vec::new();
+
+        let mut record = String::new();
+        for line in output.lines().chain(std::iter::once("")) {
+            if !line.trim().is_empty() {
+                record.push_str(line);
+                record.push('\n');
+                continue;
+            }
+            if !record.is_empty() {
+                records.push(Self::scontrol_fields(&re, &record));
+                record.clear();
+            }
+        }
+
+        Ok(records)
+    }
+
+    /// Parse `sinfo --Node` output in [`SINFO_FORMAT`]. sinfo prints a line
+    /// per node and partition; these are merged so each node appears once,
+    /// in first-seen order, with all its partitions.
+    ///
+    /// [`SINFO_FORMAT`]: crate::slurm::SINFO_FORMAT
+    pub fn parse_sinfo_output(output: &str) -> Result<Vec<Node>> {
+        let mut nodes: Vec<Node> = Vec::new();
+        let mut columns: Vec<String> = Vec::new();
+        // With -M, each cluster's table is preceded by "CLUSTER: <name>"
+        let mut cluster: Option<String> = None;
 
         for line in output.lines() {
-            for cap in re.captures_iter(line) {
-                let key = cap[1].to_string();
-                let value = cap[2].trim_matches('"').to_string();
-                fields.insert(key, value);
+            if let Some(name) = line.strip_prefix("CLUSTER:") {
+                cluster = Some(name.trim().to_string());
+                continue;
+            }
+            if line.starts_with("NODELIST") {
+                columns = line.split('|').map(|c| c.trim().to_string()).collect();
+                continue;
+            }
+            if line.trim().is_empty() || columns.is_empty() {
+                continue;
+            }
+
+            let values: Vec<&str> = line.split('|').collect();
+            let field = |name: &str| {
+                columns
+                    .iter()
+                    .position(|c| c == name)
+                    .and_then(|i| values.get(i))
+                    .map(|v| v.trim())
+                    .filter(|v| !v.is_empty() && !matches!(*v, "N/A" | "(null)" | "none"))
+            };
+
+            let Some(node_list) = field("NODELIST") else {
+                continue;
+            };
+            let partition = field("PARTITION");
+
+            for name in Self::expand_hostlist(node_list) {
+                if let Some(node) = nodes
+                    .iter_mut()
+                    .find(|node| node.name == name && node.cluster == cluster)
+                {
+                    if let Some(partition) = partition {
+                        node.partitions.push(partition.to_string());
+                    }
+                    continue;
+                }
+
+                let mut node = Node::new(name, NodeState::from(field("STATE").unwrap_or("")));
+                node.cluster = cluster.clone();
+                node.partitions.extend(partition.map(str::to_string));
+
+                if let Some([alloc, idle, _other, total]) =
+                    field("CPUS(A/I/O/T)").and_then(Self::parse_counts)
+                {
+                    node.cpus_alloc = alloc;
+                    node.cpus_idle = idle;
+                    node.cpus_total = total;
+                }
+
+                node.memory = field("MEMORY").and_then(|m| m.parse().ok());
+                node.memory_free = field("FREE_MEM").and_then(|m| m.parse().ok());
+                node.gres = field("GRES").map(str::to_string);
+                node.features = field("AVAIL_FEATURES")
+                    .map(|f| f.split(',').map(str::to_string).collect())
+                    .unwrap_or_default();
+                node.reason = field("REASON").map(str::to_string);
+
+                nodes.push(node);
+            }
+        }
+
+        Ok(nodes)
+    }
+
+    /// Parse `sinfo` output in [`PARTITION_FORMAT`]. The default partition,
+    /// printed with a trailing `*`, is flagged and the `*` dropped.
+    ///
+    /// [`PARTITION_FORMAT`]: crate::slurm::PARTITION_FORMAT
+    pub fn parse_sinfo_partitions(output: &str) -> Result<Vec<Partition>> {
+        let mut partitions: Vec<Partition> = Vec::new();
+        let mut columns: Vec<String> = Vec::new();
+        // With -M, each cluster's table is preceded by "CLUSTER: <name>"
+        let mut cluster: Option<String> = None;
+
+        for line in output.lines() {
+            if let Some(name) = line.strip_prefix("CLUSTER:") {
+                cluster = Some(name.trim().to_string());
+                continue;
+            }
+            if line.starts_with("PARTITION") {
+                columns = line.split('|').map(|c| c.trim().to_string()).collect();
+                continue;
+            }
+            if line.trim().is_empty() || columns.is_empty() {
+                continue;
+            }
+
+            let values: Vec<&str> = line.split('|').collect();
+            let field = |name: &str| {
+                columns
+                    .iter()
+                    .position(|c| c == name)
+                    .and_then(|i| values.get(i))
+                    .map(|v| v.trim())
+                    .filter(|v| !v.is_empty() && *v != "n/a")
+            };
+
+            let Some(name) = field("PARTITION") else {
+                continue;
+            };
+            let is_default = name.ends_with('*');
+            let name = name.trim_end_matches('*');
+            let nodes = field("NODES(A/I/O/T)").and_then(Self::parse_counts);
+            let cpus = field("CPUS(A/I/O/T)").and_then(Self::parse_counts);
+
+            // sinfo splits a partition over several lines when the lines
+            // would otherwise differ, so add those up
+            if let Some(partition) = partitions
+                .iter_mut()
+                .find(|p| p.name == name && p.cluster == cluster)
+            {
+                if let Some([alloc, idle, other, total]) = nodes {
+                    partition.nodes_alloc += alloc;
+                    partition.nodes_idle += idle;
+                    partition.nodes_other += other;
+                    partition.nodes_total += total;
+                }
+                if let Some([alloc, idle, other, total]) = cpus {
+                    partition.cpus_alloc += alloc;
+                    partition.cpus_idle += idle;
+                    partition.cpus_other += other;
+                    partition.cpus_total += total;
+                }
+                continue;
+            }
+
+            let mut partition = Partition::new(
+                name.to_string(),
+                PartitionState::from(field("AVAIL").unwrap_or("")),
+            );
+            partition.cluster = cluster.clone();
+            partition.is_default = is_default;
+            partition.time_limit = field("TIMELIMIT").map(str::to_string);
+            partition.default_time = field("DEFAULTTIME").map(str::to_string);
+            if let Some([alloc, idle, other, total]) = nodes {
+                partition.nodes_alloc = alloc;
+                partition.nodes_idle = idle;
+                partition.nodes_other = other;
+                partition.nodes_total = total;
+            }
+            if let Some([alloc, idle, other, total]) = cpus {
+                partition.cpus_alloc = alloc;
+                partition.cpus_idle = idle;
+                partition.cpus_other = other;
+                partition.cpus_total = total;
+            }
+
+            partitions.push(partition);
+        }
+
+        Ok(partitions)
+    }
+
+    /// Parse `scontrol show partition` output into fields keyed by
+    /// partition name.
+    pub fn parse_scontrol_partitions(
+        output: &str,
+    ) -> Result<HashMap<String, HashMap<String, String>>> {
+        Ok(Self::scontrol_records(output)?
+            .into_iter()
+            .filter_map(|fields| Some((fields.get("PartitionName")?.clone(), fields)))
+            .collect())
+    }
+
+    /// Add the memory limits sinfo cannot print from `scontrol show
+    /// partition` fields. Slurm sets either the per-CPU or the per-node
+    /// variant of each.
+    pub fn enhance_partition_with_scontrol_data(
+        partition: &mut Partition,
+        scontrol_fields: HashMap<String, String>,
+    ) {
+        let memory = |per_cpu: &str, per_node: &str| match (
+            scontrol_fields.get(per_cpu),
+            scontrol_fields.get(per_node),
+        ) {
+            (Some(mb), _) => Some(format!("{}M per CPU", mb)),
+            (None, Some(mb)) if mb == "UNLIMITED" => Some(mb.clone()),
+            (None, Some(mb)) => Some(format!("{}M per node", mb)),
+            (None, None) => None,
+        };
+        partition.default_memory = memory("DefMemPerCPU", "DefMemPerNode");
+        partition.max_memory = memory("MaxMemPerCPU", "MaxMemPerNode");
+
+        if partition.time_limit.is_none() {
+            partition.time_limit = scontrol_fields.get("MaxTime").cloned();
+        }
+
+        if partition.default_time.is_none() {
+            partition.default_time = scontrol_fields
+                .get("DefaultTime")
+                .filter(|t| *t != "NONE")
+                .cloned();
+        }
+    }
+
+    /// Split sinfo's "allocated/idle/other/total" counts.
+    fn parse_counts(counts: &str) -> Option<[u32; 4]> {
+        let counts: Vec<u32> = counts
+            .split('/')
+            .map(|n| n.parse().ok())
+            .collect::<Option<_>>()?;
+        counts.try_into().ok()
+    }
+
+    /// Fill in what sinfo does not report from `scontrol show node` fields.
+    pub fn enhance_node_with_scontrol_data(
+        node: &mut Node,
+        scontrol_fields: HashMap<String, String>,
+    ) {
+        let field = |name: &str| {
+            scontrol_fields
+                .get(name)
+                .filter(|v| !matches!(v.as_str(), "N/A" | "(null)"))
+        };
+
+        if let Some(cpu_load) = field("CPULoad") {
+            node.cpu_load = cpu_load.parse().ok();
+        }
+
+        if let Some(alloc_mem) = field("AllocMem") {
+            node.memory_alloc = alloc_mem.parse().ok();
+        }
+
+        if node.memory.is_none() {
+            node.memory = field("RealMemory").and_then(|m| m.parse().ok());
+        }
+
+        if node.memory_free.is_none() {
+            node.memory_free = field("FreeMem").and_then(|m| m.parse().ok());
+        }
+
+        if node.gres.is_none() {
+            node.gres = field("Gres").cloned();
+        }
+
+        if node.features.is_empty()
+            && let Some(features) = field("AvailableFeatures")
+        {
+            node.features = features.split(',').map(str::to_string).collect();
+        }
+    }
+
+    /// Expand a Slurm hostlist such as `gpu[01-03,07],login1` into node
+    /// names. Zero padding in ranges is kept. A pattern that would take the
+    /// list past [`MAX_HOSTLIST_HOSTS`] names is kept as written.
+    pub fn expand_hostlist(list: &str) -> Vec<String> {
+        let mut hosts = Vec::new();
+
+        // Split on commas outside brackets
+        let mut depth = 0;
+        let mut start = 0;
+        for (i, c) in list.char_indices() {
+            match c {
+                '[' => depth += 1,
+                ']' => depth -= 1,
+                ',' if depth == 0 => {
+                    Self::expand_pattern(&list[start..i], &mut hosts);
+                    start = i + 1;
+                }
+                _ => {}
             }
         }
+        Self::expand_pattern(&list[start..], &mut hosts);
+
+        hosts
+    }
 
-        Ok(fields)
+    fn expand_pattern(pattern: &str, hosts: &mut Vec<String>) {
+        let expanded = hosts.len();
+        if !Self::expand_host(pattern, hosts) {
+            hosts.truncate(expanded);
+            hosts.push(pattern.trim().to_string());
+        }
+    }
+
+    /// False once `hosts` would grow past [`MAX_HOSTLIST_HOSTS`].
+    fn expand_host(pattern: &str, hosts: &mut Vec<String>) -> bool {
+        let pattern = pattern.trim();
+        let bracket = pattern
+            .find('[')
+            .and_then(|open| Some((open, open + pattern[open..].find(']')?)));
+        let Some((open, close)) = bracket else {
+            if !pattern.is_empty() {
+                if hosts.len() == MAX_HOSTLIST_HOSTS {
+                    return false;
+                }
+                hosts.push(pattern.to_string());
+            }
+            return true;
+        };
+
+        let prefix = &pattern[..open];
+        let suffix = &pattern[close + 1..];
+        for range in pattern[open + 1..close].split(',') {
+            let expanded = match range.split_once('-') {
+                Some((low, high)) => {
+                    let (Ok(first), Ok(last)) = (low.parse::<u64>(), high.parse::<u64>()) else {
+                        continue;
+                    };
+                    let width = low.len();
+                    (first..=last)
+                        .all(|n| Self::expand_host(&format!("{prefix}{n:0width$}{suffix}"), hosts))
+                }
+                None => Self::expand_host(&format!("{prefix}{range}{suffix}"), hosts),
+            };
+            if !expanded {
+                return false;
+            }
+        }
+        true
     }
 
     pub fn enhance_job_with_scontrol_data(job: &mut Job, scontrol_fields: HashMap<String, String>) {
@@ -74,8 +837,13 @@ impl SlurmParser {
             job.submit_time = Self::parse_slurm_time(submit_time);
         }
 
+        // A pending job's StartTime is when the scheduler expects to start it
         if let Some(start_time) = scontrol_fields.get("StartTime") {
-            job.start_time = Self::parse_slurm_time(start_time);
+            if job.state == JobState::Pending {
+                job.estimated_start = Self::parse_slurm_time(start_time);
+            } else {
+                job.start_time = Self::parse_slurm_time(start_time);
+            }
         }
 
         if let Some(end_time) = scontrol_fields.get("EndTime") {
@@ -106,6 +874,47 @@ impl SlurmParser {
             job.memory = Some(memory.clone());
         }
 
+        if let Some(memory) = scontrol_fields.get("MinMemoryCPU") {
+            job.memory_per_cpu = Some(memory.clone());
+        }
+
+        if let Some(tasks) = scontrol_fields.get("NumTasks") {
+            job.num_tasks = tasks.parse().ok();
+        }
+
+        if let Some(cpus) = scontrol_fields.get("CPUs/Task") {
+            job.cpus_per_task = cpus.parse().ok();
+        }
+
+        let set = |key: &str| {
+            scontrol_fields
+                .get(key)
+                .filter(|value| !value.is_empty() && *value != "(null)" && *value != "N/A")
+        };
+
+        if let Some(tres) = set("TresPerNode") {
+            job.gres = Some(tres_to_gres(tres));
+        }
+
+        if let Some(account) = set("Account") {
+            job.account = Some(account.clone());
+        }
+
+        if let Some(qos) = set("QOS") {
+            job.qos = Some(qos.clone());
+        }
+
+        if let Some(flag) = scontrol_fields.get("BatchFlag") {
+            job.batch = Some(flag != "0");
+        }
+
+        // For srun and salloc jobs, Command is the program they run
+        if job.batch != Some(false)
+            && let Some(command) = set("Command")
+        {
+            job.command = Some(command.clone());
+        }
+
         if let Some(reason) = scontrol_fields.get("Reason") {
             job.reason = Some(reason.clone());
         }
@@ -122,6 +931,22 @@ impl SlurmParser {
         }
     }
 
+    /// Split an array task id such as "23673084_5" into
+    /// `array_job_id = 23673084` and `array_task_id = 5`.
+    fn apply_array_id(job: &mut Job) {
+        if job.job_id.contains('_') {
+            let array_parts: Vec<&str> = job.job_id.split('_').collect();
+            if array_parts.len() == 2 {
+                job.array_job_id = Some(array_parts[0].to_string());
+                job.array_task_id = array_parts[1].parse().ok();
+            }
+        }
+    }
+
+    /// Slurm's text output gives local wall-clock times. They are read in
+    /// this machine's time zone, which is the cluster's unless it is
+    /// reached with `--remote` from elsewhere, and kept as UTC like the
+    /// JSON timestamps.
     fn parse_slurm_time(time_str: &str) -> Option<DateTime<Utc>> {
         // SLURM time formats: "2024-01-15T10:19:13" or "2024-01-15T10:19:13.123"
         // Sometimes also "Unknown" or "None" for jobs that haven't started
@@ -129,17 +954,17 @@ impl SlurmParser {
             return None;
         }
 
-        // Try parsing with seconds
-        if let Ok(dt) = NaiveDateTime::parse_from_str(time_str, "%Y-%m-%dT%H:%M:%S") {
-            return Some(dt.and_utc());
-        }
-
-        // Try parsing with microseconds
-        if let Ok(dt) = NaiveDateTime::parse_from_str(time_str, "%Y-%m-%dT%H:%M:%S%.f") {
-            return Some(dt.and_utc());
-        }
-
-        None
+        let local = NaiveDateTime::parse_from_str(time_str, "%Y-%m-%dT%H:%M:%S")
+            // Try parsing with microseconds
+            .or_else(|_| NaiveDateTime::parse_from_str(time_str, "%Y-%m-%dT%H:%M:%S%.f"))
+            .ok()?;
+        // A time skipped by a DST change has no earliest; keep it as is
+        Some(
+            Local
+                .from_local_datetime(&local)
+                .earliest()
+                .map_or_else(|| local.and_utc(), |time| time.with_timezone(&Utc)),
+        )
     }
 
     pub fn get_job_log_paths(job: &Job) -> Vec<String> {
@@ -174,3 +999,16 @@ impl SlurmParser {
         paths
     }
 }
+
+/// TRES per node as scontrol and the JSON output print them, e.g.
+/// "gres/gpu:1" or before 21.08 "gres:gpu:1", in sbatch's `--gres` form.
+pub(crate) fn tres_to_gres(tres: &str) -> String {
+    tres.split(',')
+        .map(|tres| {
+            tres.strip_prefix("gres/")
+                .or_else(|| tres.strip_prefix("gres:"))
+                .unwrap_or(tres)
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
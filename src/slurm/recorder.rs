@@ -0,0 +1,438 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::slurm::executor::{CancelOptions, SignalScope, SlurmExecutor};
+
+/// Wraps another [`SlurmExecutor`], passing every call through and saving
+/// each response in the layout [`SlurmFixture`] reads, so a session can be
+/// replayed later, e.g. to reproduce a bug report.
+///
+/// Later responses overwrite earlier ones for the same file. Responses
+/// for a named cluster, or `-M` list, go in a directory of that name,
+/// where the fixture executor looks first for calls naming it, so a
+/// session across clusters replays as well. Every call, including failed
+/// ones, is appended to `manifest.jsonl` together with its arguments,
+/// after a first line naming the lazyslurm version. Job logs are passed
+/// through but not recorded, and `scancel` really cancels.
+///
+/// A failed write never fails the call it records: the session goes on,
+/// and the failure is reported through [`SlurmExecutor::status`].
+///
+/// [`SlurmFixture`]: crate::slurm::SlurmFixture
+pub struct SlurmRecorder {
+    inner: Arc<dyn SlurmExecutor>,
+    fixture_dir: PathBuf,
+    manifest: Mutex<File>,
+    /// The last write that failed.
+    write_error: Mutex<Option<String>>,
+}
+
+/// The first line of `manifest.jsonl`.
+#[derive(Debug, Serialize)]
+struct ManifestHeader {
+    lazyslurm_version: &'static str,
+}
+
+/// One executor call as listed in `manifest.jsonl`.
+#[derive(Debug, Serialize)]
+struct RecordedCall {
+    call: &'static str,
+    args: BTreeMap<&'static str, String>,
+    /// Fixture file holding the response; `None` for failed calls and
+    /// calls without output.
+    file: Option<String>,
+    error: Option<String>,
+}
+
+impl SlurmRecorder {
+    pub fn new(inner: Arc<dyn SlurmExecutor>, fixture_dir: impl Into<PathBuf>) -> Result<Self> {
+        let fixture_dir = fixture_dir.into();
+        std::fs::create_dir_all(&fixture_dir)
+            .with_context(|| format!("Failed to create {}", fixture_dir.display()))?;
+
+        let path = fixture_dir.join("manifest.jsonl");
+        let manifest = File::create(&path)
+            .and_then(|mut file| {
+                let header = ManifestHeader {
+                    lazyslurm_version: env!("CARGO_PKG_VERSION"),
+                };
+                writeln!(file, "{}", serde_json::to_string(&header)?)?;
+                Ok(file)
+            })
+            .with_context(|| format!("Failed to write manifest: {}", path.display()))?;
+
+        Ok(Self {
+            inner,
+            fixture_dir,
+            manifest: Mutex::new(manifest),
+            write_error: Mutex::new(None),
+        })
+    }
+
+    pub fn fixture_dir(&self) -> &Path {
+        &self.fixture_dir
+    }
+
+    /// Save a successful response to `file` and list the call either way.
+    /// `file` goes in the directory of the cluster in `args`, if any.
+    fn record(
+        &self,
+        call: &'static str,
+        args: BTreeMap<&'static str, String>,
+        file: &str,
+        result: &Result<String>,
+    ) {
+        let file = match args.get("cluster").or_else(|| args.get("clusters")) {
+            Some(cluster) => format!("{}/{}", cluster, file),
+            None => file.to_string(),
+        };
+        if let Ok(output) = result {
+            let path = self.fixture_dir.join(&file);
+            let written = path
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|()| std::fs::write(&path, output))
+                .with_context(|| format!("Failed to write fixture: {}", path.display()));
+            self.note_failure(written);
+        }
+
+        self.append(RecordedCall {
+            call,
+            args,
+            file: result.is_ok().then_some(file),
+            error: result.as_ref().err().map(|e| e.to_string()),
+        });
+    }
+
+    /// Note a call that acts on a job; it has no output to save.
+    fn record_action(
+        &self,
+        call: &'static str,
+        job_id: &str,
+        cluster: Option<&str>,
+        result: &Result<()>,
+    ) {
+        self.record_call(
+            call,
+            args(&[("job_id", Some(job_id)), ("cluster", cluster)]),
+            result,
+        )
+    }
+
+    fn record_call(
+        &self,
+        call: &'static str,
+        args: BTreeMap<&'static str, String>,
+        result: &Result<()>,
+    ) {
+        self.append(RecordedCall {
+            call,
+            args,
+            file: None,
+            error: result.as_ref().err().map(|e| e.to_string()),
+        });
+    }
+
+    fn append(&self, call: RecordedCall) {
+        let path = self.fixture_dir.join("manifest.jsonl");
+        let written = serde_json::to_string(&call)
+            .map_err(io::Error::from)
+            .and_then(|line| writeln!(self.manifest.lock().unwrap(), "{}", line))
+            .with_context(|| format!("Failed to write manifest: {}", path.display()));
+        self.note_failure(written);
+    }
+
+    fn note_failure(&self, written: Result<()>) {
+        if let Err(err) = written {
+            *self.write_error.lock().unwrap() = Some(format!("Recording failed: {err:#}"));
+        }
+    }
+}
+
+/// Arguments worth listing in the manifest; unset filters are left out.
+fn args(pairs: &[(&'static str, Option<&str>)]) -> BTreeMap<&'static str, String> {
+    pairs
+        .iter()
+        .filter_map(|(name, value)| value.map(|v| (*name, v.to_string())))
+        .collect()
+}
+
+#[async_trait]
+impl SlurmExecutor for SlurmRecorder {
+    async fn squeue(
+        &self,
+        user: Option<&str>,
+        partition: Option<&str>,
+        clusters: Option<&str>,
+    ) -> Result<String> {
+        let result = self.inner.squeue(user, partition, clusters).await;
+        let args = args(&[
+            ("user", user),
+            ("partition", partition),
+            ("clusters", clusters),
+        ]);
+        self.record("squeue", args, "squeue.txt", &result);
+        result
+    }
+
+    async fn squeue_start(
+        &self,
+        user: Option<&str>,
+        partition: Option<&str>,
+        clusters: Option<&str>,
+    ) -> Result<String> {
+        let result = self.inner.squeue_start(user, partition, clusters).await;
+        let args = args(&[
+            ("user", user),
+            ("partition", partition),
+            ("clusters", clusters),
+        ]);
+        self.record("squeue_start", args, "squeue_start.txt", &result);
+        result
+    }
+
+    async fn scontrol_show_job(&self, job_id: &str, cluster: Option<&str>) -> Result<String> {
+        let result = self.inner.scontrol_show_job(job_id, cluster).await;
+        let args = args(&[("job_id", Some(job_id)), ("cluster", cluster)]);
+        let file = format!("scontrol/{}.txt", job_id);
+        self.record("scontrol_show_job", args, &file, &result);
+        result
+    }
+
+    async fn scontrol_show_jobs(&self, cluster: Option<&str>) -> Result<String> {
+        let result = self.inner.scontrol_show_jobs(cluster).await;
+        let args = args(&[("cluster", cluster)]);
+        self.record("scontrol_show_jobs", args, "scontrol.txt", &result);
+        result
+    }
+
+    async fn squeue_json(
+        &self,
+        user: Option<&str>,
+        partition: Option<&str>,
+        clusters: Option<&str>,
+    ) -> Result<String> {
+        let result = self.inner.squeue_json(user, partition, clusters).await;
+        let args = args(&[
+            ("user", user),
+            ("partition", partition),
+            ("clusters", clusters),
+        ]);
+        self.record("squeue_json", args, "squeue.json", &result);
+        result
+    }
+
+    async fn version(&self) -> Result<String> {
+        let result = self.inner.version().await;
+        self.record("version", BTreeMap::new(), "version.txt", &result);
+        result
+    }
+
+    async fn scancel(
+        &self,
+        job_id: &str,
+        cluster: Option<&str>,
+        options: &CancelOptions,
+    ) -> Result<()> {
+        let result = self.inner.scancel(job_id, cluster, options).await;
+        let scope = (options.scope != SignalScope::default()).then(|| options.scope.label());
+        self.record_call(
+            "scancel",
+            args(&[
+                ("job_id", Some(job_id)),
+                ("cluster", cluster),
+                ("signal", options.signal.as_deref()),
+                ("scope", scope.as_deref()),
+            ]),
+            &result,
+        );
+        result
+    }
+
+    async fn hold(&self, job_id: &str, cluster: Option<&str>) -> Result<()> {
+        let result = self.inner.hold(job_id, cluster).await;
+        self.record_action("hold", job_id, cluster, &result);
+        result
+    }
+
+    async fn release(&self, job_id: &str, cluster: Option<&str>) -> Result<()> {
+        let result = self.inner.release(job_id, cluster).await;
+        self.record_action("release", job_id, cluster, &result);
+        result
+    }
+
+    async fn requeue(&self, job_id: &str, cluster: Option<&str>) -> Result<()> {
+        let result = self.inner.requeue(job_id, cluster).await;
+        self.record_action("requeue", job_id, cluster, &result);
+        result
+    }
+
+    async fn update_job(
+        &self,
+        job_id: &str,
+        cluster: Option<&str>,
+        changes: &[(String, String)],
+    ) -> Result<()> {
+        let result = self.inner.update_job(job_id, cluster, changes).await;
+        let changes: Vec<String> = changes
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect();
+        self.record_call(
+            "update_job",
+            args(&[
+                ("job_id", Some(job_id)),
+                ("cluster", cluster),
+                ("changes", Some(&changes.join(" "))),
+            ]),
+            &result,
+        );
+        result
+    }
+
+    async fn sbatch(
+        &self,
+        script: &str,
+        cluster: Option<&str>,
+        options: &[(String, String)],
+    ) -> Result<String> {
+        let result = self.inner.sbatch(script, cluster, options).await;
+        let options: Vec<String> = options
+            .iter()
+            .map(|(option, value)| format!("--{}={}", option, value))
+            .collect();
+        self.record(
+            "sbatch",
+            args(&[
+                ("script", Some(script)),
+                ("cluster", cluster),
+                ("options", Some(&options.join(" "))),
+            ]),
+            "sbatch.txt",
+            &result,
+        );
+        result
+    }
+
+    async fn sacct(
+        &self,
+        user: Option<&str>,
+        partition: Option<&str>,
+        clusters: Option<&str>,
+        window: Duration,
+    ) -> Result<String> {
+        let result = self.inner.sacct(user, partition, clusters, window).await;
+        let window = format!("{}s", window.as_secs());
+        let args = args(&[
+            ("user", user),
+            ("partition", partition),
+            ("clusters", clusters),
+            ("window", Some(&window)),
+        ]);
+        self.record("sacct", args, "sacct.txt", &result);
+        result
+    }
+
+    async fn sacct_job(&self, job_id: &str, cluster: Option<&str>) -> Result<String> {
+        let result = self.inner.sacct_job(job_id, cluster).await;
+        let args = args(&[("job_id", Some(job_id)), ("cluster", cluster)]);
+        let file = format!("sacct/{}.txt", job_id);
+        self.record("sacct_job", args, &file, &result);
+        result
+    }
+
+    async fn sprio(&self, partition: Option<&str>, cluster: Option<&str>) -> Result<String> {
+        let result = self.inner.sprio(partition, cluster).await;
+        let args = args(&[("partition", partition), ("cluster", cluster)]);
+        self.record("sprio", args, "sprio.txt", &result);
+        result
+    }
+
+    async fn sshare(&self, user: Option<&str>, cluster: Option<&str>) -> Result<String> {
+        let result = self.inner.sshare(user, cluster).await;
+        let args = args(&[("user", user), ("cluster", cluster)]);
+        self.record("sshare", args, "sshare.txt", &result);
+        result
+    }
+
+    async fn sstat(&self, job_id: &str, cluster: Option<&str>) -> Result<String> {
+        let result = self.inner.sstat(job_id, cluster).await;
+        let args = args(&[("job_id", Some(job_id)), ("cluster", cluster)]);
+        let file = format!("sstat/{}.txt", job_id);
+        self.record("sstat", args, &file, &result);
+        result
+    }
+
+    async fn sinfo(&self, partition: Option<&str>, clusters: Option<&str>) -> Result<String> {
+        let result = self.inner.sinfo(partition, clusters).await;
+        let args = args(&[("partition", partition), ("clusters", clusters)]);
+        self.record("sinfo", args, "sinfo.txt", &result);
+        result
+    }
+
+    async fn scontrol_show_nodes(&self, cluster: Option<&str>) -> Result<String> {
+        let result = self.inner.scontrol_show_nodes(cluster).await;
+        let args = args(&[("cluster", cluster)]);
+        self.record("scontrol_show_nodes", args, "scontrol_nodes.txt", &result);
+        result
+    }
+
+    async fn sinfo_partitions(&self, clusters: Option<&str>) -> Result<String> {
+        let result = self.inner.sinfo_partitions(clusters).await;
+        let args = args(&[("clusters", clusters)]);
+        self.record("sinfo_partitions", args, "sinfo_partitions.txt", &result);
+        result
+    }
+
+    async fn scontrol_show_partitions(&self, cluster: Option<&str>) -> Result<String> {
+        let result = self.inner.scontrol_show_partitions(cluster).await;
+        let args = args(&[("cluster", cluster)]);
+        self.record(
+            "scontrol_show_partitions",
+            args,
+            "scontrol_partitions.txt",
+            &result,
+        );
+        result
+    }
+
+    async fn clusters(&self) -> Result<String> {
+        let result = self.inner.clusters().await;
+        self.record("clusters", BTreeMap::new(), "clusters.txt", &result);
+        result
+    }
+
+    async fn tail_file(&self, path: &str, lines: usize) -> Result<String> {
+        self.inner.tail_file(path, lines).await
+    }
+
+    fn shell_command(
+        &self,
+        job_id: &str,
+        cluster: Option<&str>,
+        node: Option<&str>,
+    ) -> Result<Vec<String>> {
+        self.inner.shell_command(job_id, cluster, node)
+    }
+
+    fn status(&self) -> Option<String> {
+        self.inner
+            .status()
+            .or_else(|| self.write_error.lock().unwrap().clone())
+    }
+
+    fn is_remote(&self) -> bool {
+        self.inner.is_remote()
+    }
+
+    fn shows_job_details(&self) -> bool {
+        self.inner.shows_job_details()
+    }
+}
@@ -0,0 +1,160 @@
+use anyhow::{Context, Result};
+use std::path::Path;
+use tokio::process::Command as TokioCommand;
+
+/// A command prefix that runs Slurm tools somewhere else, e.g.
+/// `ssh -o BatchMode=yes login1 --`.
+///
+/// The Slurm command line is shell-quoted and appended as a *single*
+/// argument, so the wrapper must hand it to a shell the way ssh does.
+/// When the wrapper is ssh, a control-master connection is reused across
+/// calls unless the prefix already configures one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandWrapper {
+    prefix: Vec<String>,
+}
+
+impl CommandWrapper {
+    pub fn new(prefix: Vec<String>) -> Result<Self> {
+        anyhow::ensure!(!prefix.is_empty(), "remote command is empty");
+
+        let mut prefix = prefix;
+        if is_ssh(&prefix[0]) && !prefix.iter().any(|arg| arg.contains("ControlPath")) {
+            let options = [
+                "-o".to_string(),
+                "ControlMaster=auto".to_string(),
+                "-o".to_string(),
+                format!("ControlPath={}", control_path()),
+                "-o".to_string(),
+                "ControlPersist=10m".to_string(),
+            ];
+            prefix.splice(1..1, options);
+        }
+
+        Ok(Self { prefix })
+    }
+
+    /// Parse a prefix written as one string, e.g. from `--remote`.
+    pub fn parse(command_line: &str) -> Result<Self> {
+        Self::new(split_command_line(command_line)?)
+    }
+
+    pub fn prefix(&self) -> &[String] {
+        &self.prefix
+    }
+
+    /// Build the wrapped command for `program args...`.
+    pub fn command(&self, program: &str, args: &[String]) -> TokioCommand {
+        let mut cmd = TokioCommand::new(&self.prefix[0]);
+        cmd.args(&self.prefix[1..])
+            .arg(self.remote_command_line(program, args));
+        cmd
+    }
+
+    /// The quoted command line the remote shell will run.
+    pub fn remote_command_line(&self, program: &str, args: &[String]) -> String {
+        std::iter::once(program)
+            .chain(args.iter().map(String::as_str))
+            .map(shell_quote)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// The wrapped command line for `program args...` when it runs on the
+    /// terminal. ssh is asked for a terminal with `-t`; other wrappers have
+    /// to provide one themselves.
+    pub fn interactive_command(&self, program: &str, args: &[String]) -> Vec<String> {
+        let mut command = self.prefix.clone();
+        if is_ssh(&command[0]) {
+            command.insert(1, "-t".to_string());
+        }
+        command.push(self.remote_command_line(program, args));
+        command
+    }
+
+    /// Blocking check that `squeue` exists on the other side.
+    pub fn check_available(&self) -> bool {
+        std::process::Command::new(&self.prefix[0])
+            .args(&self.prefix[1..])
+            .arg("command -v squeue")
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false)
+    }
+}
+
+fn is_ssh(program: &str) -> bool {
+    Path::new(program)
+        .file_name()
+        .is_some_and(|name| name == "ssh")
+}
+
+/// Where the control-master socket lives: the user's runtime directory,
+/// or `~/.ssh` (which ssh expands) when there is none. Both are private
+/// to the user, unlike `/tmp`.
+fn control_path() -> String {
+    match std::env::var("XDG_RUNTIME_DIR") {
+        Ok(dir) if !dir.is_empty() => format!("{}/lazyslurm-%C", dir.trim_end_matches('/')),
+        _ => "~/.ssh/lazyslurm-%C".to_string(),
+    }
+}
+
+/// Quote `arg` for a POSIX shell, leaving plain words untouched.
+pub fn shell_quote(arg: &str) -> String {
+    let is_plain = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_@%+=:,./-".contains(c));
+    if is_plain {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+/// Split a command line on whitespace, honouring single quotes, double
+/// quotes and backslash escapes.
+pub fn split_command_line(line: &str) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    let mut current: Option<String> = None;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if let Some(word) = current.take() {
+                    words.push(word);
+                }
+            }
+            '\'' => {
+                let word = current.get_or_insert_with(String::new);
+                loop {
+                    match chars.next().context("unterminated single quote")? {
+                        '\'' => break,
+                        c => word.push(c),
+                    }
+                }
+            }
+            '"' => {
+                let word = current.get_or_insert_with(String::new);
+                loop {
+                    match chars.next().context("unterminated double quote")? {
+                        '"' => break,
+                        '\\' => word.push(chars.next().context("trailing backslash")?),
+                        c => word.push(c),
+                    }
+                }
+            }
+            '\\' => {
+                let escaped = chars.next().context("trailing backslash")?;
+                current.get_or_insert_with(String::new).push(escaped);
+            }
+            c => current.get_or_insert_with(String::new).push(c),
+        }
+    }
+
+    if let Some(word) = current {
+        words.push(word);
+    }
+    Ok(words)
+}
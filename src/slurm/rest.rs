@@ -0,0 +1,275 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use reqwest::{Client, Method, RequestBuilder};
+use serde_json::Value;
+use std::time::Duration;
+
+use crate::slurm::commands::read_tail;
+use crate::slurm::executor::{CancelOptions, SignalScope, SlurmExecutor};
+use crate::slurm::json::JobsResponse;
+
+/// Default slurmrestd API version; served by Slurm 23.11 through 24.11.
+pub const DEFAULT_REST_API_VERSION: &str = "v0.0.40";
+
+/// A [`SlurmExecutor`] that talks to slurmrestd instead of running the CLI
+/// tools. Responses use the same schema as `squeue --json`, so only the JSON
+/// methods are supported; the app picks them because [`Self::version`]
+/// always reports a JSON-capable Slurm. slurmrestd serves a single cluster,
+/// so cluster arguments are ignored.
+pub struct SlurmRest {
+    base_url: String,
+    api_version: String,
+    token: Option<String>,
+    user_name: Option<String>,
+    client: Client,
+}
+
+impl SlurmRest {
+    pub fn new(base_url: impl Into<String>, token: Option<String>) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            api_version: DEFAULT_REST_API_VERSION.to_string(),
+            token,
+            user_name: None,
+            client: Client::new(),
+        }
+    }
+
+    pub fn with_api_version(mut self, api_version: impl Into<String>) -> Self {
+        self.api_version = api_version.into();
+        self
+    }
+
+    /// Sent as `X-SLURM-USER-NAME`, needed when the token was issued to a
+    /// privileged user acting on behalf of someone else. Unset, slurmrestd
+    /// acts as the user the token names.
+    pub fn with_user_name(mut self, user_name: Option<String>) -> Self {
+        self.user_name = user_name;
+        self
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let url = format!("{}/slurm/{}/{}", self.base_url, self.api_version, path);
+        let mut request = self.client.request(method, url);
+        if let Some(token) = &self.token {
+            request = request.header("X-SLURM-USER-TOKEN", token);
+        }
+        if let Some(user_name) = &self.user_name {
+            request = request.header("X-SLURM-USER-NAME", user_name);
+        }
+        request
+    }
+
+    async fn send(&self, method: Method, path: &str) -> Result<String> {
+        let response = self
+            .request(method, path)
+            .send()
+            .await
+            .with_context(|| format!("Failed to reach slurmrestd at {}", self.base_url))?;
+
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .context("Failed to read slurmrestd response")?;
+
+        if !status.is_success() {
+            let detail = serde_json::from_str::<JobsResponse>(&body)
+                .ok()
+                .and_then(|r| r.errors.first().map(|e| e.message()))
+                .unwrap_or(body);
+            anyhow::bail!("slurmrestd {} failed ({}): {}", path, status, detail);
+        }
+
+        Ok(body)
+    }
+}
+
+fn unsupported(what: &str) -> anyhow::Error {
+    anyhow::anyhow!("{} is not available with the REST backend", what)
+}
+
+#[async_trait]
+impl SlurmExecutor for SlurmRest {
+    async fn squeue(
+        &self,
+        _user: Option<&str>,
+        _partition: Option<&str>,
+        _clusters: Option<&str>,
+    ) -> Result<String> {
+        Err(unsupported("squeue text output"))
+    }
+
+    async fn squeue_start(
+        &self,
+        _user: Option<&str>,
+        _partition: Option<&str>,
+        _clusters: Option<&str>,
+    ) -> Result<String> {
+        // Pending jobs' start_time in the jobs response is the estimate
+        Err(unsupported("squeue --start"))
+    }
+
+    async fn scontrol_show_job(&self, _job_id: &str, _cluster: Option<&str>) -> Result<String> {
+        Err(unsupported("scontrol text output"))
+    }
+
+    async fn scontrol_show_jobs(&self, _cluster: Option<&str>) -> Result<String> {
+        Err(unsupported("scontrol text output"))
+    }
+
+    async fn squeue_json(
+        &self,
+        user: Option<&str>,
+        partition: Option<&str>,
+        _clusters: Option<&str>,
+    ) -> Result<String> {
+        let body = self.send(Method::GET, "jobs").await?;
+        if user.is_none() && partition.is_none() {
+            return Ok(body);
+        }
+
+        // /jobs has no server-side filters, so apply squeue's -u/-p here
+        let mut response: Value =
+            serde_json::from_str(&body).context("Failed to parse slurmrestd response")?;
+        if let Some(jobs) = response.get_mut("jobs").and_then(Value::as_array_mut) {
+            jobs.retain(|job| {
+                let field = |name: &str| job.get(name).and_then(Value::as_str).unwrap_or("");
+                user.is_none_or(|user| field("user_name") == user)
+                    && partition.is_none_or(|partition| {
+                        field("partition").split(',').any(|p| p == partition)
+                    })
+            });
+        }
+        Ok(response.to_string())
+    }
+
+    async fn version(&self) -> Result<String> {
+        let body = self.send(Method::GET, "ping").await?;
+        let response: Value =
+            serde_json::from_str(&body).context("Failed to parse slurmrestd response")?;
+        // The key was renamed from "Slurm" to "slurm" in v0.0.39
+        let release = ["slurm", "Slurm"]
+            .iter()
+            .find_map(|key| response["meta"][key]["release"].as_str())
+            .context("slurmrestd did not report its Slurm release")?;
+        Ok(format!("slurm {}", release))
+    }
+
+    async fn scancel(
+        &self,
+        job_id: &str,
+        _cluster: Option<&str>,
+        options: &CancelOptions,
+    ) -> Result<()> {
+        let mut query = Vec::new();
+        if let Some(signal) = &options.signal {
+            query.push(format!("signal=SIG{}", signal));
+        }
+        match options.scope {
+            SignalScope::Steps => {}
+            SignalScope::Batch => query.push("flags=BATCH_JOB".to_string()),
+            SignalScope::Full => query.push("flags=FULL_JOB".to_string()),
+            SignalScope::Step(_) => return Err(unsupported("Signalling a single step")),
+        }
+        let mut path = format!("job/{}", job_id);
+        if !query.is_empty() {
+            path = format!("{}?{}", path, query.join("&"));
+        }
+        self.send(Method::DELETE, &path).await?;
+        Ok(())
+    }
+
+    async fn hold(&self, _job_id: &str, _cluster: Option<&str>) -> Result<()> {
+        Err(unsupported("Holding jobs"))
+    }
+
+    async fn release(&self, _job_id: &str, _cluster: Option<&str>) -> Result<()> {
+        Err(unsupported("Releasing jobs"))
+    }
+
+    async fn requeue(&self, _job_id: &str, _cluster: Option<&str>) -> Result<()> {
+        Err(unsupported("Requeueing jobs"))
+    }
+
+    async fn update_job(
+        &self,
+        _job_id: &str,
+        _cluster: Option<&str>,
+        _changes: &[(String, String)],
+    ) -> Result<()> {
+        Err(unsupported("Editing jobs"))
+    }
+
+    async fn sbatch(
+        &self,
+        _script: &str,
+        _cluster: Option<&str>,
+        _options: &[(String, String)],
+    ) -> Result<String> {
+        Err(unsupported("Submitting jobs"))
+    }
+
+    async fn sacct(
+        &self,
+        _user: Option<&str>,
+        _partition: Option<&str>,
+        _clusters: Option<&str>,
+        _window: Duration,
+    ) -> Result<String> {
+        Err(unsupported("Job history (sacct)"))
+    }
+
+    async fn sacct_job(&self, _job_id: &str, _cluster: Option<&str>) -> Result<String> {
+        Err(unsupported("Job history (sacct)"))
+    }
+
+    async fn sprio(&self, _partition: Option<&str>, _cluster: Option<&str>) -> Result<String> {
+        Err(unsupported("Priority factors (sprio)"))
+    }
+
+    async fn sshare(&self, _user: Option<&str>, _cluster: Option<&str>) -> Result<String> {
+        Err(unsupported("Fairshare (sshare)"))
+    }
+
+    async fn sstat(&self, _job_id: &str, _cluster: Option<&str>) -> Result<String> {
+        Err(unsupported("Live job usage (sstat)"))
+    }
+
+    async fn sinfo(&self, _partition: Option<&str>, _clusters: Option<&str>) -> Result<String> {
+        Err(unsupported("Listing nodes"))
+    }
+
+    async fn scontrol_show_nodes(&self, _cluster: Option<&str>) -> Result<String> {
+        Err(unsupported("Listing nodes"))
+    }
+
+    async fn sinfo_partitions(&self, _clusters: Option<&str>) -> Result<String> {
+        Err(unsupported("Listing partitions"))
+    }
+
+    async fn scontrol_show_partitions(&self, _cluster: Option<&str>) -> Result<String> {
+        Err(unsupported("Listing partitions"))
+    }
+
+    async fn clusters(&self) -> Result<String> {
+        Err(unsupported("Listing clusters"))
+    }
+
+    async fn tail_file(&self, path: &str, lines: usize) -> Result<String> {
+        read_tail(path, lines).await
+    }
+
+    fn shell_command(
+        &self,
+        _job_id: &str,
+        _cluster: Option<&str>,
+        _node: Option<&str>,
+    ) -> Result<Vec<String>> {
+        Err(unsupported("Attaching a shell (srun)"))
+    }
+
+    fn shows_job_details(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,1612 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Local, TimeDelta, Utc};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+use crate::models::{
+    FairShare, JobState, parse_memory_mb, parse_sbatch_directives, parse_time_limit,
+};
+use crate::slurm::commands::{SACCT_FORMAT, SSHARE_FORMAT, SSTAT_FORMAT, read_tail};
+use crate::slurm::executor::{CancelOptions, SignalScope, SlurmExecutor};
+use crate::slurm::remote::shell_quote;
+
+/// How long finished jobs stay in squeue output, like slurmctld's MinJobAge.
+const MIN_JOB_AGE: TimeDelta = TimeDelta::seconds(60);
+
+/// A job log gains a line for every this many seconds of runtime.
+const LOG_LINE_EVERY_SECS: i64 = 30;
+
+/// The demo keeps at least this many jobs pending or running.
+const DEMO_ACTIVE_JOBS: usize = 10;
+
+/// Partition name, MaxTime and DefMemPerCPU; the first is the default.
+const SIM_PARTITIONS: [(&str, &str, u64); 3] = [
+    ("debug", "1:00:00", 2000),
+    ("gpu", "2-00:00:00", 8000),
+    ("compute", "7-00:00:00", 4000),
+];
+
+const SIM_QOS: [&str; 3] = ["normal", "high", "low"];
+
+/// Priority weights, as PriorityWeight* in slurm.conf. The age factor is
+/// full after a day of waiting.
+const WEIGHT_AGE: f64 = 1000.0;
+const WEIGHT_FAIRSHARE: f64 = 10000.0;
+const WEIGHT_JOB_SIZE: f64 = 1000.0;
+const WEIGHT_PARTITION: f64 = 1000.0;
+
+/// What a simulated job does once it runs for its full runtime. A job whose
+/// runtime exceeds its time limit ends in TIMEOUT instead.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SimOutcome {
+    Completed,
+    /// Exits with this non-zero code.
+    Failed(i32),
+}
+
+/// A job submitted to the simulator. Start from [`JobRequest::new`] and
+/// override fields with struct update syntax.
+#[derive(Debug, Clone)]
+pub struct JobRequest {
+    pub name: String,
+    pub user: String,
+    pub partition: String,
+    pub cpus: u32,
+    pub nodes: u32,
+    pub memory_mb: u64,
+    pub time_limit: Duration,
+    /// How long the job would run without a time limit.
+    pub runtime: Duration,
+    pub outcome: SimOutcome,
+    /// The script handed to sbatch, shown as scontrol's Command.
+    pub script: Option<String>,
+}
+
+impl JobRequest {
+    /// One CPU on one node with 1G, running 10 minutes of a 1 hour limit.
+    pub fn new(name: &str, user: &str, partition: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            user: user.to_string(),
+            partition: partition.to_string(),
+            cpus: 1,
+            nodes: 1,
+            memory_mb: 1024,
+            time_limit: Duration::from_secs(60 * 60),
+            runtime: Duration::from_secs(10 * 60),
+            outcome: SimOutcome::Completed,
+            script: None,
+        }
+    }
+}
+
+/// An in-memory Slurm cluster for demos and tests.
+///
+/// Submitted jobs are scheduled first-in first-out onto a handful of nodes
+/// and move PENDING → RUNNING → COMPLETED/FAILED/TIMEOUT as simulated time
+/// passes; `scancel` moves them to CANCELLED. squeue, scontrol and sacct
+/// output is produced in the text formats [`SlurmParser`] reads, and every
+/// job writes a log file under [`Self::log_dir`].
+///
+/// Time only moves through [`Self::advance`] unless a speed is set with
+/// [`Self::with_speed`], in which case it follows the wall clock.
+///
+/// [`SlurmParser`]: crate::slurm::SlurmParser
+pub struct SlurmSimulator {
+    state: Mutex<SimState>,
+    log_dir: PathBuf,
+    /// Simulated seconds per real second; 0 for a manual clock.
+    speed: f64,
+    /// Demo mode: keep the queue busy with generated jobs for this user.
+    demo_user: Option<String>,
+    /// The demo's `log_dir`, removed with the simulator.
+    temp_dir: Option<TempDir>,
+}
+
+struct SimNode {
+    name: String,
+    partition: &'static str,
+    cpus: u32,
+    used: u32,
+    memory_mb: u64,
+    gres: &'static str,
+    features: &'static str,
+}
+
+struct SimJob {
+    id: u64,
+    request: JobRequest,
+    state: JobState,
+    reason: &'static str,
+    submit: DateTime<Utc>,
+    start: Option<DateTime<Utc>>,
+    end: Option<DateTime<Utc>>,
+    /// Indices into `SimState::nodes`.
+    nodes: Vec<usize>,
+    exit_code: i32,
+    signal: i32,
+    log_lines: i64,
+    /// Put on hold with `scontrol hold`; never scheduled until released.
+    held: bool,
+    qos: String,
+    comment: String,
+    /// Subtracted from the priority.
+    nice: i64,
+}
+
+impl SimJob {
+    fn is_active(&self) -> bool {
+        matches!(self.state, JobState::Pending | JobState::Running)
+    }
+
+    fn cpus_per_node(&self) -> u32 {
+        self.request.cpus.div_ceil(self.request.nodes.max(1))
+    }
+
+    /// When a running job stops, by finishing or hitting its limit.
+    fn finish_time(&self) -> Option<DateTime<Utc>> {
+        let start = self.start?;
+        let runs_for = self.request.runtime.min(self.request.time_limit);
+        Some(after(start, runs_for))
+    }
+
+    fn elapsed(&self, now: DateTime<Utc>) -> i64 {
+        match self.start {
+            Some(start) => (self.end.unwrap_or(now) - start).num_seconds().max(0),
+            None => 0,
+        }
+    }
+}
+
+struct SimState {
+    now: DateTime<Utc>,
+    last_sync: Instant,
+    nodes: Vec<SimNode>,
+    jobs: Vec<SimJob>,
+    next_id: u64,
+    rng: u64,
+}
+
+impl SlurmSimulator {
+    /// An idle cluster with a manual clock starting at 2024-01-15 09:00 UTC.
+    /// Job logs are written to `log_dir`, which is created if needed.
+    pub fn new(log_dir: impl Into<PathBuf>) -> Result<Self> {
+        let start = DateTime::from_timestamp(1_705_309_200, 0).unwrap_or_default();
+        Self::starting_at(log_dir.into(), start)
+    }
+
+    fn starting_at(log_dir: PathBuf, now: DateTime<Utc>) -> Result<Self> {
+        std::fs::create_dir_all(&log_dir)
+            .with_context(|| format!("Failed to create {}", log_dir.display()))?;
+
+        // debug: sim[01-02], gpu: sim[03-04], compute: sim[05-08]
+        let layout: [(&'static str, u32, u32, u64, &'static str, &'static str); 3] = [
+            ("debug", 2, 8, 32_000, "(null)", "cpu"),
+            ("gpu", 2, 16, 128_000, "gpu:a100:4", "gpu,a100"),
+            ("compute", 4, 32, 128_000, "(null)", "cpu,avx512"),
+        ];
+        let mut nodes = Vec::new();
+        for (partition, count, cpus, memory_mb, gres, features) in layout {
+            for _ in 0..count {
+                nodes.push(SimNode {
+                    name: format!("sim{:02}", nodes.len() + 1),
+                    partition,
+                    cpus,
+                    used: 0,
+                    memory_mb,
+                    gres,
+                    features,
+                });
+            }
+        }
+
+        Ok(Self {
+            state: Mutex::new(SimState {
+                now,
+                last_sync: Instant::now(),
+                nodes,
+                jobs: Vec::new(),
+                next_id: 1001,
+                rng: 0x9e37_79b9_7f4a_7c15,
+            }),
+            log_dir,
+            speed: 0.0,
+            demo_user: None,
+            temp_dir: None,
+        })
+    }
+
+    /// A busy cluster for `lazyslurm --demo`: running ten times faster than
+    /// real time, seeded with a mix of jobs, most of them owned by `user`,
+    /// and topped up with new submissions as jobs finish. Job logs go to a
+    /// temporary directory that is removed when the simulator is dropped.
+    pub fn demo(user: &str) -> Result<Self> {
+        let temp_dir = tempfile::Builder::new()
+            .prefix("lazyslurm-demo-")
+            .tempdir()
+            .context("Failed to create a directory for the demo's job logs")?;
+        let now = Utc::now();
+        // Start an hour back so there is some history by the time it opens
+        let mut sim = Self::starting_at(temp_dir.path().to_path_buf(), now - TimeDelta::hours(1))?
+            .with_speed(10.0);
+        sim.demo_user = Some(user.to_string());
+        sim.temp_dir = Some(temp_dir);
+
+        {
+            let mut state = sim.state.lock().unwrap();
+            for _ in 0..6 {
+                let request = random_request(&mut state, user);
+                sim.submit_locked(&mut state, request);
+            }
+            sim.step(&mut state, now);
+            sim.top_up(&mut state);
+            sim.step(&mut state, now);
+            state.last_sync = Instant::now();
+        }
+        Ok(sim)
+    }
+
+    /// Simulated seconds per real second; 0 keeps the clock manual.
+    pub fn with_speed(mut self, speed: f64) -> Self {
+        self.speed = speed.max(0.0);
+        self
+    }
+
+    pub fn log_dir(&self) -> &Path {
+        &self.log_dir
+    }
+
+    /// The current simulated time.
+    pub fn now(&self) -> DateTime<Utc> {
+        self.locked().now
+    }
+
+    /// Submit a job and return its id. It starts at once if nodes are free.
+    pub fn submit(&self, request: JobRequest) -> String {
+        let mut state = self.locked();
+        let id = self.submit_locked(&mut state, request);
+        let now = state.now;
+        self.step(&mut state, now);
+        id.to_string()
+    }
+
+    /// Move simulated time forward by `by`.
+    pub fn advance(&self, by: Duration) {
+        let mut state = self.state.lock().unwrap();
+        let to = after(state.now, by);
+        self.step(&mut state, to);
+    }
+
+    fn submit_locked(&self, state: &mut SimState, request: JobRequest) -> u64 {
+        let id = state.next_id;
+        state.next_id += 1;
+        state.jobs.push(SimJob {
+            id,
+            request,
+            state: JobState::Pending,
+            reason: "None",
+            submit: state.now,
+            start: None,
+            end: None,
+            nodes: Vec::new(),
+            exit_code: 0,
+            signal: 0,
+            log_lines: 0,
+            held: false,
+            qos: SIM_QOS[0].to_string(),
+            comment: String::new(),
+            nice: 0,
+        });
+        id
+    }
+
+    /// Catch the simulated clock up with the wall clock when a speed is set.
+    fn sync_clock(&self, state: &mut SimState) {
+        if self.speed <= 0.0 {
+            return;
+        }
+        let real = state.last_sync.elapsed();
+        state.last_sync = Instant::now();
+        let to = after(state.now, real.mul_f64(self.speed));
+        self.step(state, to);
+        if self.demo_user.is_some() {
+            self.top_up(state);
+            let now = state.now;
+            self.step(state, now);
+        }
+    }
+
+    fn top_up(&self, state: &mut SimState) {
+        let Some(user) = &self.demo_user else {
+            return;
+        };
+        while state.jobs.iter().filter(|job| job.is_active()).count() < DEMO_ACTIVE_JOBS {
+            let request = random_request(state, user);
+            self.submit_locked(state, request);
+        }
+    }
+
+    /// Run the cluster until `to`, finishing and starting jobs in order.
+    fn step(&self, state: &mut SimState, to: DateTime<Utc>) {
+        loop {
+            self.schedule(state);
+            let next_finish = state
+                .jobs
+                .iter()
+                .filter(|job| job.state == JobState::Running)
+                .filter_map(SimJob::finish_time)
+                .filter(|at| *at <= to)
+                .min();
+            let Some(at) = next_finish else {
+                break;
+            };
+            state.now = at.max(state.now);
+            self.write_logs(state);
+            for i in 0..state.jobs.len() {
+                let job = &state.jobs[i];
+                if job.state == JobState::Running && job.finish_time() <= Some(at) {
+                    self.finish(state, i);
+                }
+            }
+        }
+        state.now = to.max(state.now);
+        self.write_logs(state);
+    }
+
+    /// Start pending jobs first-in first-out, letting later jobs through
+    /// when they fit around a blocked one.
+    fn schedule(&self, state: &mut SimState) {
+        let mut blocked_partitions: Vec<String> = Vec::new();
+        for i in 0..state.jobs.len() {
+            if state.jobs[i].state != JobState::Pending || state.jobs[i].held {
+                continue;
+            }
+            let job = &state.jobs[i];
+            let wanted = job.request.nodes.max(1) as usize;
+            let per_node = job.cpus_per_node();
+            let free: Vec<usize> = state
+                .nodes
+                .iter()
+                .enumerate()
+                .filter(|(_, node)| node.partition == job.request.partition)
+                .filter(|(_, node)| node.cpus - node.used >= per_node)
+                .map(|(n, _)| n)
+                .take(wanted)
+                .collect();
+
+            if free.len() < wanted {
+                let partition = job.request.partition.clone();
+                let exists = state.nodes.iter().any(|node| node.partition == partition);
+                state.jobs[i].reason = if !exists {
+                    "PartitionConfig"
+                } else if blocked_partitions.contains(&partition) {
+                    "Priority"
+                } else {
+                    "Resources"
+                };
+                blocked_partitions.push(partition);
+                continue;
+            }
+
+            for &n in &free {
+                state.nodes[n].used += per_node;
+            }
+            let now = state.now;
+            let job = &mut state.jobs[i];
+            job.state = JobState::Running;
+            job.reason = "None";
+            job.start = Some(now);
+            job.nodes = free;
+            let node_list = node_list(&state.nodes, &state.jobs[i].nodes);
+            let job = &state.jobs[i];
+            self.append_log(
+                job.id,
+                &format!(
+                    "Job {} ({}) started on {} at {}",
+                    job.id,
+                    job.request.name,
+                    node_list,
+                    format_time(now)
+                ),
+            );
+        }
+    }
+
+    fn finish(&self, state: &mut SimState, i: usize) {
+        let now = state.now;
+        let job = &mut state.jobs[i];
+        let (message, state_after) = if job.request.runtime > job.request.time_limit {
+            job.signal = 15;
+            (
+                format!(
+                    "slurmstepd: error: *** JOB {} CANCELLED AT {} DUE TO TIME LIMIT ***",
+                    job.id,
+                    format_time(now)
+                ),
+                JobState::Timeout,
+            )
+        } else {
+            match job.request.outcome {
+                SimOutcome::Completed => ("Done.".to_string(), JobState::Completed),
+                SimOutcome::Failed(code) => {
+                    job.exit_code = code;
+                    (
+                        format!("error: step failed with exit code {}", code),
+                        JobState::Failed,
+                    )
+                }
+            }
+        };
+        job.state = state_after;
+        job.end = Some(now);
+        let id = job.id;
+        self.free_nodes(state, i);
+        self.append_log(id, &message);
+    }
+
+    fn free_nodes(&self, state: &mut SimState, i: usize) {
+        let per_node = state.jobs[i].cpus_per_node();
+        for n in std::mem::take(&mut state.jobs[i].nodes) {
+            state.nodes[n].used -= per_node;
+        }
+    }
+
+    /// Append progress lines for running jobs up to the current time.
+    fn write_logs(&self, state: &mut SimState) {
+        let now = state.now;
+        for job in state
+            .jobs
+            .iter_mut()
+            .filter(|job| job.state == JobState::Running)
+        {
+            let due = job.elapsed(now) / LOG_LINE_EVERY_SECS;
+            while job.log_lines < due {
+                job.log_lines += 1;
+                let at = job.start.unwrap_or(now)
+                    + TimeDelta::seconds(job.log_lines * LOG_LINE_EVERY_SECS);
+                let loss = 2.0 / (1.0 + job.log_lines as f64 * 0.1);
+                let line = format!(
+                    "[{}] step {}: loss={:.4}",
+                    at.format("%H:%M:%S"),
+                    job.log_lines,
+                    loss
+                );
+                self.append_log(job.id, &line);
+            }
+        }
+    }
+
+    fn log_path(&self, id: u64) -> PathBuf {
+        self.log_dir.join(format!("slurm-{}.out", id))
+    }
+
+    /// Job logs are best effort; a failed write only leaves a gap.
+    fn append_log(&self, id: u64, line: &str) {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.log_path(id));
+        if let Ok(mut file) = file {
+            let _ = writeln!(file, "{}", line);
+        }
+    }
+
+    fn locked(&self) -> std::sync::MutexGuard<'_, SimState> {
+        let mut state = self.state.lock().unwrap();
+        self.sync_clock(&mut state);
+        state
+    }
+
+    fn scontrol_record(&self, state: &SimState, job: &SimJob) -> String {
+        let now = state.now;
+        let end = match (job.end, job.start) {
+            (Some(end), _) => format_time(end),
+            // Expected end, as scontrol shows for running jobs
+            (None, Some(start)) => format_time(after(start, job.request.time_limit)),
+            (None, None) => "Unknown".to_string(),
+        };
+        let log = self.log_path(job.id).display().to_string();
+        let node_list = if job.nodes.is_empty() {
+            "(null)".to_string()
+        } else {
+            node_list(&state.nodes, &job.nodes)
+        };
+
+        format!(
+            "JobId={id} JobName={name}\n   UserId={user}(1000) GroupId={user}(1000) MCS_label=N/A\n   \
+             JobState={state} Reason={reason} Dependency=(null)\n   \
+             Nice={nice} Account=sim QOS={qos}\n   \
+             Requeue=1 Restarts=0 BatchFlag=1 Reboot=0 ExitCode={exit}:{signal}\n   \
+             RunTime={run} TimeLimit={limit} TimeMin=N/A\n   \
+             SubmitTime={submit} EligibleTime={submit}\n   \
+             StartTime={start} EndTime={end} Deadline=N/A\n   \
+             Partition={partition} AllocNode:Sid=sim-login:1\n   \
+             NodeList={node_list}\n   \
+             NumNodes={nodes} NumCPUs={cpus} NumTasks=1 CPUs/Task={cpus}\n   \
+             MinMemoryNode={memory}\n   \
+             Command={command}\n   \
+             WorkDir={work_dir}\n   \
+             StdErr={log}\n   \
+             StdIn=/dev/null\n   \
+             StdOut={log}\n{comment}",
+            id = job.id,
+            name = job.request.name,
+            user = job.request.user,
+            state = long_state(&job.state),
+            reason = job.reason,
+            nice = job.nice,
+            qos = job.qos,
+            exit = job.exit_code,
+            signal = job.signal,
+            run = format_duration(job.elapsed(now)),
+            limit = format_duration(job.request.time_limit.as_secs() as i64),
+            submit = format_time(job.submit),
+            start = job
+                .start
+                .map(format_time)
+                .unwrap_or_else(|| "Unknown".to_string()),
+            end = end,
+            partition = job.request.partition,
+            node_list = node_list,
+            nodes = job.request.nodes,
+            cpus = job.request.cpus,
+            memory = format_memory(job.request.memory_mb),
+            command = job.request.script.as_deref().unwrap_or("(null)"),
+            work_dir = self.log_dir.display(),
+            log = log,
+            // scontrol leaves out an unset comment
+            comment = if job.comment.is_empty() {
+                String::new()
+            } else {
+                format!("   Comment={}\n", job.comment)
+            },
+        )
+    }
+
+    /// Memory held by running jobs on node `n`, in MB.
+    fn memory_alloc(&self, state: &SimState, n: usize) -> u64 {
+        state
+            .jobs
+            .iter()
+            .filter(|job| job.nodes.contains(&n))
+            .map(|job| job.request.memory_mb)
+            .sum()
+    }
+
+    fn node_record(&self, state: &SimState, n: usize) -> String {
+        let node = &state.nodes[n];
+        let alloc_mem = self.memory_alloc(state, n);
+        format!(
+            "NodeName={name} Arch=x86_64 CoresPerSocket={cpus}\n   \
+             CPUAlloc={used} CPUEfctv={cpus} CPUTot={cpus} CPULoad={load:.2}\n   \
+             AvailableFeatures={features}\n   \
+             ActiveFeatures={features}\n   \
+             Gres={gres}\n   \
+             NodeAddr={name} NodeHostName={name}\n   \
+             RealMemory={memory} AllocMem={alloc_mem} FreeMem={free}\n   \
+             State={state} ThreadsPerCore=1 Weight=1\n   \
+             Partitions={partition}\n",
+            name = node.name,
+            cpus = node.cpus,
+            used = node.used,
+            load = node.used as f64 * 0.95,
+            features = node.features,
+            gres = node.gres,
+            memory = node.memory_mb,
+            alloc_mem = alloc_mem,
+            free = node.memory_mb - alloc_mem.min(node.memory_mb),
+            state = node_state(node).to_uppercase(),
+            partition = node.partition,
+        )
+    }
+
+    /// Jobs squeue still lists: active ones and those that just finished.
+    fn visible_jobs<'a>(&self, state: &'a SimState) -> impl Iterator<Item = &'a SimJob> {
+        let now = state.now;
+        state.jobs.iter().filter(move |job| {
+            job.is_active() || job.end.is_some_and(|end| now - end < MIN_JOB_AGE)
+        })
+    }
+}
+
+/// When each pending job should start if running jobs use their whole time
+/// limit, placing pending jobs in queue order without backfill. Jobs that
+/// can never fit their partition have no estimate.
+fn estimated_starts(state: &SimState) -> HashMap<u64, DateTime<Utc>> {
+    let mut used: Vec<u32> = state.nodes.iter().map(|node| node.used).collect();
+    // CPUs per node freed on some nodes at some time
+    let mut releases: Vec<(DateTime<Utc>, Vec<usize>, u32)> = state
+        .jobs
+        .iter()
+        .filter(|job| job.state == JobState::Running)
+        .filter_map(|job| {
+            let end = after(job.start?, job.request.time_limit);
+            Some((end, job.nodes.clone(), job.cpus_per_node()))
+        })
+        .collect();
+
+    let mut starts = HashMap::new();
+    for job in state
+        .jobs
+        .iter()
+        .filter(|job| job.state == JobState::Pending && !job.held)
+    {
+        let wanted = job.request.nodes.max(1) as usize;
+        let per_node = job.cpus_per_node();
+        releases.sort_by_key(|(when, ..)| *when);
+
+        let mut used_then = used.clone();
+        let mut when = state.now;
+        let mut pending_releases = releases.iter();
+        let placed = loop {
+            let free: Vec<usize> = state
+                .nodes
+                .iter()
+                .enumerate()
+                .filter(|(n, node)| {
+                    node.partition == job.request.partition
+                        && node.cpus.saturating_sub(used_then[*n]) >= per_node
+                })
+                .map(|(n, _)| n)
+                .take(wanted)
+                .collect();
+            if free.len() == wanted {
+                break Some(free);
+            }
+            let Some((at, nodes, cpus)) = pending_releases.next() else {
+                break None;
+            };
+            when = when.max(*at);
+            for &n in nodes {
+                used_then[n] = used_then[n].saturating_sub(*cpus);
+            }
+        };
+
+        // Hold the CPUs from now on so later jobs queue behind this one
+        if let Some(nodes) = placed {
+            for &n in &nodes {
+                used[n] += per_node;
+            }
+            releases.push((after(when, job.request.time_limit), nodes, per_node));
+            starts.insert(job.id, when);
+        }
+    }
+    starts
+}
+
+/// Every user's standing, each owning an equal share of one account and
+/// charged for CPU-seconds, in first-seen order.
+fn fair_shares(state: &SimState) -> Vec<FairShare> {
+    let mut usage: Vec<(String, u64)> = Vec::new();
+    for job in &state.jobs {
+        let used = job.elapsed(state.now) as u64 * job.request.cpus as u64;
+        match usage.iter_mut().find(|(user, _)| *user == job.request.user) {
+            Some((_, total)) => *total += used,
+            None => usage.push((job.request.user.clone(), used)),
+        }
+    }
+
+    let total: u64 = usage.iter().map(|(_, used)| used).sum();
+    let norm_shares = 1.0 / usage.len().max(1) as f64;
+    usage
+        .into_iter()
+        .map(|(user, used)| {
+            let effective = if total == 0 {
+                0.0
+            } else {
+                used as f64 / total as f64
+            };
+            FairShare {
+                raw_shares: Some(1),
+                norm_shares: Some(norm_shares),
+                raw_usage: Some(used),
+                effective_usage: Some(effective),
+                // The classic fairshare formula: 0.5 at exactly the fair share
+                fairshare: Some(2f64.powf(-effective / norm_shares)),
+                ..FairShare::new("sim".to_string(), user)
+            }
+        })
+        .collect()
+}
+
+fn job_index(state: &SimState, job_id: &str) -> Result<usize> {
+    state
+        .jobs
+        .iter()
+        .position(|job| job.id.to_string() == job_id)
+        .with_context(|| format!("Invalid job id specified: {}", job_id))
+}
+
+/// The number of a signal that ends the simulated job script.
+fn terminating_signal(signal: &str) -> Option<i32> {
+    match signal {
+        "HUP" => Some(1),
+        "INT" => Some(2),
+        "KILL" => Some(9),
+        "TERM" => Some(15),
+        _ => None,
+    }
+}
+
+fn matches_filters(job: &SimJob, user: Option<&str>, partition: Option<&str>) -> bool {
+    user.is_none_or(|user| job.request.user == user)
+        && partition.is_none_or(|partition| job.request.partition == partition)
+}
+
+#[async_trait]
+impl SlurmExecutor for SlurmSimulator {
+    async fn squeue(
+        &self,
+        user: Option<&str>,
+        partition: Option<&str>,
+        _clusters: Option<&str>,
+    ) -> Result<String> {
+        let state = self.locked();
+        let mut output = String::from("JOBID,NAME,USER,ST,TIME,NODELIST,PARTITION\n");
+        for job in self.visible_jobs(&state) {
+            if !matches_filters(job, user, partition) {
+                continue;
+            }
+            // %N is empty until nodes are allocated
+            let node_list = node_list(&state.nodes, &job.nodes);
+            output.push_str(&format!(
+                "{},{},{},{},{},{},{}\n",
+                job.id,
+                job.request.name,
+                job.request.user,
+                job.state,
+                format_squeue_time(job.elapsed(state.now)),
+                node_list,
+                job.request.partition
+            ));
+        }
+        Ok(output)
+    }
+
+    async fn squeue_start(
+        &self,
+        user: Option<&str>,
+        partition: Option<&str>,
+        _clusters: Option<&str>,
+    ) -> Result<String> {
+        let state = self.locked();
+        let starts = estimated_starts(&state);
+        let mut output = String::from("JOBID|USER|PARTITION|START_TIME\n");
+        for job in state
+            .jobs
+            .iter()
+            .filter(|job| job.state == JobState::Pending)
+        {
+            if !matches_filters(job, user, partition) {
+                continue;
+            }
+            let start = starts
+                .get(&job.id)
+                .map(|start| format_time(*start))
+                .unwrap_or_else(|| "N/A".to_string());
+            output.push_str(&format!(
+                "{}|{}|{}|{}\n",
+                job.id, job.request.user, job.request.partition, start
+            ));
+        }
+        Ok(output)
+    }
+
+    async fn scontrol_show_job(&self, job_id: &str, _cluster: Option<&str>) -> Result<String> {
+        let state = self.locked();
+        let job = state
+            .jobs
+            .iter()
+            .find(|job| job.id.to_string() == job_id)
+            .context("slurm_load_jobs error: Invalid job id specified")?;
+        Ok(self.scontrol_record(&state, job))
+    }
+
+    async fn scontrol_show_jobs(&self, _cluster: Option<&str>) -> Result<String> {
+        let state = self.locked();
+        let records: Vec<String> = self
+            .visible_jobs(&state)
+            .map(|job| self.scontrol_record(&state, job))
+            .collect();
+        Ok(records.join("\n"))
+    }
+
+    async fn squeue_json(
+        &self,
+        _user: Option<&str>,
+        _partition: Option<&str>,
+        _clusters: Option<&str>,
+    ) -> Result<String> {
+        anyhow::bail!("the simulator only produces text output")
+    }
+
+    async fn version(&self) -> Result<String> {
+        // No version number, so the app reads text output
+        Ok("slurm simulator".to_string())
+    }
+
+    async fn scancel(
+        &self,
+        job_id: &str,
+        _cluster: Option<&str>,
+        options: &CancelOptions,
+    ) -> Result<()> {
+        let mut state = self.locked();
+        let i = state
+            .jobs
+            .iter()
+            .position(|job| job.id.to_string() == job_id)
+            .with_context(|| {
+                format!(
+                    "scancel: error: Kill job error on job id {}: Invalid job id specified",
+                    job_id
+                )
+            })?;
+        if !state.jobs[i].is_active() {
+            anyhow::bail!(
+                "scancel: error: Kill job error on job id {}: Job/step already completing or completed",
+                job_id
+            );
+        }
+
+        // The simulated script ends on terminating signals and only logs
+        // the others, as a checkpointing training script would
+        let ends_job = match (&options.scope, options.signal.as_deref()) {
+            (SignalScope::Step(_), _) => None,
+            (_, None) => Some(15),
+            (_, Some(signal)) => terminating_signal(signal),
+        };
+        let Some(signal) = ends_job else {
+            let job = &state.jobs[i];
+            if job.state == JobState::Pending {
+                anyhow::bail!(
+                    "scancel: error: Kill job error on job id {}: Job is pending execution",
+                    job_id
+                );
+            }
+            let line = match &options.signal {
+                Some(signal) => format!("Caught SIG{} in {}", signal, options.scope.label()),
+                None => format!("srun: Job {} {} aborted", job_id, options.scope.label()),
+            };
+            self.append_log(job.id, &line);
+            return Ok(());
+        };
+
+        let now = state.now;
+        let was_running = state.jobs[i].state == JobState::Running;
+        let job = &mut state.jobs[i];
+        job.state = JobState::Cancelled;
+        job.end = Some(now);
+        if was_running {
+            job.signal = signal;
+            let id = job.id;
+            self.free_nodes(&mut state, i);
+            self.append_log(
+                id,
+                &format!(
+                    "slurmstepd: error: *** JOB {} CANCELLED AT {} ***",
+                    id,
+                    format_time(now)
+                ),
+            );
+        }
+        self.step(&mut state, now);
+        Ok(())
+    }
+
+    async fn hold(&self, job_id: &str, _cluster: Option<&str>) -> Result<()> {
+        let mut state = self.locked();
+        let i = job_index(&state, job_id)?;
+        let job = &mut state.jobs[i];
+        if job.state != JobState::Pending {
+            anyhow::bail!("slurm_update error: Job is no longer pending execution");
+        }
+        job.held = true;
+        job.reason = "JobHeldUser";
+        Ok(())
+    }
+
+    async fn release(&self, job_id: &str, _cluster: Option<&str>) -> Result<()> {
+        let mut state = self.locked();
+        let i = job_index(&state, job_id)?;
+        let job = &mut state.jobs[i];
+        // Releasing a job that is not held does nothing, as with scontrol
+        if job.held {
+            job.held = false;
+            job.reason = "None";
+            let now = state.now;
+            self.step(&mut state, now);
+        }
+        Ok(())
+    }
+
+    async fn requeue(&self, job_id: &str, _cluster: Option<&str>) -> Result<()> {
+        let mut state = self.locked();
+        let i = job_index(&state, job_id)?;
+        if state.jobs[i].state == JobState::Pending {
+            anyhow::bail!(
+                "Requeue of JobId={} returned an error: Job is pending execution",
+                job_id
+            );
+        }
+
+        let now = state.now;
+        if state.jobs[i].state == JobState::Running {
+            self.free_nodes(&mut state, i);
+        }
+        let job = &mut state.jobs[i];
+        job.state = JobState::Pending;
+        job.reason = "None";
+        job.start = None;
+        job.end = None;
+        job.exit_code = 0;
+        job.signal = 0;
+        let id = job.id;
+        self.append_log(id, &format!("Job {} requeued at {}", id, format_time(now)));
+        self.step(&mut state, now);
+        Ok(())
+    }
+
+    async fn update_job(
+        &self,
+        job_id: &str,
+        _cluster: Option<&str>,
+        changes: &[(String, String)],
+    ) -> Result<()> {
+        let mut state = self.locked();
+        let i = job_index(&state, job_id)?;
+        let job = &state.jobs[i];
+        if !job.is_active() {
+            anyhow::bail!("slurm_update error: Job/step already completing or completed");
+        }
+
+        // Check every change before applying any, like the controller
+        let mut request = job.request.clone();
+        let (mut qos, mut comment, mut nice) = (job.qos.clone(), job.comment.clone(), job.nice);
+        let pending = job.state == JobState::Pending;
+        let reject = |why: &str| anyhow::anyhow!("slurm_update error: {}", why);
+        for (key, value) in changes {
+            match key.as_str() {
+                "TimeLimit" => {
+                    let limit = parse_time_limit(value).ok_or_else(|| {
+                        reject("Requested time limit is invalid (missing or exceeds some limit)")
+                    })?;
+                    // Only operators may raise a time limit
+                    if limit > request.time_limit {
+                        return Err(reject("Access/permission denied"));
+                    }
+                    request.time_limit = limit;
+                }
+                "Partition" | "QOS" | "NumNodes" if !pending => {
+                    return Err(reject("Job is no longer pending execution"));
+                }
+                "Partition" => {
+                    if !SIM_PARTITIONS.iter().any(|(name, _, _)| name == value) {
+                        return Err(reject("Invalid partition name specified"));
+                    }
+                    request.partition = value.clone();
+                }
+                "QOS" => {
+                    if !SIM_QOS.contains(&value.as_str()) {
+                        return Err(reject("Invalid qos specification"));
+                    }
+                    qos = value.clone();
+                }
+                "NumNodes" => {
+                    let min = value.split('-').next().unwrap_or(value);
+                    request.nodes = min
+                        .parse()
+                        .map_err(|_| reject("Invalid node count specified"))?;
+                }
+                "JobName" => request.name = value.clone(),
+                "Comment" => comment = value.clone(),
+                "Nice" => {
+                    nice = value
+                        .parse()
+                        .map_err(|_| reject("Invalid nice value specified"))?;
+                    if nice < 0 {
+                        return Err(reject("Access/permission denied"));
+                    }
+                }
+                _ => return Err(reject(&format!("Update of {} is not supported", key))),
+            }
+        }
+
+        let max_time = SIM_PARTITIONS
+            .iter()
+            .find(|(name, _, _)| *name == request.partition)
+            .and_then(|(_, max_time, _)| parse_time_limit(max_time));
+        if max_time.is_some_and(|max_time| request.time_limit > max_time) {
+            return Err(reject(
+                "Requested time limit is invalid (missing or exceeds some limit)",
+            ));
+        }
+        let partition_nodes = state
+            .nodes
+            .iter()
+            .filter(|node| node.partition == request.partition)
+            .count();
+        if request.nodes as usize > partition_nodes {
+            return Err(reject("Requested node configuration is not available"));
+        }
+
+        let job = &mut state.jobs[i];
+        job.request = request;
+        job.qos = qos;
+        job.comment = comment;
+        job.nice = nice;
+        let now = state.now;
+        self.step(&mut state, now);
+        Ok(())
+    }
+
+    async fn sbatch(
+        &self,
+        script: &str,
+        _cluster: Option<&str>,
+        options: &[(String, String)],
+    ) -> Result<String> {
+        let contents = std::fs::read_to_string(script)
+            .map_err(|_| anyhow::anyhow!("sbatch: error: Unable to open file {}", script))?;
+        if !contents.starts_with("#!") {
+            anyhow::bail!(
+                "sbatch: error: This does not look like a batch script.  The first line must start with #! followed by the path to an interpreter."
+            );
+        }
+
+        // Options on the command line win over the script's directives.
+        // Those with no bearing on the simulation, such as GRES or
+        // dependencies, are accepted and ignored.
+        let name = Path::new(script)
+            .file_name()
+            .map_or(script, |name| name.to_str().unwrap_or(script));
+        let user = self
+            .demo_user
+            .clone()
+            .or_else(|| std::env::var("USER").ok());
+        let mut request = JobRequest::new(
+            name,
+            user.as_deref().unwrap_or("nobody"),
+            SIM_PARTITIONS[0].0,
+        );
+        request.script = Some(script.to_string());
+        let directives = parse_sbatch_directives(&contents)
+            .into_iter()
+            .filter_map(|directive| Some((directive.option, directive.value?)));
+        let reject =
+            |why: &str| anyhow::anyhow!("sbatch: error: Batch job submission failed: {}", why);
+        for (option, value) in directives.chain(options.iter().cloned()) {
+            match option.as_str() {
+                "job-name" => request.name = value,
+                "partition" => {
+                    if !SIM_PARTITIONS.iter().any(|(name, _, _)| *name == value) {
+                        anyhow::bail!("sbatch: error: invalid partition specified: {}", value);
+                    }
+                    request.partition = value;
+                }
+                "time" => {
+                    request.time_limit = parse_time_limit(&value).ok_or_else(|| {
+                        reject("Requested time limit is invalid (missing or exceeds some limit)")
+                    })?;
+                }
+                "nodes" => {
+                    let min = value.split('-').next().unwrap_or(&value);
+                    request.nodes = min.parse().map_err(|_| {
+                        anyhow::anyhow!("sbatch: error: Invalid node count specification")
+                    })?;
+                }
+                "cpus-per-task" => {
+                    request.cpus = value.parse().map_err(|_| {
+                        anyhow::anyhow!(
+                            "sbatch: error: Invalid numeric value \"{}\" for cpus-per-task.",
+                            value
+                        )
+                    })?;
+                }
+                "mem" => {
+                    request.memory_mb = parse_memory_mb(&value).ok_or_else(|| {
+                        anyhow::anyhow!("sbatch: error: Invalid --mem specification")
+                    })?;
+                }
+                _ => {}
+            }
+        }
+
+        let mut state = self.locked();
+        let max_time = SIM_PARTITIONS
+            .iter()
+            .find(|(name, _, _)| *name == request.partition)
+            .and_then(|(_, max_time, _)| parse_time_limit(max_time));
+        if max_time.is_some_and(|max_time| request.time_limit > max_time) {
+            return Err(reject(
+                "Requested time limit is invalid (missing or exceeds some limit)",
+            ));
+        }
+        let fits = state.nodes.iter().filter(|node| {
+            node.partition == request.partition
+                && node.cpus >= request.cpus
+                && node.memory_mb >= request.memory_mb
+        });
+        if (request.nodes as usize) > fits.count() {
+            return Err(reject("Requested node configuration is not available"));
+        }
+
+        let id = self.submit_locked(&mut state, request);
+        let now = state.now;
+        self.step(&mut state, now);
+        Ok(id.to_string())
+    }
+
+    async fn sacct(
+        &self,
+        user: Option<&str>,
+        partition: Option<&str>,
+        _clusters: Option<&str>,
+        window: Duration,
+    ) -> Result<String> {
+        let state = self.locked();
+        let since = TimeDelta::from_std(window)
+            .ok()
+            .and_then(|window| state.now.checked_sub_signed(window))
+            .unwrap_or(DateTime::<Utc>::MIN_UTC);
+        Ok(self.sacct_rows(&state, |job| {
+            job.end.is_none_or(|end| end >= since) && matches_filters(job, user, partition)
+        }))
+    }
+
+    async fn sacct_job(&self, job_id: &str, _cluster: Option<&str>) -> Result<String> {
+        let state = self.locked();
+        Ok(self.sacct_rows(&state, |job| job.id.to_string() == job_id))
+    }
+
+    async fn sprio(&self, partition: Option<&str>, _cluster: Option<&str>) -> Result<String> {
+        let state = self.locked();
+        let shares = fair_shares(&state);
+        let mut output = String::new();
+        // Held jobs have a priority of 0 and are left out
+        for job in state
+            .jobs
+            .iter()
+            .filter(|job| job.state == JobState::Pending && !job.held)
+        {
+            if !matches_filters(job, None, partition) {
+                continue;
+            }
+            let waited = (state.now - job.submit).num_seconds() as f64;
+            let age = (WEIGHT_AGE * (waited / 86400.0).min(1.0)) as u64;
+            let fairshare = shares
+                .iter()
+                .find(|share| share.user == job.request.user)
+                .and_then(|share| share.fairshare)
+                .map_or(0, |factor| (WEIGHT_FAIRSHARE * factor) as u64);
+            let cpus: u32 = state.nodes.iter().map(|node| node.cpus).sum();
+            let job_size = (WEIGHT_JOB_SIZE * job.request.cpus as f64 / cpus as f64) as u64;
+            // The debug partition's PriorityJobFactor is twice the others'
+            let partition_factor = if job.request.partition == SIM_PARTITIONS[0].0 {
+                WEIGHT_PARTITION as u64
+            } else {
+                WEIGHT_PARTITION as u64 / 2
+            };
+            let factors = age + fairshare + job_size + partition_factor;
+            output.push_str(&format!(
+                "{}|{}|{}|{}|{}|{}|{}|{}|0|\n",
+                job.id,
+                job.request.user,
+                job.request.partition,
+                (factors as i64 - job.nice).max(0),
+                age,
+                fairshare,
+                job_size,
+                partition_factor
+            ));
+        }
+        Ok(output)
+    }
+
+    async fn sshare(&self, user: Option<&str>, _cluster: Option<&str>) -> Result<String> {
+        let state = self.locked();
+        let columns: Vec<&str> = SSHARE_FORMAT.split(',').collect();
+        let mut output = format!("{}\n", columns.join("|"));
+        for share in fair_shares(&state) {
+            if user.is_some_and(|user| user != share.user) {
+                continue;
+            }
+            let number = |n: Option<f64>| n.map(|n| format!("{:.6}", n)).unwrap_or_default();
+            let values = |column: &str| match column {
+                "Account" => share.account.clone(),
+                "User" => share.user.clone(),
+                "RawShares" => share.raw_shares.unwrap_or_default().to_string(),
+                "NormShares" => number(share.norm_shares),
+                "RawUsage" => share.raw_usage.unwrap_or_default().to_string(),
+                "EffectvUsage" => number(share.effective_usage),
+                "FairShare" => number(share.fairshare),
+                _ => String::new(),
+            };
+            let row: Vec<String> = columns.iter().map(|column| values(column)).collect();
+            output.push_str(&format!("{}\n", row.join("|")));
+        }
+        Ok(output)
+    }
+
+    async fn sstat(&self, job_id: &str, _cluster: Option<&str>) -> Result<String> {
+        let state = self.locked();
+        let job = state
+            .jobs
+            .iter()
+            .find(|job| job.id.to_string() == job_id && job.state == JobState::Running)
+            .with_context(|| {
+                format!(
+                    "sstat: error: couldn't get steps for job {} with state RUNNING",
+                    job_id
+                )
+            })?;
+
+        // Memory climbs from a third of the request towards it as the job
+        // runs, so long jobs come close to their limit
+        let elapsed = job.elapsed(state.now);
+        let progress = (elapsed as f64 / job.request.runtime.as_secs().max(1) as f64).min(1.0);
+        let request_kb = job.request.memory_mb * 1024;
+        let max_rss = (request_kb as f64 * (0.3 + 0.65 * progress)) as u64;
+        let values = |column: &str| match column {
+            "JobID" => format!("{}.batch", job.id),
+            "AveCPU" => format_duration(elapsed * 9 / 10),
+            "AveRSS" => format!("{}K", max_rss * 9 / 10),
+            "MaxRSS" => format!("{}K", max_rss),
+            "MaxRSSNode" => node_list(&state.nodes, &job.nodes[..1.min(job.nodes.len())]),
+            "MaxVMSize" => format!("{}K", max_rss * 3 / 2),
+            "AveDiskRead" => format!("{:.2}M", elapsed as f64 * 0.4),
+            "AveDiskWrite" => format!("{:.2}M", elapsed as f64 * 0.1),
+            _ => String::new(),
+        };
+
+        let columns: Vec<&str> = SSTAT_FORMAT.split(',').collect();
+        let row: Vec<String> = columns.iter().map(|column| values(column)).collect();
+        Ok(format!("{}\n{}\n", columns.join("|"), row.join("|")))
+    }
+
+    async fn sinfo(&self, partition: Option<&str>, _clusters: Option<&str>) -> Result<String> {
+        let state = self.locked();
+        let mut output = String::from(
+            "NODELIST|PARTITION|STATE|CPUS(A/I/O/T)|MEMORY|FREE_MEM|GRES|AVAIL_FEATURES|REASON\n",
+        );
+        for (n, node) in state.nodes.iter().enumerate() {
+            if partition.is_some_and(|partition| node.partition != partition) {
+                continue;
+            }
+            let free = node.memory_mb - self.memory_alloc(&state, n).min(node.memory_mb);
+            output.push_str(&format!(
+                "{}|{}|{}|{}/{}/0/{}|{}|{}|{}|{}|none\n",
+                node.name,
+                node.partition,
+                node_state(node),
+                node.used,
+                node.cpus - node.used,
+                node.cpus,
+                node.memory_mb,
+                free,
+                node.gres,
+                node.features,
+            ));
+        }
+        Ok(output)
+    }
+
+    async fn scontrol_show_nodes(&self, _cluster: Option<&str>) -> Result<String> {
+        let state = self.locked();
+        let records: Vec<String> = (0..state.nodes.len())
+            .map(|n| self.node_record(&state, n))
+            .collect();
+        Ok(records.join("\n"))
+    }
+
+    async fn sinfo_partitions(&self, _clusters: Option<&str>) -> Result<String> {
+        let state = self.locked();
+        let mut output =
+            String::from("PARTITION|AVAIL|TIMELIMIT|DEFAULTTIME|NODES(A/I/O/T)|CPUS(A/I/O/T)\n");
+        for (i, (name, max_time, _)) in SIM_PARTITIONS.iter().enumerate() {
+            let nodes: Vec<&SimNode> = state
+                .nodes
+                .iter()
+                .filter(|node| node.partition == *name)
+                .collect();
+            // sinfo counts mixed nodes as allocated
+            let busy = nodes.iter().filter(|node| node.used > 0).count();
+            let used: u32 = nodes.iter().map(|node| node.used).sum();
+            let cpus: u32 = nodes.iter().map(|node| node.cpus).sum();
+            output.push_str(&format!(
+                "{}{}|up|{}|n/a|{}/{}/0/{}|{}/{}/0/{}\n",
+                name,
+                if i == 0 { "*" } else { "" },
+                max_time,
+                busy,
+                nodes.len() - busy,
+                nodes.len(),
+                used,
+                cpus - used,
+                cpus,
+            ));
+        }
+        Ok(output)
+    }
+
+    async fn scontrol_show_partitions(&self, _cluster: Option<&str>) -> Result<String> {
+        let state = self.locked();
+        let records: Vec<String> = SIM_PARTITIONS
+            .iter()
+            .enumerate()
+            .map(|(i, (name, max_time, mem_per_cpu))| {
+                let nodes: Vec<usize> = (0..state.nodes.len())
+                    .filter(|&n| state.nodes[n].partition == *name)
+                    .collect();
+                let cpus: u32 = nodes.iter().map(|&n| state.nodes[n].cpus).sum();
+                format!(
+                    "PartitionName={name}\n   \
+                     AllowGroups=ALL AllowAccounts=ALL AllowQos=ALL\n   \
+                     Default={default} QoS=N/A\n   \
+                     DefaultTime=NONE DisableRootJobs=NO ExclusiveUser=NO\n   \
+                     MaxNodes=UNLIMITED MaxTime={max_time} MinNodes=0 LLN=NO\n   \
+                     Nodes={nodes}\n   \
+                     State=UP TotalCPUs={cpus} TotalNodes={total}\n   \
+                     DefMemPerCPU={mem_per_cpu} MaxMemPerNode=UNLIMITED\n",
+                    default = if i == 0 { "YES" } else { "NO" },
+                    nodes = node_list(&state.nodes, &nodes),
+                    total = nodes.len(),
+                )
+            })
+            .collect();
+        Ok(records.join("\n"))
+    }
+
+    async fn clusters(&self) -> Result<String> {
+        Ok("sim\n".to_string())
+    }
+
+    async fn tail_file(&self, path: &str, lines: usize) -> Result<String> {
+        read_tail(path, lines).await
+    }
+
+    /// Simulated nodes cannot be logged into, so this is a local shell that
+    /// says which node it stands in for.
+    fn shell_command(
+        &self,
+        job_id: &str,
+        _cluster: Option<&str>,
+        node: Option<&str>,
+    ) -> Result<Vec<String>> {
+        let state = self.locked();
+        let job = state
+            .jobs
+            .iter()
+            .find(|job| job.id.to_string() == job_id && job.state == JobState::Running)
+            .with_context(|| {
+                format!(
+                    "srun: error: Unable to confirm allocation for job {}: Job is not running",
+                    job_id
+                )
+            })?;
+        let node = match node {
+            Some(node) => node.to_string(),
+            None => node_list(&state.nodes, &job.nodes[..1.min(job.nodes.len())]),
+        };
+        let banner = format!(
+            "Simulated job {} on {}: this is a local shell; exit to return to lazyslurm.",
+            job_id, node
+        );
+        Ok(vec![
+            "sh".to_string(),
+            "-c".to_string(),
+            format!("echo {}; exec \"${{SHELL:-sh}}\"", shell_quote(&banner)),
+        ])
+    }
+}
+
+impl SlurmSimulator {
+    /// sacct output in [`SACCT_FORMAT`] for the jobs `keep` selects.
+    fn sacct_rows(&self, state: &SimState, keep: impl Fn(&SimJob) -> bool) -> String {
+        let columns: Vec<&str> = SACCT_FORMAT.split(',').collect();
+
+        let mut output = columns.join("|");
+        output.push('\n');
+        for job in state.jobs.iter().filter(|job| keep(job)) {
+            let values: Vec<String> = columns
+                .iter()
+                .map(|column| self.sacct_field(state, job, column))
+                .collect();
+            output.push_str(&values.join("|"));
+            output.push('\n');
+        }
+        output
+    }
+
+    fn sacct_field(&self, state: &SimState, job: &SimJob, column: &str) -> String {
+        let time =
+            |t: Option<DateTime<Utc>>| t.map(format_time).unwrap_or_else(|| "Unknown".to_string());
+        match column {
+            "Cluster" => "sim".to_string(),
+            "JobID" => job.id.to_string(),
+            "JobName" => job.request.name.clone(),
+            "User" => job.request.user.clone(),
+            "Partition" => job.request.partition.clone(),
+            "State" if job.state == JobState::Cancelled => "CANCELLED by 1000".to_string(),
+            "State" => long_state(&job.state).to_string(),
+            "ExitCode" => format!("{}:{}", job.exit_code, job.signal),
+            "Elapsed" => format_duration(job.elapsed(state.now)),
+            "Timelimit" => format_duration(job.request.time_limit.as_secs() as i64),
+            "Submit" => time(Some(job.submit)),
+            "Start" => time(job.start),
+            "End" => time(job.end),
+            "NNodes" => job.request.nodes.to_string(),
+            "AllocCPUS" if job.start.is_none() => "0".to_string(),
+            "AllocCPUS" => job.request.cpus.to_string(),
+            "NodeList" if job.start.is_none() => "None assigned".to_string(),
+            "NodeList" => node_list(&state.nodes, &job.nodes),
+            "ReqMem" => format_memory(job.request.memory_mb),
+            "WorkDir" => self.log_dir.display().to_string(),
+            "Reason" => job.reason.to_string(),
+            // Only finished jobs have accounting totals; some jobs use
+            // little of what they asked for so the demo shows waste
+            "TotalCPU" if job.end.is_some() => format_duration(
+                (job.elapsed(state.now) as f64 * job.request.cpus as f64 * busyness(job.id)) as i64,
+            ),
+            "MaxRSS" if job.end.is_some() => format!(
+                "{}K",
+                (job.request.memory_mb as f64 * 1024.0 * busyness(job.id + 3)) as u64
+            ),
+            _ => String::new(),
+        }
+    }
+}
+
+/// A fixed share of its allocation that a job uses, from 0.1 to 0.95.
+fn busyness(id: u64) -> f64 {
+    0.1 + (id * 7 % 18) as f64 * 0.05
+}
+
+/// A random job for the demo; about a third belong to other users.
+fn random_request(state: &mut SimState, user: &str) -> JobRequest {
+    const NAMES: [&str; 8] = [
+        "train_model",
+        "preprocess",
+        "eval",
+        "sweep",
+        "render",
+        "align_reads",
+        "md_sim",
+        "postprocess",
+    ];
+    const PARTITIONS: [&str; 3] = ["debug", "gpu", "compute"];
+
+    let mut next = |n: u64| {
+        // xorshift64
+        state.rng ^= state.rng << 13;
+        state.rng ^= state.rng >> 7;
+        state.rng ^= state.rng << 17;
+        state.rng % n
+    };
+
+    let owner = match next(6) {
+        0 => "alice",
+        1 => "bob",
+        _ => user,
+    };
+    let mut request = JobRequest::new(
+        NAMES[next(NAMES.len() as u64) as usize],
+        owner,
+        PARTITIONS[next(PARTITIONS.len() as u64) as usize],
+    );
+    request.cpus = [1, 2, 4, 8][next(4) as usize];
+    request.memory_mb = u64::from(request.cpus) * 2048;
+    request.runtime = Duration::from_secs(60 * (2 + next(28)));
+    // Some limits are too tight, so the demo shows timeouts
+    let limit_minutes = request.runtime.as_secs() / 60 * (3 + next(6)) / 4;
+    request.time_limit = Duration::from_secs(60 * limit_minutes.max(1));
+    if next(7) == 0 {
+        request.outcome = SimOutcome::Failed(1);
+    }
+    request
+}
+
+/// Node names in Slurm's compressed hostlist form, e.g. `sim[03-04]`.
+fn node_list(nodes: &[SimNode], indices: &[usize]) -> String {
+    let mut numbers: Vec<usize> = indices.to_vec();
+    numbers.sort_unstable();
+    match numbers.as_slice() {
+        [] => return String::new(),
+        [only] => return nodes[*only].name.clone(),
+        _ => {}
+    }
+
+    let mut ranges: Vec<String> = Vec::new();
+    let mut i = 0;
+    while i < numbers.len() {
+        let mut j = i;
+        while j + 1 < numbers.len() && numbers[j + 1] == numbers[j] + 1 {
+            j += 1;
+        }
+        let first = nodes[numbers[i]].name.trim_start_matches("sim");
+        let last = nodes[numbers[j]].name.trim_start_matches("sim");
+        ranges.push(if i == j {
+            first.to_string()
+        } else {
+            format!("{}-{}", first, last)
+        });
+        i = j + 1;
+    }
+    format!("sim[{}]", ranges.join(","))
+}
+
+/// sinfo's long state for a node: idle, mixed or allocated.
+fn node_state(node: &SimNode) -> &'static str {
+    if node.used == 0 {
+        "idle"
+    } else if node.used < node.cpus {
+        "mixed"
+    } else {
+        "allocated"
+    }
+}
+
+fn long_state(state: &JobState) -> &'static str {
+    match state {
+        JobState::Pending => "PENDING",
+        JobState::Running => "RUNNING",
+        JobState::Completed => "COMPLETED",
+        JobState::Cancelled => "CANCELLED",
+        JobState::Failed => "FAILED",
+        JobState::Timeout => "TIMEOUT",
+        JobState::NodeFail => "NODE_FAIL",
+        JobState::Preempted => "PREEMPTED",
+        JobState::OutOfMemory => "OUT_OF_MEMORY",
+        JobState::Unknown(_) => "UNKNOWN",
+    }
+}
+
+/// `time + by`, saturating instead of overflowing for huge limits.
+fn after(time: DateTime<Utc>, by: Duration) -> DateTime<Utc> {
+    TimeDelta::from_std(by)
+        .ok()
+        .and_then(|by| time.checked_add_signed(by))
+        .unwrap_or(DateTime::<Utc>::MAX_UTC)
+}
+
+/// Local wall-clock time, as Slurm prints it.
+fn format_time(time: DateTime<Utc>) -> String {
+    time.with_timezone(&Local)
+        .format("%Y-%m-%dT%H:%M:%S")
+        .to_string()
+}
+
+/// `[D-]HH:MM:SS`, as scontrol and sacct print durations.
+fn format_duration(secs: i64) -> String {
+    let (days, hours, mins, secs) = (secs / 86400, secs / 3600 % 24, secs / 60 % 60, secs % 60);
+    if days > 0 {
+        format!("{}-{:02}:{:02}:{:02}", days, hours, mins, secs)
+    } else {
+        format!("{:02}:{:02}:{:02}", hours, mins, secs)
+    }
+}
+
+/// squeue's `%M`: `M:SS`, `H:MM:SS` or `D-HH:MM:SS`.
+fn format_squeue_time(secs: i64) -> String {
+    let (days, hours, mins, secs) = (secs / 86400, secs / 3600 % 24, secs / 60 % 60, secs % 60);
+    if days > 0 {
+        format!("{}-{:02}:{:02}:{:02}", days, hours, mins, secs)
+    } else if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, mins, secs)
+    } else {
+        format!("{}:{:02}", mins, secs)
+    }
+}
+
+fn format_memory(mb: u64) -> String {
+    if mb.is_multiple_of(1024) {
+        format!("{}G", mb / 1024)
+    } else {
+        format!("{}M", mb)
+    }
+}
@@ -1,50 +1,1056 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 
-use crate::models::{Job, JobList};
-use crate::slurm::{SlurmExecutor, SlurmParser, SlurmProcess};
+use crate::models::{
+    FairShare, Job, JobField, JobKey, JobList, JobPriority, JobState, JobUsage, Node, Partition,
+    SbatchDirective, SubmitField, TaskRangeError, format_task_ranges, parse_sbatch_directives,
+    parse_task_ranges_upto, parse_task_throttle, resubmit_options, script_requests,
+};
+use crate::slurm::{
+    CancelOptions, JobUpdate, SignalScope, SlurmExecutor, SlurmParser, SlurmProcess,
+    json_unsupported,
+};
 
 #[derive(Debug, Clone)]
 pub enum AppEvent {
     Refresh,
     JobSelected(String),
+    /// A background job refresh finished.
+    JobsFetched(JobsFetched),
+    /// A background refresh of the History, Nodes or Partitions tab
+    /// finished.
+    ViewFetched(ViewFetched),
+    /// A job's log tail, loaded in the background.
+    LogsFetched(JobLogs),
+    /// sstat figures of a running job, polled in the background.
+    UsageFetched(JobUsage),
+    /// A Slurm command started from a key, e.g. scancel or sbatch, landed.
+    ActionDone(Box<ActionDone>),
     Quit,
 }
 
+/// The outcome of a job refresh.
+#[derive(Debug, Clone)]
+pub struct JobsFetched {
+    /// Which refresh this answers; results overtaken by a newer refresh,
+    /// e.g. after the filters changed, are dropped.
+    pub generation: u64,
+    pub jobs: Result<Vec<Job>, String>,
+    pub output_format: Option<OutputFormat>,
+    /// The message shown when the refresh started. The outcome replaces
+    /// it, but not one set meanwhile, e.g. about an action.
+    pub shown_message: Option<String>,
+}
+
+/// The outcome of a History, Nodes or Partitions refresh.
+#[derive(Debug, Clone)]
+pub struct ViewFetched {
+    /// Like [`JobsFetched::generation`], counted separately.
+    pub generation: u64,
+    pub data: ViewData,
+    /// Like [`JobsFetched::shown_message`].
+    pub shown_message: Option<String>,
+}
+
+/// The outcome of a Slurm command started from a key. These run in the
+/// background like refreshes, so a slow or retrying controller does not
+/// freeze the UI; popups open once what they show has landed.
+#[derive(Debug, Clone)]
+pub enum ActionDone {
+    /// scancel of a job, or of part of its array.
+    Cancelled {
+        target: String,
+        result: Result<(), String>,
+    },
+    /// A confirmed hold, release or requeue.
+    Action {
+        action: JobAction,
+        job_id: String,
+        result: Result<(), String>,
+    },
+    /// A bulk action, with the index into `targets` and the error of each
+    /// failure.
+    Bulk {
+        action: JobAction,
+        targets: Vec<Job>,
+        failures: Vec<(usize, String)>,
+    },
+    Signalled {
+        signal: String,
+        job_id: String,
+        result: Result<(), String>,
+    },
+    /// `scontrol show job` for the edit form.
+    EditLoaded {
+        job: Job,
+        fields: Result<HashMap<String, String>, String>,
+    },
+    /// `scontrol update` from the edit form.
+    Edited {
+        job_id: String,
+        result: Result<(), String>,
+    },
+    /// `scontrol show job` for the resubmit form.
+    ResubmitLoaded {
+        job: Job,
+        fields: Result<HashMap<String, String>, String>,
+    },
+    /// sacct's tasks and scontrol's records for the array summary.
+    ArrayLoaded {
+        job: Job,
+        array_job_id: String,
+        tasks: Result<Vec<Job>, String>,
+        records: Result<HashMap<String, HashMap<String, String>>, String>,
+    },
+    /// sbatch for an array's failed tasks, with the new array's id.
+    ArrayResubmitted {
+        array_job_id: String,
+        cluster: Option<String>,
+        failed: Vec<u32>,
+        result: Result<String, String>,
+    },
+    /// sbatch from the submit form, with the new job's id.
+    Submitted {
+        cluster: Option<String>,
+        resubmitted: Option<JobKey>,
+        result: Result<String, String>,
+    },
+    PendingLoaded(Box<PendingReport>),
+    /// sacctmgr's clusters for the cluster selector, if it answered.
+    ClustersLoaded(Option<String>),
+}
+
+#[derive(Debug, Clone)]
+pub enum ViewData {
+    History(Result<Vec<Job>, String>),
+    Nodes(Result<Vec<Node>, String>),
+    Partitions(Result<Vec<Partition>, String>),
+}
+
+/// The tab shown in the left-hand list.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum View {
+    /// Jobs currently in the queue (squeue).
+    Jobs,
+    /// Jobs that have left the queue within the history window (sacct).
+    History,
+    /// Cluster nodes and what is running on them (sinfo).
+    Nodes,
+    /// Partitions with their limits and capacity; picks the partition filter.
+    Partitions,
+}
+
+impl View {
+    pub const ALL: [View; 4] = [View::Jobs, View::History, View::Nodes, View::Partitions];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            View::Jobs => "Jobs",
+            View::History => "History",
+            View::Nodes => "Nodes",
+            View::Partitions => "Partitions",
+        }
+    }
+
+    pub fn next(&self) -> View {
+        let idx = Self::ALL.iter().position(|v| v == self).unwrap_or(0);
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+}
+
+/// Number of log lines shown in the Job Logs panel.
+const LOG_TAIL_LINES: usize = 20;
+
+/// The tail of a job's log as last loaded for the Job Logs panel.
+#[derive(Debug, Clone)]
+pub struct JobLogs {
+    pub job_id: String,
+    pub content: String,
+}
+
+/// What the "Why pending?" popup shows, gathered when it opens.
+#[derive(Debug, Clone)]
+pub struct PendingReport {
+    pub job: Job,
+    /// The job's priority factors in its partition; `None` when sprio has
+    /// nothing for it, e.g. with priority/basic or for held jobs.
+    pub priority: Option<JobPriority>,
+    /// The job's place among the partition's pending jobs by priority,
+    /// counting from 1, and how many are pending there.
+    pub rank: Option<(usize, usize)>,
+    /// The job owner's fairshare standing.
+    pub fairshare: Option<FairShare>,
+    /// Why sprio or sshare could not be read.
+    pub errors: Vec<String>,
+}
+
+/// Signals offered by the signal picker, with what they usually mean.
+pub const COMMON_SIGNALS: [(&str, &str); 8] = [
+    ("USR1", "user-defined; often checkpoint"),
+    ("USR2", "user-defined"),
+    ("TERM", "ask to terminate"),
+    ("INT", "interrupt, like Ctrl+C"),
+    ("HUP", "hang up; often reload"),
+    ("STOP", "pause"),
+    ("CONT", "resume after STOP"),
+    ("KILL", "kill at once"),
+];
+
+/// The signal picker popup's choices for a snapshotted job.
+#[derive(Debug, Clone)]
+pub struct SignalPicker {
+    /// Snapshotted like [`App::cancel_target`].
+    pub job: Job,
+    /// Index into [`COMMON_SIGNALS`].
+    pub signal_index: usize,
+    pub scopes: Vec<SignalScope>,
+    pub scope_index: usize,
+}
+
+impl SignalPicker {
+    /// Offers the job's steps from `usage` (sstat) as scopes besides the
+    /// batch script and the whole job.
+    pub fn new(job: Job, usage: Option<&JobUsage>) -> Self {
+        let mut scopes = vec![SignalScope::Batch, SignalScope::Full, SignalScope::Steps];
+        if let Some(usage) = usage.filter(|usage| usage.job_id == job.job_id) {
+            scopes.extend(
+                usage
+                    .steps
+                    .iter()
+                    .filter(|step| !matches!(step.step_id.as_str(), "batch" | "extern"))
+                    .map(|step| SignalScope::Step(step.step_id.clone())),
+            );
+        }
+        Self {
+            job,
+            signal_index: 0,
+            scopes,
+            scope_index: 0,
+        }
+    }
+
+    pub fn next_signal(&mut self) {
+        self.signal_index = (self.signal_index + 1) % COMMON_SIGNALS.len();
+    }
+
+    pub fn previous_signal(&mut self) {
+        self.signal_index = (self.signal_index + COMMON_SIGNALS.len() - 1) % COMMON_SIGNALS.len();
+    }
+
+    pub fn next_scope(&mut self) {
+        self.scope_index = (self.scope_index + 1) % self.scopes.len();
+    }
+
+    pub fn signal(&self) -> &'static str {
+        COMMON_SIGNALS[self.signal_index].0
+    }
+
+    pub fn options(&self) -> CancelOptions {
+        CancelOptions::signal(self.signal(), self.scopes[self.scope_index].clone())
+    }
+}
+
+/// How much of a job array the cancel popup cancels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ArrayCancelScope {
+    /// The selected entry: one task, or a pending range squeue lists as one.
+    Task,
+    /// Every task, by cancelling the array job id.
+    Array,
+    /// Tasks typed as a range like "3-17,20".
+    Range,
+}
+
+/// The cancel popup's choices when the cancelled job is an array task.
+#[derive(Debug, Clone)]
+pub struct ArrayCancel {
+    pub array_job_id: String,
+    /// The selected entry's id and tasks, for [`ArrayCancelScope::Task`].
+    pub task_id: String,
+    pub task_ids: Vec<u32>,
+    /// Every task of the array in the job list, ascending.
+    pub known_tasks: Vec<u32>,
+    pub scope: ArrayCancelScope,
+    pub range: String,
+}
+
+impl ArrayCancel {
+    /// `None` unless `job` is an array task; its siblings are found in `jobs`.
+    pub fn new(job: &Job, jobs: &[Job]) -> Option<Self> {
+        let array_job_id = job.array_job_id.clone()?;
+        let mut known_tasks: Vec<u32> = jobs
+            .iter()
+            .filter(|other| {
+                other.cluster == job.cluster
+                    && other.array_job_id.as_deref() == Some(array_job_id.as_str())
+            })
+            .flat_map(Job::array_task_ids)
+            .collect();
+        known_tasks.sort_unstable();
+        known_tasks.dedup();
+        Some(Self {
+            array_job_id,
+            task_id: job.job_id.clone(),
+            task_ids: job.array_task_ids(),
+            known_tasks,
+            scope: ArrayCancelScope::Task,
+            range: String::new(),
+        })
+    }
+
+    pub fn next_scope(&mut self) {
+        self.scope = match self.scope {
+            ArrayCancelScope::Task => ArrayCancelScope::Array,
+            ArrayCancelScope::Array => ArrayCancelScope::Range,
+            ArrayCancelScope::Range => ArrayCancelScope::Task,
+        };
+    }
+
+    pub fn previous_scope(&mut self) {
+        self.scope = match self.scope {
+            ArrayCancelScope::Task => ArrayCancelScope::Range,
+            ArrayCancelScope::Array => ArrayCancelScope::Task,
+            ArrayCancelScope::Range => ArrayCancelScope::Array,
+        };
+    }
+
+    /// Only characters a range can hold are taken.
+    pub fn push(&mut self, c: char) {
+        if c.is_ascii_digit() || matches!(c, '-' | ',' | ':') {
+            self.range.push(c);
+        }
+    }
+
+    pub fn pop(&mut self) {
+        self.range.pop();
+    }
+
+    /// The id to hand scancel and how many tasks it covers, or why the
+    /// typed range cannot be used.
+    pub fn target(&self) -> Result<(String, usize), String> {
+        match self.scope {
+            ArrayCancelScope::Task => Ok((self.task_id.clone(), self.task_ids.len().max(1))),
+            ArrayCancelScope::Array => Ok((self.array_job_id.clone(), self.known_tasks.len())),
+            ArrayCancelScope::Range => {
+                if self.range.is_empty() {
+                    return Err("type task ids, e.g. 3-17,20".to_string());
+                }
+                let unknown = |task: u32| {
+                    format!(
+                        "array {} has no task {} in the queue",
+                        self.array_job_id, task
+                    )
+                };
+                // Nothing past the highest known task is expanded.
+                let max = self.known_tasks.last().copied().unwrap_or(0);
+                let tasks = match parse_task_ranges_upto(&self.range, max) {
+                    Ok(tasks) => tasks,
+                    Err(TaskRangeError::Beyond(task)) => return Err(unknown(task)),
+                    Err(TaskRangeError::Malformed) => {
+                        return Err("expected ranges like 3-17,20".to_string());
+                    }
+                };
+                if let Some(task) = tasks
+                    .iter()
+                    .find(|t| self.known_tasks.binary_search(t).is_err())
+                {
+                    return Err(unknown(*task));
+                }
+                Ok((
+                    format!("{}_[{}]", self.array_job_id, self.range),
+                    tasks.len(),
+                ))
+            }
+        }
+    }
+}
+
+/// One line of the edit or submit form.
+#[derive(Debug, Clone)]
+pub struct FormField<F = JobField> {
+    pub field: F,
+    /// As `scontrol show job` reported it, or the script's directive.
+    pub original: String,
+    pub value: String,
+}
+
+impl<F> FormField<F> {
+    pub fn is_changed(&self) -> bool {
+        self.value.trim() != self.original
+    }
+}
+
+/// The edit popup's form, pre-filled from `scontrol show job`.
+#[derive(Debug, Clone)]
+pub struct JobEditForm {
+    /// Snapshotted like [`App::cancel_target`].
+    pub job: Job,
+    pub fields: Vec<FormField>,
+    pub selected: usize,
+    /// Why the last apply failed, from validation or the controller.
+    pub error: Option<String>,
+}
+
+impl JobEditForm {
+    pub fn new(job: Job, scontrol_fields: &HashMap<String, String>) -> Self {
+        let fields = JobField::EDITABLE
+            .iter()
+            .map(|&field| {
+                // scontrol prints unset values as "(null)" or "N/A"
+                let original = scontrol_fields
+                    .get(field.key())
+                    .filter(|value| !matches!(value.as_str(), "(null)" | "N/A"))
+                    .cloned()
+                    .unwrap_or_default();
+                FormField {
+                    field,
+                    value: original.clone(),
+                    original,
+                }
+            })
+            .collect();
+        Self {
+            job,
+            fields,
+            selected: 0,
+            error: None,
+        }
+    }
+
+    pub fn next_field(&mut self) {
+        self.selected = (self.selected + 1) % self.fields.len();
+    }
+
+    pub fn previous_field(&mut self) {
+        self.selected = (self.selected + self.fields.len() - 1) % self.fields.len();
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.fields[self.selected].value.push(c);
+    }
+
+    pub fn pop(&mut self) {
+        self.fields[self.selected].value.pop();
+    }
+
+    /// The changed fields as `scontrol update` arguments, or the index of
+    /// the first invalid one and why.
+    pub fn changes(&self) -> Result<JobUpdate, (usize, String)> {
+        let mut changes = Vec::new();
+        for (i, field) in self.fields.iter().enumerate() {
+            if !field.is_changed() {
+                continue;
+            }
+            let value = field.value.trim();
+            field
+                .field
+                .validate(value)
+                .map_err(|why| (i, format!("{}: {}", field.field.label(), why)))?;
+            changes.push((field.field.key().to_string(), value.to_string()));
+        }
+        Ok(changes)
+    }
+}
+
+/// The submit popup's file browser. It starts where lazyslurm was started
+/// and can go into subdirectories, but not above that.
+#[derive(Debug, Clone)]
+pub struct FilePicker {
+    pub root: PathBuf,
+    pub dir: PathBuf,
+    /// `..` below the root, then subdirectories, then batch scripts, each
+    /// by name.
+    pub entries: Vec<PathBuf>,
+    pub selected: usize,
+}
+
+impl FilePicker {
+    pub fn new(root: PathBuf) -> Result<Self> {
+        let mut picker = Self {
+            dir: root.clone(),
+            root,
+            entries: Vec::new(),
+            selected: 0,
+        };
+        picker.read_dir()?;
+        Ok(picker)
+    }
+
+    fn read_dir(&mut self) -> Result<()> {
+        let mut dirs = Vec::new();
+        let mut scripts = Vec::new();
+        let entries = std::fs::read_dir(&self.dir)
+            .with_context(|| format!("Failed to list {}", self.dir.display()))?;
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let path = entry.path();
+            let hidden = entry.file_name().to_string_lossy().starts_with('.');
+            if path.is_dir() && !hidden {
+                dirs.push(path);
+            } else if is_batch_script(&path) {
+                scripts.push(path);
+            }
+        }
+        dirs.sort();
+        scripts.sort();
+
+        self.entries = Vec::new();
+        if self.dir != self.root {
+            self.entries.push(self.dir.join(".."));
+        }
+        self.entries.extend(dirs);
+        self.entries.extend(scripts);
+        self.selected = 0;
+        Ok(())
+    }
+
+    pub fn next(&mut self) {
+        if !self.entries.is_empty() {
+            self.selected = (self.selected + 1) % self.entries.len();
+        }
+    }
+
+    pub fn previous(&mut self) {
+        if !self.entries.is_empty() {
+            self.selected = (self.selected + self.entries.len() - 1) % self.entries.len();
+        }
+    }
+
+    /// Go into the selected directory, or return the selected script.
+    pub fn enter(&mut self) -> Result<Option<PathBuf>> {
+        let Some(path) = self.entries.get(self.selected).cloned() else {
+            return Ok(None);
+        };
+        if path.ends_with("..") {
+            if let Some(parent) = self.dir.parent() {
+                self.dir = parent.to_path_buf();
+            }
+        } else if path.is_dir() {
+            self.dir = path;
+        } else {
+            return Ok(Some(path));
+        }
+        self.read_dir()?;
+        Ok(None)
+    }
+}
+
+/// `*.sh` and `*.sbatch` files.
+fn is_batch_script(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .is_some_and(|ext| ext == "sh" || ext == "sbatch")
+}
+
+/// The submit popup's form: sbatch options pre-filled from the script's
+/// `#SBATCH` directives, next to a preview of those directives.
+#[derive(Debug, Clone)]
+pub struct SubmitForm {
+    pub script: PathBuf,
+    pub directives: Vec<SbatchDirective>,
+    pub fields: Vec<FormField<SubmitField>>,
+    pub selected: usize,
+    /// Why the last submission failed, from validation or sbatch.
+    pub error: Option<String>,
+    /// Set when the form submits a finished job again.
+    pub resubmit: Option<Resubmit>,
+    /// Shown above the directives, e.g. when a resubmitted job's script
+    /// has changed since it was submitted.
+    pub warning: Option<String>,
+    /// Whether `directives` come from the script. When they do not, a
+    /// resubmitted job's options are all sent, whatever the script says.
+    pub script_read: bool,
+}
+
+/// A finished job being submitted again, with what it asked for.
+#[derive(Debug, Clone)]
+pub struct Resubmit {
+    /// Snapshotted like [`App::cancel_target`].
+    pub job: Job,
+    /// Options the form does not show, such as the working directory;
+    /// sent as they are.
+    pub options: JobUpdate,
+}
+
+impl SubmitForm {
+    pub fn new(script: PathBuf, contents: &str) -> Self {
+        let directives = parse_sbatch_directives(contents);
+        let fields = SubmitField::EDITABLE
+            .iter()
+            .map(|&field| {
+                // As with sbatch, a repeated directive overrides earlier ones
+                let original = directives
+                    .iter()
+                    .rev()
+                    .find(|directive| directive.option == field.option())
+                    .and_then(|directive| directive.value.clone())
+                    .unwrap_or_default();
+                FormField {
+                    field,
+                    value: original.clone(),
+                    original,
+                }
+            })
+            .collect();
+        Self {
+            script,
+            directives,
+            fields,
+            selected: 0,
+            error: None,
+            resubmit: None,
+            warning: None,
+            script_read: true,
+        }
+    }
+
+    /// A form for submitting `job` again, pre-filled with what it asked
+    /// for rather than with its script's directives, which it may have
+    /// overridden. `contents` is the script, if it can be read from here,
+    /// and `modified` when it was last written.
+    pub fn resubmit(
+        job: Job,
+        script: PathBuf,
+        contents: Option<&str>,
+        modified: Option<DateTime<Utc>>,
+    ) -> Self {
+        // sbatch runs the script as it is now, not as it was submitted
+        let warning = if contents.is_none() {
+            Some(format!(
+                "The script cannot be read from here, so everything job {} asked for is sent",
+                job.job_id
+            ))
+        } else {
+            modified
+                .zip(job.submit_time)
+                .filter(|(modified, submitted)| modified > submitted)
+                .map(|_| {
+                    format!(
+                        "The script has changed since job {} was submitted; its current version runs",
+                        job.job_id
+                    )
+                })
+        };
+        let fields = SubmitField::EDITABLE
+            .iter()
+            .map(|&field| {
+                let original = field.value_of(&job).unwrap_or_default();
+                FormField {
+                    field,
+                    value: original.clone(),
+                    original,
+                }
+            })
+            .collect();
+        Self {
+            script,
+            directives: contents.map(parse_sbatch_directives).unwrap_or_default(),
+            fields,
+            selected: 0,
+            error: None,
+            resubmit: Some(Resubmit {
+                options: resubmit_options(&job),
+                job,
+            }),
+            warning,
+            script_read: contents.is_some(),
+        }
+    }
+
+    /// Whether `field` is handed to sbatch: when changed, or when it holds
+    /// what a resubmitted job asked for beyond what its script says.
+    fn is_sent(&self, field: &FormField<SubmitField>) -> bool {
+        let value = field.value.trim();
+        field.is_changed()
+            || (self.resubmit.is_some()
+                && !value.is_empty()
+                && !self.script_requests(field.field.option(), value))
+    }
+
+    /// The options of a resubmitted job the form does not show, less
+    /// those its script asks for by itself.
+    fn resubmit_options(&self) -> impl Iterator<Item = &(String, String)> {
+        self.resubmit
+            .iter()
+            .flat_map(|resubmit| &resubmit.options)
+            .filter(|(option, value)| !self.script_requests(option, value))
+    }
+
+    /// Whether the script, as read, asks for `value` for `option`; never
+    /// when it could not be read.
+    fn script_requests(&self, option: &str, value: &str) -> bool {
+        self.script_read && script_requests(&self.directives, option, value)
+    }
+
+    pub fn next_field(&mut self) {
+        self.selected = (self.selected + 1) % self.fields.len();
+    }
+
+    pub fn previous_field(&mut self) {
+        self.selected = (self.selected + self.fields.len() - 1) % self.fields.len();
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.fields[self.selected].value.push(c);
+    }
+
+    pub fn pop(&mut self) {
+        self.fields[self.selected].value.pop();
+    }
+
+    /// The fields to send as sbatch options, after those of a resubmitted
+    /// job the form does not show, or the index of the first invalid one
+    /// and why.
+    pub fn overrides(&self) -> Result<JobUpdate, (usize, String)> {
+        let mut overrides: JobUpdate = self.resubmit_options().cloned().collect();
+        for (i, field) in self.fields.iter().enumerate() {
+            if !self.is_sent(field) {
+                continue;
+            }
+            let value = field.value.trim();
+            field
+                .field
+                .validate(value)
+                .map_err(|why| (i, format!("{}: {}", field.field.label(), why)))?;
+            overrides.push((field.field.option().to_string(), value.to_string()));
+        }
+        Ok(overrides)
+    }
+
+    /// Whether the form overrides `directive`.
+    pub fn overrides_directive(&self, directive: &SbatchDirective) -> bool {
+        let resubmitted = self
+            .resubmit_options()
+            .any(|(option, _)| *option == directive.option);
+        resubmitted
+            || self
+                .fields
+                .iter()
+                .any(|field| self.is_sent(field) && field.field.option() == directive.option)
+    }
+}
+
+/// The node picker for attaching a shell to a job that runs on several.
+#[derive(Debug, Clone)]
+pub struct NodePicker {
+    /// Snapshotted like [`App::cancel_target`].
+    pub job: Job,
+    /// The job's nodes, expanded from its node list.
+    pub nodes: Vec<String>,
+    pub selected: usize,
+}
+
+impl NodePicker {
+    pub fn next(&mut self) {
+        self.selected = (self.selected + 1) % self.nodes.len();
+    }
+
+    pub fn previous(&mut self) {
+        self.selected = (self.selected + self.nodes.len() - 1) % self.nodes.len();
+    }
+}
+
+/// A shell the event loop runs on the terminal in place of the TUI.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShellRequest {
+    pub job_id: String,
+    /// Program and arguments, from [`SlurmExecutor::shell_command`].
+    pub command: Vec<String>,
+}
+
+/// What the array summary popup shows for a job array, gathered when it
+/// opens: finished tasks from sacct, queued ones from the job list.
+#[derive(Debug, Clone)]
+pub struct ArraySummary {
+    pub array_job_id: String,
+    /// The array as `scontrol show job` knows it, with its script and what
+    /// it asked for, or else the selected task. Snapshotted like
+    /// [`App::cancel_target`].
+    pub job: Job,
+    /// How many tasks are in each state, most common first.
+    pub counts: Vec<(JobState, usize)>,
+    /// Tasks that failed, timed out or ran out of memory, ascending.
+    pub failed: Vec<u32>,
+    /// The array's `%N` limit on tasks running at once.
+    pub throttle: Option<u32>,
+    /// Why sacct, scontrol or the last resubmission failed.
+    pub errors: Vec<String>,
+}
+
+impl ArraySummary {
+    pub fn total(&self) -> usize {
+        self.counts.iter().map(|(_, count)| count).sum()
+    }
+
+    /// `--array` for the failed tasks under the original throttle, e.g.
+    /// "1,3-5%4". `None` when no task failed.
+    pub fn failed_array(&self) -> Option<String> {
+        if self.failed.is_empty() {
+            return None;
+        }
+        let mut array = format_task_ranges(&self.failed);
+        if let Some(throttle) = self.throttle {
+            array.push_str(&format!("%{}", throttle));
+        }
+        Some(array)
+    }
+
+    /// sbatch options for running the failed tasks again: what the array
+    /// asked for that its script, with `directives`, leaves unset, and
+    /// `--array` narrowed to those tasks. Unlike the resubmit form, which
+    /// shows what is sent, any option the script sets now is left to it:
+    /// the command line would override a limit raised there after a task
+    /// ran out of memory or time. With no `directives`, the script could
+    /// not be read and everything the array asked for is sent.
+    pub fn resubmit_options(&self, directives: Option<&[SbatchDirective]>) -> Option<JobUpdate> {
+        let array = self.failed_array()?;
+        let mut options: JobUpdate = resubmit_options(&self.job)
+            .into_iter()
+            .filter(|(option, _)| option != "array")
+            .collect();
+        options.extend(
+            SubmitField::EDITABLE
+                .iter()
+                .filter_map(|field| Some((field.option().to_string(), field.value_of(&self.job)?))),
+        );
+        if let Some(directives) = directives {
+            options.retain(|(option, value)| {
+                !directives
+                    .iter()
+                    .any(|directive| directive.option == *option)
+                    && !script_requests(directives, option, value)
+            });
+        }
+        options.push(("array".to_string(), array));
+        Some(options)
+    }
+}
+
+/// Which squeue/scontrol output format the app reads.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutputFormat {
+    Text,
+    /// `--json`, available from Slurm 21.08.
+    Json,
+}
+
+/// Order of the Jobs tab.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum JobSort {
+    /// As squeue lists them.
+    #[default]
+    Queue,
+    /// Jobs that already started first, then pending ones by expected
+    /// start; those without an estimate last.
+    ExpectedStart,
+}
+
+impl JobSort {
+    pub fn sort(&self, jobs: &mut [Job]) {
+        if *self == JobSort::ExpectedStart {
+            jobs.sort_by_key(|job| {
+                let pending = job.state == JobState::Pending;
+                (pending, job.estimated_start.is_none(), job.estimated_start)
+            });
+        }
+    }
+}
+
+/// A job action confirmed through [`AppState::JobActionPopup`], or for
+/// marked jobs through [`AppState::BulkActionPopup`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum JobAction {
+    Cancel,
+    Hold,
+    Release,
+    Requeue,
+}
+
+impl JobAction {
+    pub fn title(&self) -> &'static str {
+        match self {
+            JobAction::Cancel => "Cancel",
+            JobAction::Hold => "Hold",
+            JobAction::Release => "Release",
+            JobAction::Requeue => "Requeue",
+        }
+    }
+
+    /// Whether Slurm accepts the action for `job` in its current state.
+    pub fn applies_to(&self, job: &Job) -> bool {
+        match self {
+            JobAction::Cancel => !job.is_finished(),
+            JobAction::Hold => job.state == JobState::Pending && !job.is_held(),
+            JobAction::Release => job.is_held(),
+            // Only batch jobs that are running, suspended or have ended;
+            // states such as configuring or completing are left alone
+            JobAction::Requeue => {
+                job.batch != Some(false)
+                    && (job.is_suspended()
+                        || !matches!(job.state, JobState::Pending | JobState::Unknown(_)))
+            }
+        }
+    }
+
+    async fn run(&self, executor: &dyn SlurmExecutor, job: &Job) -> Result<()> {
+        let cluster = job.cluster.as_deref();
+        match self {
+            JobAction::Cancel => {
+                executor
+                    .scancel(&job.job_id, cluster, &CancelOptions::default())
+                    .await
+            }
+            JobAction::Hold => executor.hold(&job.job_id, cluster).await,
+            JobAction::Release => executor.release(&job.job_id, cluster).await,
+            JobAction::Requeue => executor.requeue(&job.job_id, cluster).await,
+        }
+    }
+}
+
+/// How many jobs a bulk action works on at once, to spare the controller.
+const BULK_CONCURRENCY: usize = 8;
+
+/// The outcome of a bulk action that failed for some jobs.
+#[derive(Debug, Clone)]
+pub struct BulkReport {
+    pub action: JobAction,
+    pub succeeded: usize,
+    /// Job id and error of each failure, in list order.
+    pub failures: Vec<(String, String)>,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum AppState {
     Normal,
-    PartitionSearchPopup,
     UserSearchPopup,
     CancelJobPopup,
+    ClusterSelectPopup,
+    WhyPendingPopup,
+    JobActionPopup(JobAction),
+    /// Confirm an action on every marked job it applies to.
+    BulkActionPopup(JobAction),
+    BulkReportPopup,
+    EditJobPopup,
+    SignalPopup,
+    /// The signal picked in [`AppState::SignalPopup`] awaits a y/n.
+    ConfirmSignalPopup,
+    /// Pick a batch script to submit.
+    SubmitFilePopup,
+    /// Override the picked script's options before submitting it.
+    SubmitFormPopup,
+    /// Task counts of a job array, with its failed tasks to resubmit.
+    ArraySummaryPopup,
+    /// Pick which of a job's nodes to attach a shell on.
+    AttachNodePopup,
 }
 
 pub struct App {
     pub job_list: JobList,
     pub state: AppState,
+    pub view: View,
+    /// Finished jobs from sacct, shown in the History tab.
+    pub history_list: JobList,
+    pub history_index: usize,
+    /// How far back the History tab looks.
+    pub history_window: Duration,
+    /// Nodes from sinfo, shown in the Nodes tab.
+    pub node_list: Vec<Node>,
+    pub node_index: usize,
+    /// Partitions from sinfo, shown in the Partitions tab.
+    pub partition_list: Vec<Partition>,
+    pub partition_index: usize,
     pub selected_job_index: usize,
     pub selected_job: Option<Job>,
+    pub job_sort: JobSort,
     pub current_user: Option<String>,
     pub current_partition: Option<String>,
+    /// Passed to `-M`: a cluster name, a comma separated list, or "all".
+    /// `None` means the local cluster.
+    pub current_clusters: Option<String>,
+    /// Choices in the cluster selector popup; `None` is the local cluster.
+    pub cluster_options: Vec<Option<String>>,
+    pub cluster_popup_index: usize,
     pub last_refresh: Instant,
     pub refresh_interval: Duration,
     pub is_loading: bool,
     pub error_message: Option<String>,
     pub event_sender: mpsc::UnboundedSender<AppEvent>,
     pub event_receiver: mpsc::UnboundedReceiver<AppEvent>,
+    /// Filled when the "Why pending?" popup opens.
+    pub pending_report: Option<PendingReport>,
     /// Job snapshotted when the cancel popup opens, so the cancel always
     /// applies to the job the user confirmed, even if the list refreshes
     /// underneath the popup.
     pub cancel_target: Option<Job>,
+    /// Set with [`Self::cancel_target`] when it is an array task.
+    pub array_cancel: Option<ArrayCancel>,
+    /// Snapshotted like [`Self::cancel_target`] for the hold, release and
+    /// requeue popups.
+    pub action_target: Option<Job>,
+    pub edit_form: Option<JobEditForm>,
+    pub signal_picker: Option<SignalPicker>,
+    /// Jobs marked for a bulk action. Marks are dropped when a job leaves
+    /// the list.
+    pub marked_jobs: HashSet<JobKey>,
+    /// Where a `v` range starts: the last job marked with space.
+    pub mark_anchor: Option<JobKey>,
+    /// Snapshotted like [`Self::cancel_target`] when a bulk action popup
+    /// opens.
+    pub bulk_targets: Vec<Job>,
+    /// Marked jobs left out of `bulk_targets` because the action does not
+    /// apply to them.
+    pub bulk_skipped: usize,
+    pub bulk_report: Option<BulkReport>,
+    /// Where the submit popup's file picker starts.
+    pub submit_dir: PathBuf,
+    pub file_picker: Option<FilePicker>,
+    pub submit_form: Option<SubmitForm>,
+    /// A job just submitted, selected once squeue lists it.
+    pub submitted_job: Option<JobKey>,
+    /// Jobs resubmitted this session, to the job that replaced them.
+    /// Failed array tasks resubmitted together map to their task of the
+    /// new array, and the old array id to the new one.
+    pub resubmissions: HashMap<JobKey, JobKey>,
+    /// Filled when the array summary popup opens.
+    pub array_summary: Option<ArraySummary>,
+    pub node_picker: Option<NodePicker>,
+    /// Taken by the event loop, which suspends the TUI while it runs.
+    pub shell_request: Option<ShellRequest>,
     pub input: String,
     pub executor: Arc<dyn SlurmExecutor>,
+    /// Detected from the Slurm version on the first refresh.
+    pub output_format: Option<OutputFormat>,
+    /// Read through the executor so logs also show when Slurm is remote.
+    pub job_logs: Option<JobLogs>,
+    /// sstat figures for the selected job while it runs.
+    pub job_usage: Option<JobUsage>,
+    /// Bumped whenever a job refresh starts.
+    pub refresh_generation: u64,
+    /// A background refresh started by [`Self::start_refresh`] is running.
+    pub refresh_in_flight: bool,
+    /// Bumped whenever a History, Nodes or Partitions refresh starts.
+    pub view_generation: u64,
+    /// Background fetches whose event has not been handled yet.
+    pub fetches_in_flight: usize,
+    /// The job whose logs are loading in the background.
+    pub logs_loading: Option<String>,
+    /// The job whose usage is being polled in the background.
+    pub usage_loading: Option<String>,
+    /// Slurm commands started from a key that have not landed yet.
+    pub actions_in_flight: usize,
+    /// A popup is being filled, or is sending what it holds; keys that
+    /// would start another such command are ignored until it lands.
+    pub popup_loading: bool,
 }
 
 impl App {
     pub fn new() -> Self {
-        Self::with_executor(Arc::new(SlurmProcess))
+        Self::with_executor(Arc::new(SlurmProcess::new()))
     }
 
     pub fn with_executor(executor: Arc<dyn SlurmExecutor>) -> Self {
@@ -53,24 +1059,69 @@ impl App {
         Self {
             job_list: JobList::new(),
             state: AppState::Normal,
+            view: View::Jobs,
+            history_list: JobList::new(),
+            history_index: 0,
+            history_window: Duration::from_secs(24 * 60 * 60),
+            node_list: Vec::new(),
+            node_index: 0,
+            partition_list: Vec::new(),
+            partition_index: 0,
             selected_job_index: 0,
             selected_job: None,
+            job_sort: JobSort::default(),
             current_user: std::env::var("USER").ok(),
             current_partition: None,
+            current_clusters: None,
+            cluster_options: Vec::new(),
+            cluster_popup_index: 0,
             last_refresh: Instant::now(),
             refresh_interval: Duration::from_secs(2),
             is_loading: false,
             error_message: None,
             event_sender,
             event_receiver,
+            pending_report: None,
             cancel_target: None,
+            array_cancel: None,
+            action_target: None,
+            edit_form: None,
+            signal_picker: None,
+            marked_jobs: HashSet::new(),
+            mark_anchor: None,
+            bulk_targets: Vec::new(),
+            bulk_skipped: 0,
+            bulk_report: None,
+            submit_dir: std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
+            file_picker: None,
+            submit_form: None,
+            submitted_job: None,
+            resubmissions: HashMap::new(),
+            array_summary: None,
+            node_picker: None,
+            shell_request: None,
             input: "".to_string(),
             executor,
+            output_format: None,
+            job_logs: None,
+            job_usage: None,
+            refresh_generation: 0,
+            refresh_in_flight: false,
+            view_generation: 0,
+            fetches_in_flight: 0,
+            logs_loading: None,
+            usage_loading: None,
+            actions_in_flight: 0,
+            popup_loading: false,
         }
     }
 
-    pub fn with_cli(user: Option<String>, partition: Option<String>) -> Self {
-        let mut app = Self::new();
+    pub fn with_cli(
+        executor: Arc<dyn SlurmExecutor>,
+        user: Option<String>,
+        partition: Option<String>,
+    ) -> Self {
+        let mut app = Self::with_executor(executor);
         if user.is_some() {
             app.current_user = user;
         }
@@ -80,76 +1131,605 @@ impl App {
 
     pub async fn refresh_jobs(&mut self) -> Result<()> {
         self.is_loading = true;
-        self.error_message = None;
+        let fetched = self.job_fetch().run().await;
+        // Whatever runs in the background has been overtaken
+        self.refresh_in_flight = false;
+        self.apply_jobs(fetched);
+        self.refresh_logs().await;
+        self.refresh_usage().await;
+        Ok(())
+    }
 
-        match self.fetch_jobs().await {
-            Ok(jobs) => {
-                let previous_id = self.selected_job.as_ref().map(|j| j.job_id.clone());
-                self.job_list.update(jobs);
-                self.sync_selection(previous_id.as_deref());
-                self.last_refresh = Instant::now();
-            }
-            Err(e) => {
-                self.error_message = Some(format!("Failed to fetch jobs: {}", e));
-            }
+    /// Refresh jobs on a background task so a slow controller does not
+    /// freeze the UI. The result arrives as [`AppEvent::JobsFetched`] and
+    /// is applied by [`Self::handle_app_event`].
+    pub fn start_refresh(&mut self) {
+        if self.refresh_in_flight {
+            return;
         }
-
-        self.is_loading = false;
-        Ok(())
+        self.restart_refresh();
     }
 
-    async fn fetch_jobs(&self) -> Result<Vec<Job>> {
-        let squeue_output = self
-            .executor
-            .squeue(
-                self.current_user.as_deref(),
-                self.current_partition.as_deref(),
-            )
-            .await?;
-        let mut jobs = SlurmParser::parse_squeue_output(&squeue_output)?;
+    /// Like [`Self::start_refresh`], for when the jobs just changed, e.g.
+    /// after an action or a new filter: a refresh already running predates
+    /// the change, so it is overtaken rather than waited for.
+    pub fn restart_refresh(&mut self) {
+        self.refresh_in_flight = true;
+        self.is_loading = true;
+        let fetch = self.job_fetch();
+        self.spawn_fetch(async move { AppEvent::JobsFetched(fetch.run().await) });
+    }
 
-        // For each job, get detailed info from scontrol (but only for first few to avoid overwhelming)
-        for job in jobs.iter_mut().take(10) {
-            if let Ok(scontrol_output) = self.executor.scontrol_show_job(&job.job_id).await
-                && let Ok(fields) = SlurmParser::parse_scontrol_output(&scontrol_output)
-            {
-                SlurmParser::enhance_job_with_scontrol_data(job, fields);
-            }
+    /// Refresh whatever the current tab is showing, in the background.
+    pub fn start_view_refresh(&mut self) {
+        if self.view == View::Jobs {
+            self.restart_refresh();
+            return;
         }
+        self.is_loading = true;
+        let fetch = self.view_fetch(self.view);
+        self.spawn_fetch(async move { AppEvent::ViewFetched(fetch.run().await) });
+    }
 
-        Ok(jobs)
+    fn spawn_fetch(&mut self, fetch: impl Future<Output = AppEvent> + Send + 'static) {
+        self.fetches_in_flight += 1;
+        let sender = self.event_sender.clone();
+        tokio::spawn(async move {
+            let _ = sender.send(fetch.await);
+        });
     }
 
-    pub fn should_refresh(&self) -> bool {
-        self.last_refresh.elapsed() >= self.refresh_interval
+    /// Run a Slurm command started from a key in the background. Its
+    /// outcome arrives as [`AppEvent::ActionDone`].
+    fn spawn_action(&mut self, action: impl Future<Output = ActionDone> + Send + 'static) {
+        self.actions_in_flight += 1;
+        self.spawn_fetch(async move { AppEvent::ActionDone(Box::new(action.await)) });
     }
 
-    pub fn select_next_job(&mut self) {
-        if !self.job_list.jobs.is_empty() && self.selected_job_index < self.job_list.jobs.len() - 1
-        {
-            self.selected_job_index += 1;
-            self.update_selected_job();
+    pub fn handle_app_event(&mut self, event: AppEvent) {
+        match event {
+            AppEvent::JobsFetched(fetched) => {
+                self.fetches_in_flight = self.fetches_in_flight.saturating_sub(1);
+                if fetched.generation == self.refresh_generation {
+                    self.refresh_in_flight = false;
+                    self.apply_jobs(fetched);
+                    self.start_logs_fetch();
+                    self.start_usage_fetch();
+                }
+            }
+            AppEvent::ViewFetched(fetched) => {
+                self.fetches_in_flight = self.fetches_in_flight.saturating_sub(1);
+                if fetched.generation == self.view_generation {
+                    let history = matches!(fetched.data, ViewData::History(_));
+                    self.apply_view(fetched);
+                    if history {
+                        self.start_logs_fetch();
+                    }
+                }
+            }
+            AppEvent::LogsFetched(logs) => {
+                self.fetches_in_flight = self.fetches_in_flight.saturating_sub(1);
+                if self.logs_loading.as_ref() == Some(&logs.job_id) {
+                    self.logs_loading = None;
+                }
+                // The selection may have moved on while they loaded
+                if self
+                    .get_selected_job()
+                    .is_some_and(|job| job.job_id == logs.job_id)
+                {
+                    self.job_logs = Some(logs);
+                }
+            }
+            AppEvent::UsageFetched(usage) => {
+                self.fetches_in_flight = self.fetches_in_flight.saturating_sub(1);
+                if self.usage_loading.as_ref() == Some(&usage.job_id) {
+                    self.usage_loading = None;
+                }
+                if self
+                    .get_selected_job()
+                    .is_some_and(|job| job.is_running() && job.job_id == usage.job_id)
+                {
+                    self.job_usage = Some(usage);
+                }
+            }
+            AppEvent::ActionDone(done) => {
+                self.fetches_in_flight = self.fetches_in_flight.saturating_sub(1);
+                self.actions_in_flight = self.actions_in_flight.saturating_sub(1);
+                self.apply_action(*done);
+            }
+            AppEvent::Refresh | AppEvent::JobSelected(_) | AppEvent::Quit => {}
         }
     }
 
-    pub fn select_previous_job(&mut self) {
-        if self.selected_job_index > 0 {
-            self.selected_job_index -= 1;
-            self.update_selected_job();
+    /// Handle events until every background fetch has landed, as the
+    /// event loop would.
+    pub async fn wait_for_fetches(&mut self) {
+        while self.fetches_in_flight > 0 {
+            let Some(event) = self.receive_event().await else {
+                return;
+            };
+            self.handle_app_event(event);
         }
     }
 
-    fn update_selected_job(&mut self) {
-        self.selected_job = self.job_list.jobs.get(self.selected_job_index).cloned();
+    fn job_fetch(&mut self) -> JobFetch {
+        self.refresh_generation += 1;
+        JobFetch {
+            generation: self.refresh_generation,
+            executor: self.executor.clone(),
+            user: self.current_user.clone(),
+            partition: self.current_partition.clone(),
+            clusters: self.current_clusters.clone(),
+            output_format: self.output_format,
+            shown_message: self.error_message.clone(),
+        }
     }
 
-    /// Re-resolve the selection after the job list changes. Follows the
-    /// previously selected job by id if it still exists, otherwise clamps
-    /// the index so it stays in bounds.
-    pub fn sync_selection(&mut self, previous_id: Option<&str>) {
-        if let Some(idx) =
-            previous_id.and_then(|id| self.job_list.jobs.iter().position(|j| j.job_id == id))
-        {
+    fn view_fetch(&mut self, view: View) -> ViewFetch {
+        self.view_generation += 1;
+        ViewFetch {
+            generation: self.view_generation,
+            view,
+            executor: self.executor.clone(),
+            user: self.current_user.clone(),
+            partition: self.current_partition.clone(),
+            clusters: self.current_clusters.clone(),
+            history_window: self.history_window,
+            shown_message: self.error_message.clone(),
+        }
+    }
+
+    fn apply_jobs(&mut self, fetched: JobsFetched) {
+        self.output_format = fetched.output_format.or(self.output_format);
+        if self.error_message == fetched.shown_message {
+            self.error_message = None;
+        }
+
+        match fetched.jobs {
+            Ok(mut jobs) => {
+                let previous_id = self.selected_job.as_ref().map(|j| j.job_id.clone());
+                self.job_sort.sort(&mut jobs);
+                let present: HashSet<JobKey> = jobs.iter().map(Job::key).collect();
+                self.marked_jobs.retain(|key| present.contains(key));
+                self.job_list.update(jobs);
+                self.sync_selection(previous_id.as_deref());
+                self.select_submitted_job();
+            }
+            Err(e) => {
+                self.error_message = Some(format!("Failed to fetch jobs: {}", e));
+            }
+        }
+
+        // Also after a failure, so a struggling controller is not polled
+        // on every tick
+        self.last_refresh = Instant::now();
+        self.is_loading = false;
+    }
+
+    fn apply_view(&mut self, fetched: ViewFetched) {
+        if self.error_message == fetched.shown_message {
+            self.error_message = None;
+        }
+
+        match fetched.data {
+            ViewData::History(Ok(jobs)) => {
+                self.history_list.update(jobs);
+                if self.history_index >= self.history_list.jobs.len() {
+                    self.history_index = self.history_list.jobs.len().saturating_sub(1);
+                }
+            }
+            ViewData::Nodes(Ok(nodes)) => {
+                // Follow the selected node by name as nodes come and go
+                let previous = self
+                    .get_selected_node()
+                    .map(|n| (n.name.clone(), n.cluster.clone()));
+                self.node_list = nodes;
+                if let Some(idx) = previous.and_then(|(name, cluster)| {
+                    self.node_list
+                        .iter()
+                        .position(|n| n.name == name && n.cluster == cluster)
+                }) {
+                    self.node_index = idx;
+                } else if self.node_index >= self.node_list.len() {
+                    self.node_index = self.node_list.len().saturating_sub(1);
+                }
+            }
+            ViewData::Partitions(Ok(partitions)) => {
+                self.partition_list = partitions;
+                // Start on the partition being filtered on, if any
+                if let Some(idx) = self
+                    .current_partition
+                    .as_ref()
+                    .and_then(|current| self.partition_list.iter().position(|p| &p.name == current))
+                {
+                    self.partition_index = idx;
+                } else if self.partition_index >= self.partition_list.len() {
+                    self.partition_index = self.partition_list.len().saturating_sub(1);
+                }
+            }
+            ViewData::History(Err(e)) => {
+                self.error_message = Some(format!("Failed to fetch job history: {}", e));
+            }
+            ViewData::Nodes(Err(e)) => {
+                self.error_message = Some(format!("Failed to fetch nodes: {}", e));
+            }
+            ViewData::Partitions(Err(e)) => {
+                self.error_message = Some(format!("Failed to fetch partitions: {}", e));
+            }
+        }
+
+        self.is_loading = false;
+    }
+
+    /// Apply the outcome of a Slurm command started from a key. A popup
+    /// only opens if no other one was opened meanwhile.
+    fn apply_action(&mut self, done: ActionDone) {
+        match done {
+            ActionDone::Cancelled { target, result } => {
+                self.report_action(result, || format!("Failed to cancel job {}", target));
+            }
+            ActionDone::Action {
+                action,
+                job_id,
+                result,
+            } => {
+                self.report_action(result, || {
+                    format!("Failed to {} job {}", action.title().to_lowercase(), job_id)
+                });
+            }
+            ActionDone::Bulk {
+                action,
+                targets,
+                failures,
+            } => self.report_bulk(action, &targets, failures),
+            ActionDone::Signalled {
+                signal,
+                job_id,
+                result,
+            } => {
+                self.report_action(result, || {
+                    format!("Failed to send SIG{} to job {}", signal, job_id)
+                });
+            }
+            ActionDone::EditLoaded { job, fields } => {
+                self.popup_loading = false;
+                match fields {
+                    Ok(fields) if self.state == AppState::Normal => {
+                        self.edit_form = Some(JobEditForm::new(job, &fields));
+                        self.state = AppState::EditJobPopup;
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        self.error_message =
+                            Some(format!("Failed to read job {}: {}", job.job_id, e));
+                    }
+                }
+            }
+            ActionDone::Edited { job_id, result } => {
+                self.popup_loading = false;
+                match result {
+                    Ok(()) => {
+                        if self.edit_form.is_some() {
+                            self.dismiss_edit_popup();
+                        }
+                        self.restart_refresh();
+                    }
+                    // The form stays open with the reason, if it still is
+                    Err(e) => match self.edit_form.as_mut() {
+                        Some(form) => form.error = Some(e.trim().to_string()),
+                        None => {
+                            self.error_message =
+                                Some(format!("Failed to update job {}: {}", job_id, e.trim()));
+                        }
+                    },
+                }
+            }
+            ActionDone::ResubmitLoaded { job, fields } => {
+                self.popup_loading = false;
+                self.open_resubmit_form(job, fields);
+            }
+            ActionDone::ArrayLoaded {
+                job,
+                array_job_id,
+                tasks,
+                records,
+            } => {
+                self.popup_loading = false;
+                if self.state == AppState::Normal {
+                    self.open_array_summary(job, array_job_id, tasks, records);
+                }
+            }
+            ActionDone::ArrayResubmitted {
+                array_job_id,
+                cluster,
+                failed,
+                result,
+            } => {
+                self.popup_loading = false;
+                self.array_resubmitted(array_job_id, cluster, &failed, result);
+            }
+            ActionDone::Submitted {
+                cluster,
+                resubmitted,
+                result,
+            } => {
+                self.popup_loading = false;
+                self.job_submitted(cluster, resubmitted, result);
+            }
+            ActionDone::PendingLoaded(report) => {
+                self.popup_loading = false;
+                if self.state == AppState::Normal {
+                    self.pending_report = Some(*report);
+                    self.state = AppState::WhyPendingPopup;
+                }
+            }
+            ActionDone::ClustersLoaded(output) => {
+                self.popup_loading = false;
+                if self.state == AppState::Normal {
+                    self.open_cluster_options(output);
+                }
+            }
+        }
+    }
+
+    /// Refresh after a confirmed action, or say why it failed.
+    fn report_action(&mut self, result: Result<(), String>, what: impl FnOnce() -> String) {
+        match result {
+            Ok(()) => self.restart_refresh(),
+            Err(e) => self.error_message = Some(format!("{}: {}", what(), e)),
+        }
+    }
+
+    /// Refresh after a bulk action and list its failures, in the report
+    /// popup unless another one was opened meanwhile.
+    fn report_bulk(&mut self, action: JobAction, targets: &[Job], failures: Vec<(usize, String)>) {
+        self.restart_refresh();
+        if failures.is_empty() {
+            return;
+        }
+        if self.state != AppState::Normal {
+            self.error_message = Some(format!(
+                "Failed to {} {} of {} jobs",
+                action.title().to_lowercase(),
+                failures.len(),
+                targets.len()
+            ));
+            return;
+        }
+        self.bulk_report = Some(BulkReport {
+            action,
+            succeeded: targets.len() - failures.len(),
+            failures: failures
+                .into_iter()
+                .map(|(i, e)| {
+                    let id = targets.get(i).map_or("?", |job| job.job_id.as_str());
+                    (id.to_string(), e)
+                })
+                .collect(),
+        });
+        self.state = AppState::BulkReportPopup;
+    }
+
+    pub async fn refresh_history(&mut self) -> Result<()> {
+        self.refresh_tab(View::History).await;
+        self.refresh_logs().await;
+        Ok(())
+    }
+
+    pub async fn refresh_nodes(&mut self) -> Result<()> {
+        self.refresh_tab(View::Nodes).await;
+        Ok(())
+    }
+
+    pub async fn refresh_partitions(&mut self) -> Result<()> {
+        self.refresh_tab(View::Partitions).await;
+        Ok(())
+    }
+
+    async fn refresh_tab(&mut self, view: View) {
+        self.is_loading = true;
+        let fetched = self.view_fetch(view).run().await;
+        self.apply_view(fetched);
+    }
+
+    pub fn get_selected_partition(&self) -> Option<&Partition> {
+        self.partition_list.get(self.partition_index)
+    }
+
+    /// Filter on the highlighted partition and go back to the Jobs tab.
+    /// Choosing the partition already filtered on clears the filter.
+    pub fn confirm_partition(&mut self) {
+        let Some(partition) = self.get_selected_partition() else {
+            return;
+        };
+        self.current_partition = if self.current_partition.as_ref() == Some(&partition.name) {
+            None
+        } else {
+            Some(partition.name.clone())
+        };
+        self.view = View::Jobs;
+        self.restart_refresh();
+    }
+
+    pub fn get_selected_node(&self) -> Option<&Node> {
+        self.node_list.get(self.node_index)
+    }
+
+    /// Running jobs from the Jobs tab that have `node` in their node list.
+    pub fn jobs_on_node(&self, node: &Node) -> Vec<&Job> {
+        self.job_list
+            .jobs
+            .iter()
+            .filter(|job| job.is_running() && job.cluster == node.cluster)
+            .filter(|job| {
+                job.node_list
+                    .as_deref()
+                    .is_some_and(|list| SlurmParser::expand_hostlist(list).contains(&node.name))
+            })
+            .collect()
+    }
+
+    /// True when the Job Logs panel belongs to a different job than the
+    /// one now selected.
+    pub fn logs_stale(&self) -> bool {
+        self.get_selected_job().map(|job| job.job_id.as_str())
+            != self.job_logs.as_ref().map(|logs| logs.job_id.as_str())
+    }
+
+    /// Reload the log tail of the selected job.
+    pub async fn refresh_logs(&mut self) {
+        self.job_logs = match self.get_selected_job().cloned() {
+            Some(job) => Some(JobLogs {
+                content: load_job_logs(self.executor.as_ref(), &job).await,
+                job_id: job.job_id,
+            }),
+            None => None,
+        };
+    }
+
+    /// Reload the log tail of the selected job in the background, unless
+    /// it is already loading. The result arrives as
+    /// [`AppEvent::LogsFetched`].
+    pub fn start_logs_fetch(&mut self) {
+        let Some(job) = self.get_selected_job().cloned() else {
+            self.job_logs = None;
+            return;
+        };
+        if self.logs_loading.as_ref() == Some(&job.job_id) {
+            return;
+        }
+        self.logs_loading = Some(job.job_id.clone());
+        let executor = self.executor.clone();
+        self.spawn_fetch(async move {
+            AppEvent::LogsFetched(JobLogs {
+                content: load_job_logs(executor.as_ref(), &job).await,
+                job_id: job.job_id,
+            })
+        });
+    }
+
+    /// True when the usage shown is not that of the selected running job.
+    pub fn usage_stale(&self) -> bool {
+        let running = self.get_selected_job().filter(|job| job.is_running());
+        running.map(|job| job.job_id.as_str())
+            != self.job_usage.as_ref().map(|usage| usage.job_id.as_str())
+    }
+
+    /// Poll sstat for the selected job; only running jobs have live usage.
+    pub async fn refresh_usage(&mut self) {
+        self.job_usage = match self.get_selected_job().filter(|job| job.is_running()) {
+            Some(job) => Some(poll_usage(self.executor.as_ref(), job).await),
+            None => None,
+        };
+    }
+
+    /// Like [`Self::start_logs_fetch`] for [`Self::refresh_usage`]; the
+    /// result arrives as [`AppEvent::UsageFetched`].
+    pub fn start_usage_fetch(&mut self) {
+        let Some(job) = self
+            .get_selected_job()
+            .filter(|job| job.is_running())
+            .cloned()
+        else {
+            self.job_usage = None;
+            return;
+        };
+        if self.usage_loading.as_ref() == Some(&job.job_id) {
+            return;
+        }
+        self.usage_loading = Some(job.job_id.clone());
+        let executor = self.executor.clone();
+        self.spawn_fetch(async move {
+            AppEvent::UsageFetched(poll_usage(executor.as_ref(), &job).await)
+        });
+    }
+
+    /// Switch to `view` and load its data in the background.
+    pub fn switch_view(&mut self, view: View) {
+        self.view = view;
+        match view {
+            View::Jobs => self.start_logs_fetch(),
+            _ => self.start_view_refresh(),
+        }
+    }
+
+    /// Switch the Jobs tab between queue order and expected start. Going
+    /// back to queue order needs a fresh squeue listing.
+    pub fn toggle_job_sort(&mut self) {
+        self.job_sort = match self.job_sort {
+            JobSort::Queue => JobSort::ExpectedStart,
+            JobSort::ExpectedStart => JobSort::Queue,
+        };
+        self.restart_refresh();
+    }
+
+    pub fn should_refresh(&self) -> bool {
+        self.last_refresh.elapsed() >= self.refresh_interval
+    }
+
+    pub fn select_next_job(&mut self) {
+        if self.view == View::History {
+            if self.history_index + 1 < self.history_list.jobs.len() {
+                self.history_index += 1;
+            }
+            return;
+        }
+        if self.view == View::Nodes {
+            if self.node_index + 1 < self.node_list.len() {
+                self.node_index += 1;
+            }
+            return;
+        }
+        if self.view == View::Partitions {
+            if self.partition_index + 1 < self.partition_list.len() {
+                self.partition_index += 1;
+            }
+            return;
+        }
+        if !self.job_list.jobs.is_empty() && self.selected_job_index < self.job_list.jobs.len() - 1
+        {
+            self.selected_job_index += 1;
+            self.update_selected_job();
+        }
+    }
+
+    pub fn select_previous_job(&mut self) {
+        if self.view == View::History {
+            self.history_index = self.history_index.saturating_sub(1);
+            return;
+        }
+        if self.view == View::Nodes {
+            self.node_index = self.node_index.saturating_sub(1);
+            return;
+        }
+        if self.view == View::Partitions {
+            self.partition_index = self.partition_index.saturating_sub(1);
+            return;
+        }
+        if self.selected_job_index > 0 {
+            self.selected_job_index -= 1;
+            self.update_selected_job();
+        }
+    }
+
+    fn update_selected_job(&mut self) {
+        self.selected_job = self.job_list.jobs.get(self.selected_job_index).cloned();
+    }
+
+    /// Re-resolve the selection after the job list changes. Follows the
+    /// previously selected job by id if it still exists, otherwise clamps
+    /// the index so it stays in bounds.
+    pub fn sync_selection(&mut self, previous_id: Option<&str>) {
+        // Job ids repeat across clusters, so prefer the selected job's cluster
+        let previous_cluster = self.selected_job.as_ref().map(|j| j.cluster.clone());
+        let position = |id: &str| {
+            let jobs = &self.job_list.jobs;
+            jobs.iter()
+                .position(|j| j.job_id == id && Some(&j.cluster) == previous_cluster.as_ref())
+                .or_else(|| jobs.iter().position(|j| j.job_id == id))
+        };
+
+        if let Some(idx) = previous_id.and_then(position) {
             self.selected_job_index = idx;
         } else if self.selected_job_index >= self.job_list.jobs.len() {
             self.selected_job_index = self.job_list.jobs.len().saturating_sub(1);
@@ -157,8 +1737,31 @@ impl App {
         self.update_selected_job();
     }
 
+    /// Select the job submitted last, if it is listed by now.
+    fn select_submitted_job(&mut self) {
+        let Some((cluster, job_id)) = &self.submitted_job else {
+            return;
+        };
+        // A submitted array is listed by its tasks
+        let position = self.job_list.jobs.iter().position(|job| {
+            (&job.job_id == job_id || job.array_job_id.as_ref() == Some(job_id))
+                && (cluster.is_none() || &job.cluster == cluster)
+        });
+        if let Some(index) = position {
+            self.selected_job_index = index;
+            self.update_selected_job();
+            self.submitted_job = None;
+        }
+    }
+
+    /// The job highlighted in the current tab; none in the Nodes and
+    /// Partitions tabs.
     pub fn get_selected_job(&self) -> Option<&Job> {
-        self.selected_job.as_ref()
+        match self.view {
+            View::Jobs => self.selected_job.as_ref(),
+            View::History => self.history_list.jobs.get(self.history_index),
+            View::Nodes | View::Partitions => None,
+        }
     }
 
     pub fn running_jobs(&self) -> Vec<&Job> {
@@ -174,27 +1777,960 @@ impl App {
     }
 
     pub fn open_cancel_popup(&mut self) {
-        if self.selected_job.is_some() {
+        if self.view == View::Jobs && !self.marked_jobs.is_empty() {
+            self.open_bulk_popup(JobAction::Cancel);
+        } else if self.view == View::Jobs && self.selected_job.is_some() {
             self.cancel_target = self.selected_job.clone();
+            self.array_cancel = self
+                .selected_job
+                .as_ref()
+                .and_then(|job| ArrayCancel::new(job, &self.job_list.jobs));
             self.state = AppState::CancelJobPopup;
         }
     }
 
     pub fn dismiss_cancel_popup(&mut self) {
         self.cancel_target = None;
+        self.array_cancel = None;
         self.state = AppState::Normal;
     }
 
-    pub async fn confirm_cancel(&mut self) -> Result<()> {
+    /// Cancel the snapshotted job, or the part of its array chosen in the
+    /// popup. A range that does not check out leaves the popup open.
+    pub fn confirm_cancel(&mut self) {
+        let target = match &self.array_cancel {
+            Some(array) => match array.target() {
+                Ok((target, _)) => Some(target),
+                Err(_) => return,
+            },
+            None => None,
+        };
+        self.array_cancel = None;
         if let Some(job) = self.cancel_target.take() {
-            if let Err(e) = self.executor.scancel(&job.job_id).await {
-                self.error_message = Some(format!("Failed to cancel job {}: {}", job.job_id, e));
-            } else {
-                self.refresh_jobs().await?;
+            let target = target.unwrap_or_else(|| job.job_id.clone());
+            let executor = self.executor.clone();
+            self.spawn_action(async move {
+                let result = executor
+                    .scancel(&target, job.cluster.as_deref(), &CancelOptions::default())
+                    .await
+                    .map_err(|e| e.to_string());
+                ActionDone::Cancelled { target, result }
+            });
+        }
+        self.state = AppState::Normal;
+    }
+
+    /// Ask to confirm `action` on the selected job, if it applies to the
+    /// job's current state.
+    /// Like the cancel popup, only for jobs listed by squeue: sacct
+    /// records in the History view may be long gone from slurmctld.
+    pub fn open_action_popup(&mut self, action: JobAction) {
+        if self.view != View::Jobs {
+            return;
+        }
+        if !self.marked_jobs.is_empty() {
+            self.open_bulk_popup(action);
+            return;
+        }
+        let Some(job) = self.selected_job.as_ref() else {
+            return;
+        };
+        if action.applies_to(job) {
+            self.action_target = Some(job.clone());
+            self.state = AppState::JobActionPopup(action);
+        }
+    }
+
+    pub fn dismiss_action_popup(&mut self) {
+        self.action_target = None;
+        self.state = AppState::Normal;
+    }
+
+    pub fn confirm_action(&mut self) {
+        let AppState::JobActionPopup(action) = self.state else {
+            return;
+        };
+        if let Some(job) = self.action_target.take() {
+            let executor = self.executor.clone();
+            self.spawn_action(async move {
+                let result = action
+                    .run(executor.as_ref(), &job)
+                    .await
+                    .map_err(|e| e.to_string());
+                ActionDone::Action {
+                    action,
+                    job_id: job.job_id,
+                    result,
+                }
+            });
+        }
+        self.state = AppState::Normal;
+    }
+
+    /// Mark or unmark the selected job.
+    pub fn toggle_mark(&mut self) {
+        if self.view != View::Jobs {
+            return;
+        }
+        let Some(key) = self.selected_job.as_ref().map(Job::key) else {
+            return;
+        };
+        if !self.marked_jobs.remove(&key) {
+            self.marked_jobs.insert(key.clone());
+        }
+        self.mark_anchor = Some(key);
+    }
+
+    /// Mark every job from the last one marked with space to the selected
+    /// one.
+    pub fn mark_range(&mut self) {
+        if self.view != View::Jobs || self.selected_job.is_none() {
+            return;
+        }
+        let jobs = &self.job_list.jobs;
+        let anchor = self
+            .mark_anchor
+            .as_ref()
+            .and_then(|anchor| jobs.iter().position(|job| job.key() == *anchor))
+            .unwrap_or(self.selected_job_index);
+        let (from, to) = if anchor <= self.selected_job_index {
+            (anchor, self.selected_job_index)
+        } else {
+            (self.selected_job_index, anchor)
+        };
+        self.marked_jobs
+            .extend(jobs[from..=to.min(jobs.len() - 1)].iter().map(Job::key));
+    }
+
+    /// Mark every listed job, or clear the marks if all are marked.
+    pub fn toggle_mark_all(&mut self) {
+        if self.view != View::Jobs {
+            return;
+        }
+        let all: HashSet<JobKey> = self.job_list.jobs.iter().map(Job::key).collect();
+        if !all.is_empty() && all.is_subset(&self.marked_jobs) {
+            self.clear_marks();
+        } else {
+            self.marked_jobs.extend(all);
+        }
+    }
+
+    pub fn clear_marks(&mut self) {
+        self.marked_jobs.clear();
+        self.mark_anchor = None;
+    }
+
+    pub fn is_marked(&self, job: &Job) -> bool {
+        self.marked_jobs.contains(&job.key())
+    }
+
+    /// Ask to confirm `action` on the marked jobs it applies to.
+    fn open_bulk_popup(&mut self, action: JobAction) {
+        let marked: Vec<&Job> = self
+            .job_list
+            .jobs
+            .iter()
+            .filter(|job| self.is_marked(job))
+            .collect();
+        let targets: Vec<Job> = marked
+            .iter()
+            .filter(|job| action.applies_to(job))
+            .map(|job| (*job).clone())
+            .collect();
+        if targets.is_empty() {
+            self.error_message = Some(format!(
+                "Cannot {} any of the {} marked jobs",
+                action.title().to_lowercase(),
+                marked.len()
+            ));
+            return;
+        }
+        self.bulk_skipped = marked.len() - targets.len();
+        self.bulk_targets = targets;
+        self.state = AppState::BulkActionPopup(action);
+    }
+
+    pub fn dismiss_bulk_popup(&mut self) {
+        self.bulk_targets.clear();
+        self.bulk_skipped = 0;
+        self.bulk_report = None;
+        self.state = AppState::Normal;
+    }
+
+    /// Run the confirmed bulk action on all its jobs concurrently, in the
+    /// background. The marks are cleared; failures are listed in a report
+    /// popup.
+    pub fn confirm_bulk(&mut self) {
+        let AppState::BulkActionPopup(action) = self.state else {
+            return;
+        };
+        let targets = std::mem::take(&mut self.bulk_targets);
+        self.clear_marks();
+        self.dismiss_bulk_popup();
+        let executor = self.executor.clone();
+        self.spawn_action(async move {
+            let failures = run_bulk(executor, action, &targets).await;
+            ActionDone::Bulk {
+                action,
+                targets,
+                failures,
             }
+        });
+    }
+
+    /// Pick a signal for the selected job, if it is running.
+    pub fn open_signal_popup(&mut self) {
+        if self.view != View::Jobs {
+            return;
+        }
+        let Some(job) = self.selected_job.clone() else {
+            return;
+        };
+        if job.is_running() {
+            self.signal_picker = Some(SignalPicker::new(job, self.job_usage.as_ref()));
+            self.state = AppState::SignalPopup;
+        }
+    }
+
+    /// Ask to confirm the picked signal.
+    pub fn choose_signal(&mut self) {
+        if self.signal_picker.is_some() {
+            self.state = AppState::ConfirmSignalPopup;
         }
+    }
+
+    pub fn dismiss_signal_popup(&mut self) {
+        self.signal_picker = None;
+        self.state = AppState::Normal;
+    }
+
+    pub fn confirm_signal(&mut self) {
+        if let Some(picker) = self.signal_picker.take() {
+            let executor = self.executor.clone();
+            self.spawn_action(async move {
+                let job = &picker.job;
+                let result = executor
+                    .scancel(&job.job_id, job.cluster.as_deref(), &picker.options())
+                    .await
+                    .map_err(|e| e.to_string());
+                ActionDone::Signalled {
+                    signal: picker.signal().to_string(),
+                    job_id: job.job_id.clone(),
+                    result,
+                }
+            });
+        }
+        self.state = AppState::Normal;
+    }
+
+    /// Attach a shell to the selected running job: straight away when it
+    /// runs on one node, otherwise once a node is picked.
+    pub fn open_attach_popup(&mut self) {
+        if self.view != View::Jobs {
+            return;
+        }
+        let Some(job) = self.selected_job.clone() else {
+            return;
+        };
+        if !job.is_running() {
+            return;
+        }
+        let nodes = job
+            .node_list
+            .as_deref()
+            .map(SlurmParser::expand_hostlist)
+            .unwrap_or_default();
+        if nodes.len() > 1 {
+            self.node_picker = Some(NodePicker {
+                job,
+                nodes,
+                selected: 0,
+            });
+            self.state = AppState::AttachNodePopup;
+        } else {
+            // srun picks the job's only node itself
+            self.request_shell(&job, None);
+        }
+    }
+
+    pub fn attach_selected_node(&mut self) {
+        if let Some(picker) = self.node_picker.take() {
+            let node = picker.nodes[picker.selected].clone();
+            self.request_shell(&picker.job, Some(&node));
+        }
+        self.state = AppState::Normal;
+    }
+
+    pub fn dismiss_attach_popup(&mut self) {
+        self.node_picker = None;
+        self.state = AppState::Normal;
+    }
+
+    fn request_shell(&mut self, job: &Job, node: Option<&str>) {
+        match self
+            .executor
+            .shell_command(&job.job_id, job.cluster.as_deref(), node)
+        {
+            Ok(command) => {
+                self.shell_request = Some(ShellRequest {
+                    job_id: job.display_id(),
+                    command,
+                });
+            }
+            Err(e) => {
+                self.error_message = Some(format!(
+                    "Cannot attach to job {}: {}",
+                    job.display_id(),
+                    e.to_string().trim()
+                ));
+            }
+        }
+    }
+
+    /// Refresh once the shell from `request` has exited, since the job may
+    /// have moved on meanwhile, and report a shell that failed: its errors
+    /// went to the screen the TUI has since redrawn.
+    pub fn shell_exited(
+        &mut self,
+        request: &ShellRequest,
+        status: std::io::Result<std::process::ExitStatus>,
+    ) {
+        self.restart_refresh();
+        match status {
+            Ok(status) if status.success() => {}
+            Ok(status) => {
+                self.error_message = Some(format!(
+                    "The shell in job {} ended with {}",
+                    request.job_id, status
+                ));
+            }
+            Err(e) => {
+                self.error_message = Some(format!(
+                    "Failed to run {}: {}",
+                    request.command.first().map_or("", String::as_str),
+                    e
+                ));
+            }
+        }
+    }
+
+    /// Open the edit form for the selected job, if it is still queued,
+    /// once `scontrol show job` has answered.
+    pub fn open_edit_popup(&mut self) {
+        if self.view != View::Jobs || self.popup_loading || !self.executor.shows_job_details() {
+            return;
+        }
+        let Some(job) = self.selected_job.clone() else {
+            return;
+        };
+        if job.is_finished() {
+            return;
+        }
+
+        self.popup_loading = true;
+        let executor = self.executor.clone();
+        self.spawn_action(async move {
+            let fields = executor
+                .scontrol_show_job(&job.job_id, job.cluster.as_deref())
+                .await
+                .and_then(|output| SlurmParser::parse_scontrol_output(&output))
+                .map_err(|e| e.to_string());
+            ActionDone::EditLoaded { job, fields }
+        });
+    }
+
+    pub fn dismiss_edit_popup(&mut self) {
+        self.edit_form = None;
+        self.state = AppState::Normal;
+    }
+
+    /// Send the changed fields to `scontrol update`. The form stays open
+    /// with the reason when a field is invalid or the controller refuses.
+    pub fn apply_edit(&mut self) {
+        if self.popup_loading {
+            return;
+        }
+        let Some(form) = self.edit_form.as_mut() else {
+            return;
+        };
+        let changes = match form.changes() {
+            Ok(changes) => changes,
+            Err((index, why)) => {
+                form.selected = index;
+                form.error = Some(why);
+                return;
+            }
+        };
+        if changes.is_empty() {
+            self.dismiss_edit_popup();
+            return;
+        }
+
+        let job = form.job.clone();
+        self.popup_loading = true;
+        let executor = self.executor.clone();
+        self.spawn_action(async move {
+            let result = executor
+                .update_job(&job.job_id, job.cluster.as_deref(), &changes)
+                .await
+                .map_err(|e| e.to_string());
+            ActionDone::Edited {
+                job_id: job.job_id,
+                result,
+            }
+        });
+    }
+
+    /// Open the file picker for a batch script to submit. Not under
+    /// `--remote`: the picker lists this machine's files, which sbatch on
+    /// the cluster may not see.
+    pub fn open_submit_popup(&mut self) {
+        if self.view != View::Jobs {
+            return;
+        }
+        if self.executor.is_remote() {
+            self.error_message = Some(
+                "Cannot submit with --remote: scripts are picked from this machine, not the cluster"
+                    .to_string(),
+            );
+            return;
+        }
+        match FilePicker::new(self.submit_dir.clone()) {
+            Ok(picker) => {
+                self.file_picker = Some(picker);
+                self.state = AppState::SubmitFilePopup;
+            }
+            Err(e) => self.error_message = Some(e.to_string()),
+        }
+    }
+
+    /// Go into the selected directory, or read the selected script into
+    /// the submit form.
+    pub fn pick_file(&mut self) {
+        let Some(picker) = self.file_picker.as_mut() else {
+            return;
+        };
+        let script = match picker.enter() {
+            Ok(Some(script)) => script,
+            Ok(None) => return,
+            Err(e) => {
+                self.error_message = Some(e.to_string());
+                return;
+            }
+        };
+        match std::fs::read_to_string(&script) {
+            Ok(contents) => {
+                self.submit_form = Some(SubmitForm::new(script, &contents));
+                self.state = AppState::SubmitFormPopup;
+            }
+            Err(e) => {
+                self.error_message = Some(format!("Failed to read {}: {}", script.display(), e));
+            }
+        }
+    }
+
+    /// Leave the submit form for the file picker, where it was left, or
+    /// close it when resubmitting.
+    pub fn back_to_file_picker(&mut self) {
+        if self.file_picker.is_none() {
+            self.dismiss_submit_popup();
+            return;
+        }
+        self.submit_form = None;
+        self.state = AppState::SubmitFilePopup;
+    }
+
+    /// Open the submit form for the selected finished job, pre-filled with
+    /// what it asked for according to `scontrol show job`, once it has
+    /// answered.
+    pub fn open_resubmit_popup(&mut self) {
+        if self.popup_loading || !self.executor.shows_job_details() {
+            return;
+        }
+        let Some(job) = self.get_selected_job().cloned() else {
+            return;
+        };
+        if !job.is_finished() {
+            return;
+        }
+
+        // Listed jobs may lack the details, and History jobs come from sacct
+        self.popup_loading = true;
+        let executor = self.executor.clone();
+        self.spawn_action(async move {
+            let fields = executor
+                .scontrol_show_job(&job.job_id, job.cluster.as_deref())
+                .await
+                .and_then(|output| SlurmParser::parse_scontrol_output(&output))
+                .map_err(|e| e.to_string());
+            ActionDone::ResubmitLoaded { job, fields }
+        });
+    }
+
+    fn open_resubmit_form(
+        &mut self,
+        mut job: Job,
+        fields: Result<HashMap<String, String>, String>,
+    ) {
+        match fields {
+            Ok(fields) => SlurmParser::enhance_job_with_scontrol_data(&mut job, fields),
+            Err(e) if job.command.is_none() => {
+                self.error_message = Some(format!(
+                    "Cannot resubmit job {}: the controller no longer knows it ({})",
+                    job.job_id,
+                    e.trim()
+                ));
+                return;
+            }
+            Err(_) => {}
+        }
+        if self.state != AppState::Normal {
+            return;
+        }
+        let Some(command) = job.command.clone() else {
+            self.error_message = Some(format!(
+                "Cannot resubmit job {}: it has no batch script",
+                job.job_id
+            ));
+            return;
+        };
+
+        let (contents, modified) = self.read_local_script(&command).unzip();
+        self.file_picker = None;
+        self.submit_form = Some(SubmitForm::resubmit(
+            job,
+            PathBuf::from(command),
+            contents.as_deref(),
+            modified.flatten(),
+        ));
+        self.state = AppState::SubmitFormPopup;
+    }
+
+    /// A job's batch script and when it was last written, if it can be
+    /// read here: under `--remote` the path is the cluster's, not ours.
+    fn read_local_script(&self, path: &str) -> Option<(String, Option<DateTime<Utc>>)> {
+        if self.executor.is_remote() {
+            return None;
+        }
+        let contents = std::fs::read_to_string(path).ok()?;
+        let modified = std::fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .map(DateTime::<Utc>::from);
+        Some((contents, modified))
+    }
+
+    /// The job `job` was resubmitted as this session.
+    pub fn resubmitted_as(&self, job: &Job) -> Option<&JobKey> {
+        self.resubmissions.get(&job.key())
+    }
+
+    /// The job that `job` was resubmitted from this session. Tasks of a
+    /// resubmitted array that squeue lists under the array id, such as its
+    /// pending range, come from the original array.
+    pub fn resubmission_of(&self, job: &Job) -> Option<&JobKey> {
+        let array = job
+            .array_job_id
+            .as_ref()
+            .map(|array_id| (job.cluster.clone(), array_id.clone()));
+        [Some(job.key()), array]
+            .into_iter()
+            .flatten()
+            .find_map(|key| {
+                self.resubmissions
+                    .iter()
+                    .find(|(_, new)| **new == key)
+                    .map(|(original, _)| original)
+            })
+    }
+
+    /// Summarize the array the selected task belongs to: how many of its
+    /// tasks are in each state, counting finished tasks from sacct and
+    /// queued ones from the job list, and which tasks failed. Opens once
+    /// sacct and scontrol have answered.
+    pub fn open_array_summary_popup(&mut self) {
+        if self.popup_loading || !self.executor.shows_job_details() {
+            return;
+        }
+        let Some(job) = self.get_selected_job().cloned() else {
+            return;
+        };
+        let Some(array_job_id) = job.array_job_id.clone() else {
+            return;
+        };
+
+        self.popup_loading = true;
+        let executor = self.executor.clone();
+        self.spawn_action(async move {
+            let cluster = job.cluster.as_deref();
+            let tasks = executor
+                .sacct_job(&array_job_id, cluster)
+                .await
+                .and_then(|output| SlurmParser::parse_sacct_output(&output))
+                .map_err(|e| e.to_string());
+            let records = executor
+                .scontrol_show_job(&array_job_id, cluster)
+                .await
+                .and_then(|output| SlurmParser::parse_scontrol_multi(&output))
+                .map_err(|e| e.to_string());
+            ActionDone::ArrayLoaded {
+                job,
+                array_job_id,
+                tasks,
+                records,
+            }
+        });
+    }
+
+    fn open_array_summary(
+        &mut self,
+        mut job: Job,
+        array_job_id: String,
+        sacct: Result<Vec<Job>, String>,
+        scontrol: Result<HashMap<String, HashMap<String, String>>, String>,
+    ) {
+        let cluster = job.cluster.clone();
+        let mut errors = Vec::new();
+
+        let mut tasks: BTreeMap<u32, JobState> = BTreeMap::new();
+        match sacct {
+            Ok(records) => {
+                for record in records
+                    .iter()
+                    .filter(|record| record.array_job_id.as_ref() == Some(&array_job_id))
+                {
+                    for task in record.array_task_ids() {
+                        tasks.insert(task, record.state.clone());
+                    }
+                }
+            }
+            Err(e) => errors.push(format!("sacct: {}", e.trim())),
+        }
+        // The queue is more current than accounting for tasks still in it
+        let queued: Vec<&Job> = self
+            .job_list
+            .jobs
+            .iter()
+            .filter(|task| {
+                task.array_job_id.as_ref() == Some(&array_job_id) && task.cluster == cluster
+            })
+            .collect();
+        for task in &queued {
+            for id in task.array_task_ids() {
+                tasks.insert(id, task.state.clone());
+            }
+        }
+
+        // squeue shows the throttle on the pending range, e.g. 100_[4-9%5]
+        let mut throttle = queued
+            .iter()
+            .find_map(|task| parse_task_throttle(&task.job_id));
+        match scontrol {
+            Ok(mut records) => {
+                let fields = records
+                    .remove(&array_job_id)
+                    .or_else(|| records.into_values().next());
+                if let Some(fields) = fields {
+                    throttle = fields
+                        .get("ArrayTaskThrottle")
+                        .and_then(|throttle| throttle.parse().ok())
+                        .filter(|throttle| *throttle > 0)
+                        .or(throttle);
+                    SlurmParser::enhance_job_with_scontrol_data(&mut job, fields);
+                }
+            }
+            Err(e) => errors.push(format!("scontrol: {}", e.trim())),
+        }
+
+        let mut counts: Vec<(JobState, usize)> = Vec::new();
+        for state in tasks.values() {
+            match counts.iter_mut().find(|(counted, _)| counted == state) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((state.clone(), 1)),
+            }
+        }
+        counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        let failed = tasks
+            .iter()
+            .filter(|(_, state)| state.is_failure())
+            .map(|(task, _)| *task)
+            .collect();
+
+        self.array_summary = Some(ArraySummary {
+            array_job_id,
+            job,
+            counts,
+            failed,
+            throttle,
+            errors,
+        });
+        self.state = AppState::ArraySummaryPopup;
+    }
+
+    pub fn dismiss_array_summary(&mut self) {
+        self.array_summary = None;
         self.state = AppState::Normal;
-        Ok(())
+    }
+
+    /// Submit the array's script again for just its failed tasks, keeping
+    /// their indices and the original throttle. The popup stays open with
+    /// the reason when sbatch refuses; otherwise the new array is selected
+    /// once it is listed.
+    pub fn resubmit_failed_tasks(&mut self) {
+        if self.popup_loading {
+            return;
+        }
+        let Some(summary) = self
+            .array_summary
+            .as_mut()
+            .filter(|summary| !summary.failed.is_empty())
+        else {
+            return;
+        };
+        let Some(script) = summary.job.command.clone() else {
+            summary.errors.push(format!(
+                "Cannot resubmit array {}: its batch script is unknown",
+                summary.array_job_id
+            ));
+            return;
+        };
+        // The script as sbatch will read it, which may have changed since
+        let directives = self
+            .read_local_script(&script)
+            .map(|(contents, _)| parse_sbatch_directives(&contents));
+        let Some(summary) = self.array_summary.as_ref() else {
+            return;
+        };
+        let Some(options) = summary.resubmit_options(directives.as_deref()) else {
+            return;
+        };
+
+        let array_job_id = summary.array_job_id.clone();
+        let cluster = summary.job.cluster.clone();
+        let failed = summary.failed.clone();
+        self.popup_loading = true;
+        let executor = self.executor.clone();
+        self.spawn_action(async move {
+            let result = executor
+                .sbatch(&script, cluster.as_deref(), &options)
+                .await
+                .map_err(|e| e.to_string());
+            ActionDone::ArrayResubmitted {
+                array_job_id,
+                cluster,
+                failed,
+                result,
+            }
+        });
+    }
+
+    fn array_resubmitted(
+        &mut self,
+        array_job_id: String,
+        cluster: Option<String>,
+        failed: &[u32],
+        result: Result<String, String>,
+    ) {
+        let job_id = match result {
+            Ok(job_id) => job_id,
+            Err(e) => {
+                match self.array_summary.as_mut() {
+                    Some(summary) => summary.errors.push(format!("sbatch: {}", e.trim())),
+                    None => {
+                        self.error_message = Some(format!(
+                            "Failed to resubmit array {}: {}",
+                            array_job_id,
+                            e.trim()
+                        ));
+                    }
+                }
+                return;
+            }
+        };
+
+        let key = |id: String| (cluster.clone(), id);
+        for task in failed {
+            self.resubmissions.insert(
+                key(format!("{}_{}", array_job_id, task)),
+                key(format!("{}_{}", job_id, task)),
+            );
+        }
+        self.resubmissions
+            .insert(key(array_job_id), key(job_id.clone()));
+        self.submitted_job = Some(key(job_id));
+        if self.array_summary.is_some() {
+            self.dismiss_array_summary();
+        }
+        self.view = View::Jobs;
+        self.restart_refresh();
+    }
+
+    pub fn dismiss_submit_popup(&mut self) {
+        self.file_picker = None;
+        self.submit_form = None;
+        self.state = AppState::Normal;
+    }
+
+    /// Hand the script and the changed fields to sbatch. The form stays
+    /// open with the reason when a field is invalid or sbatch refuses;
+    /// otherwise the new job is selected once it is listed.
+    pub fn submit_job(&mut self) {
+        if self.popup_loading {
+            return;
+        }
+        let Some(form) = self.submit_form.as_mut() else {
+            return;
+        };
+        let overrides = match form.overrides() {
+            Ok(overrides) => overrides,
+            Err((index, why)) => {
+                form.selected = index;
+                form.error = Some(why);
+                return;
+            }
+        };
+
+        // sbatch's -M takes a single cluster
+        let cluster = match &form.resubmit {
+            Some(resubmit) => resubmit.job.cluster.clone(),
+            None => self
+                .current_clusters
+                .clone()
+                .filter(|clusters| clusters != "all" && !clusters.contains(',')),
+        };
+        let script = form.script.to_string_lossy().to_string();
+        let resubmitted = form.resubmit.as_ref().map(|resubmit| resubmit.job.key());
+        self.popup_loading = true;
+        let executor = self.executor.clone();
+        self.spawn_action(async move {
+            let result = executor
+                .sbatch(&script, cluster.as_deref(), &overrides)
+                .await
+                .map_err(|e| e.to_string());
+            ActionDone::Submitted {
+                cluster,
+                resubmitted,
+                result,
+            }
+        });
+    }
+
+    fn job_submitted(
+        &mut self,
+        cluster: Option<String>,
+        resubmitted: Option<JobKey>,
+        result: Result<String, String>,
+    ) {
+        match result {
+            Ok(job_id) => {
+                let new = (cluster, job_id);
+                if let Some(original) = resubmitted {
+                    self.resubmissions.insert(original, new.clone());
+                }
+                self.submitted_job = Some(new);
+                if self.submit_form.is_some() {
+                    self.dismiss_submit_popup();
+                }
+                // Resubmitted History jobs reappear in the Jobs tab
+                self.view = View::Jobs;
+                self.restart_refresh();
+            }
+            // The form stays open with the reason, if it still is
+            Err(e) => match self.submit_form.as_mut() {
+                Some(form) => form.error = Some(e.trim().to_string()),
+                None => self.error_message = Some(format!("Failed to submit: {}", e.trim())),
+            },
+        }
+    }
+
+    /// Explain why the selected job is still pending: its reason, the
+    /// priority factors from sprio, where it ranks in its partition, and
+    /// its owner's fairshare from sshare. Opens once both have answered.
+    pub fn open_why_pending_popup(&mut self) {
+        if self.popup_loading {
+            return;
+        }
+        let Some(job) = self.get_selected_job().cloned() else {
+            return;
+        };
+        if job.state != JobState::Pending {
+            return;
+        }
+
+        self.popup_loading = true;
+        let executor = self.executor.clone();
+        self.spawn_action(async move {
+            ActionDone::PendingLoaded(Box::new(load_pending_report(executor.as_ref(), job).await))
+        });
+    }
+
+    pub fn dismiss_why_pending_popup(&mut self) {
+        self.pending_report = None;
+        self.state = AppState::Normal;
+    }
+
+    /// Open the cluster selector, offering the local cluster, "all", and
+    /// whatever clusters accounting knows about, once sacctmgr has
+    /// answered.
+    pub fn open_cluster_popup(&mut self) {
+        if self.popup_loading {
+            return;
+        }
+        self.popup_loading = true;
+        let executor = self.executor.clone();
+        self.spawn_action(
+            async move { ActionDone::ClustersLoaded(executor.clusters().await.ok()) },
+        );
+    }
+
+    fn open_cluster_options(&mut self, clusters: Option<String>) {
+        let mut options = vec![None, Some("all".to_string())];
+        if let Some(output) = clusters {
+            options.extend(
+                SlurmParser::parse_clusters_output(&output)
+                    .into_iter()
+                    .map(Some),
+            );
+        }
+        // Keep clusters given on the command line or seen in jobs selectable
+        // even when sacctmgr is unavailable
+        let known = self
+            .current_clusters
+            .iter()
+            .flat_map(|c| c.split(','))
+            .map(str::to_string)
+            .chain(self.job_list.jobs.iter().filter_map(|j| j.cluster.clone()));
+        for cluster in known {
+            if !options.contains(&Some(cluster.clone())) {
+                options.push(Some(cluster));
+            }
+        }
+
+        self.cluster_popup_index = options
+            .iter()
+            .position(|c| *c == self.current_clusters)
+            .unwrap_or(0);
+        self.cluster_options = options;
+        self.state = AppState::ClusterSelectPopup;
+    }
+
+    pub fn select_next_cluster(&mut self) {
+        if self.cluster_popup_index + 1 < self.cluster_options.len() {
+            self.cluster_popup_index += 1;
+        }
+    }
+
+    pub fn select_previous_cluster(&mut self) {
+        self.cluster_popup_index = self.cluster_popup_index.saturating_sub(1);
+    }
+
+    /// Apply the highlighted cluster choice and reload.
+    pub fn confirm_cluster(&mut self) {
+        if let Some(choice) = self.cluster_options.get(self.cluster_popup_index) {
+            self.current_clusters = choice.clone();
+        }
+        self.state = AppState::Normal;
+        self.start_view_refresh();
     }
 
     pub fn send_event(&self, event: AppEvent) -> Result<()> {
@@ -205,6 +2741,11 @@ impl App {
     pub async fn receive_event(&mut self) -> Option<AppEvent> {
         self.event_receiver.recv().await
     }
+
+    /// The next pending event, without waiting.
+    pub fn try_receive_event(&mut self) -> Option<AppEvent> {
+        self.event_receiver.try_recv().ok()
+    }
 }
 
 impl Default for App {
@@ -212,3 +2753,393 @@ impl Default for App {
         Self::new()
     }
 }
+
+/// Run `action` on `targets`, a few at a time, and return the index and
+/// error of each failure in list order.
+async fn run_bulk(
+    executor: Arc<dyn SlurmExecutor>,
+    action: JobAction,
+    targets: &[Job],
+) -> Vec<(usize, String)> {
+    let permits = Arc::new(tokio::sync::Semaphore::new(BULK_CONCURRENCY));
+    let mut tasks = tokio::task::JoinSet::new();
+    for (i, job) in targets.iter().cloned().enumerate() {
+        let executor = executor.clone();
+        let permits = permits.clone();
+        tasks.spawn(async move {
+            let _permit = permits.acquire_owned().await;
+            (i, action.run(executor.as_ref(), &job).await)
+        });
+    }
+
+    let mut failures = Vec::new();
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok((_, Ok(()))) => {}
+            Ok((i, Err(e))) => failures.push((i, e.to_string().trim().to_string())),
+            Err(e) => failures.push((usize::MAX, e.to_string())),
+        }
+    }
+    failures.sort_by_key(|(i, _)| *i);
+    failures
+}
+
+/// sprio's and sshare's take on why `job` is still pending.
+async fn load_pending_report(executor: &dyn SlurmExecutor, job: Job) -> PendingReport {
+    let mut report = PendingReport {
+        job,
+        priority: None,
+        rank: None,
+        fairshare: None,
+        errors: Vec::new(),
+    };
+    let job = &report.job;
+    let cluster = job.cluster.as_deref();
+    // A job pending in several partitions is listed as "a,b"
+    let partition = job.partition.split(',').next().unwrap_or("");
+
+    match executor.sprio(Some(partition), cluster).await {
+        Ok(output) => {
+            let priorities = SlurmParser::parse_sprio_output(&output).unwrap_or_default();
+            let in_partition: Vec<&JobPriority> = priorities
+                .iter()
+                .filter(|p| p.partition == partition)
+                .collect();
+            if let Some(own) = in_partition.iter().find(|p| p.job_id == job.job_id) {
+                let ahead = in_partition
+                    .iter()
+                    .filter(|p| p.priority > own.priority)
+                    .count();
+                report.rank = Some((ahead + 1, in_partition.len()));
+                report.priority = Some((*own).clone());
+            }
+        }
+        Err(e) => report.errors.push(format!("sprio: {}", e)),
+    }
+
+    match executor.sshare(Some(&job.user), cluster).await {
+        Ok(output) => {
+            report.fairshare = SlurmParser::parse_sshare_output(&output)
+                .unwrap_or_default()
+                .into_iter()
+                .find(|share| share.user == job.user);
+        }
+        Err(e) => report.errors.push(format!("sshare: {}", e)),
+    }
+
+    report
+}
+
+async fn load_job_logs(executor: &dyn SlurmExecutor, job: &Job) -> String {
+    let log_paths = SlurmParser::get_job_log_paths(job);
+
+    // Try each potential log path
+    for path in &log_paths {
+        if let Ok(content) = executor.tail_file(path, LOG_TAIL_LINES).await {
+            if content.is_empty() {
+                return format!("Log file exists but is empty: {}", path);
+            }
+
+            return format!("Log file: {}\n{}\n{}", path, "-".repeat(50), content);
+        }
+    }
+
+    // No logs found
+    if log_paths.is_empty() {
+        "No log file paths available".to_string()
+    } else {
+        format!("No logs found. Checked paths:\n{}", log_paths.join("\n"))
+    }
+}
+
+async fn poll_usage(executor: &dyn SlurmExecutor, job: &Job) -> JobUsage {
+    // Without figures the steps stay empty, so a failing sstat is not
+    // retried on every tick
+    let steps = match executor.sstat(&job.job_id, job.cluster.as_deref()).await {
+        Ok(output) => SlurmParser::parse_sstat_output(&output).unwrap_or_default(),
+        Err(_) => Vec::new(),
+    };
+    JobUsage {
+        job_id: job.job_id.clone(),
+        steps,
+    }
+}
+
+/// Everything a History, Nodes or Partitions refresh needs, detached from
+/// [`App`] like [`JobFetch`].
+struct ViewFetch {
+    generation: u64,
+    view: View,
+    executor: Arc<dyn SlurmExecutor>,
+    user: Option<String>,
+    partition: Option<String>,
+    clusters: Option<String>,
+    history_window: Duration,
+    shown_message: Option<String>,
+}
+
+impl ViewFetch {
+    async fn run(self) -> ViewFetched {
+        let data = match self.view {
+            View::History => {
+                ViewData::History(self.fetch_history().await.map_err(|e| e.to_string()))
+            }
+            View::Nodes => ViewData::Nodes(self.fetch_nodes().await.map_err(|e| e.to_string())),
+            View::Partitions => {
+                ViewData::Partitions(self.fetch_partitions().await.map_err(|e| e.to_string()))
+            }
+            View::Jobs => unreachable!("jobs are refreshed by JobFetch"),
+        };
+        ViewFetched {
+            generation: self.generation,
+            data,
+            shown_message: self.shown_message,
+        }
+    }
+
+    async fn fetch_history(&self) -> Result<Vec<Job>> {
+        let sacct_output = self
+            .executor
+            .sacct(
+                self.user.as_deref(),
+                self.partition.as_deref(),
+                self.clusters.as_deref(),
+                self.history_window,
+            )
+            .await?;
+        let mut jobs = SlurmParser::parse_sacct_output(&sacct_output)?;
+        jobs.retain(|job| job.is_finished());
+        // Most recently finished first
+        jobs.sort_by_key(|job| std::cmp::Reverse(job.end_time));
+        Ok(jobs)
+    }
+
+    async fn fetch_nodes(&self) -> Result<Vec<Node>> {
+        let sinfo_output = self
+            .executor
+            .sinfo(self.partition.as_deref(), self.clusters.as_deref())
+            .await?;
+        let mut nodes = SlurmParser::parse_sinfo_output(&sinfo_output)?;
+
+        // One `scontrol show node` per cluster adds load and allocated memory
+        let mut clusters: Vec<Option<String>> = Vec::new();
+        for node in &nodes {
+            if !clusters.contains(&node.cluster) {
+                clusters.push(node.cluster.clone());
+            }
+        }
+
+        for cluster in clusters {
+            let Ok(scontrol_output) = self.executor.scontrol_show_nodes(cluster.as_deref()).await
+            else {
+                continue;
+            };
+            let Ok(mut details) = SlurmParser::parse_scontrol_nodes(&scontrol_output) else {
+                continue;
+            };
+            for node in nodes.iter_mut().filter(|node| node.cluster == cluster) {
+                if let Some(fields) = details.remove(&node.name) {
+                    SlurmParser::enhance_node_with_scontrol_data(node, fields);
+                }
+            }
+        }
+
+        Ok(nodes)
+    }
+
+    async fn fetch_partitions(&self) -> Result<Vec<Partition>> {
+        let sinfo_output = self
+            .executor
+            .sinfo_partitions(self.clusters.as_deref())
+            .await?;
+        let mut partitions = SlurmParser::parse_sinfo_partitions(&sinfo_output)?;
+
+        // Memory limits only come from scontrol, one call per cluster
+        let mut clusters: Vec<Option<String>> = Vec::new();
+        for partition in &partitions {
+            if !clusters.contains(&partition.cluster) {
+                clusters.push(partition.cluster.clone());
+            }
+        }
+
+        for cluster in clusters {
+            let Ok(scontrol_output) = self
+                .executor
+                .scontrol_show_partitions(cluster.as_deref())
+                .await
+            else {
+                continue;
+            };
+            let Ok(mut details) = SlurmParser::parse_scontrol_partitions(&scontrol_output) else {
+                continue;
+            };
+            for partition in partitions.iter_mut().filter(|p| p.cluster == cluster) {
+                if let Some(fields) = details.remove(&partition.name) {
+                    SlurmParser::enhance_partition_with_scontrol_data(partition, fields);
+                }
+            }
+        }
+
+        // The job list is narrowed to the current partition, so count from
+        // a squeue across all of them
+        if let Ok(squeue_output) = self
+            .executor
+            .squeue(self.user.as_deref(), None, self.clusters.as_deref())
+            .await
+            && let Ok(jobs) = SlurmParser::parse_squeue_output(&squeue_output)
+        {
+            for partition in &mut partitions {
+                // Pending jobs may list several partitions, e.g. "debug,gpu"
+                let in_partition = jobs.iter().filter(|job| {
+                    job.cluster == partition.cluster
+                        && job.partition.split(',').any(|p| p == partition.name)
+                });
+                for job in in_partition {
+                    match job.state {
+                        JobState::Running => partition.running_jobs += 1,
+                        JobState::Pending => partition.pending_jobs += 1,
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        Ok(partitions)
+    }
+}
+
+/// Everything a job refresh needs, detached from [`App`] so it can run on
+/// a background task.
+struct JobFetch {
+    generation: u64,
+    executor: Arc<dyn SlurmExecutor>,
+    user: Option<String>,
+    partition: Option<String>,
+    clusters: Option<String>,
+    output_format: Option<OutputFormat>,
+    shown_message: Option<String>,
+}
+
+impl JobFetch {
+    async fn run(mut self) -> JobsFetched {
+        let jobs = self.fetch_jobs().await.map_err(|e| e.to_string());
+        JobsFetched {
+            generation: self.generation,
+            jobs,
+            output_format: self.output_format,
+            shown_message: self.shown_message,
+        }
+    }
+
+    async fn fetch_jobs(&mut self) -> Result<Vec<Job>> {
+        let mut jobs = self.fetch_queue().await?;
+        if jobs.iter().any(|job| job.state == JobState::Pending) {
+            self.add_estimated_starts(&mut jobs).await;
+        }
+        Ok(jobs)
+    }
+
+    /// Overlay `squeue --start` estimates on pending jobs. Optional: the
+    /// scontrol or JSON StartTime already gives one on most clusters.
+    async fn add_estimated_starts(&self, jobs: &mut [Job]) {
+        let Ok(output) = self
+            .executor
+            .squeue_start(
+                self.user.as_deref(),
+                self.partition.as_deref(),
+                self.clusters.as_deref(),
+            )
+            .await
+        else {
+            return;
+        };
+        let Ok(mut starts) = SlurmParser::parse_squeue_start(&output) else {
+            return;
+        };
+        for job in jobs.iter_mut().filter(|job| job.state == JobState::Pending) {
+            if let Some(start) = starts.remove(&(job.cluster.clone(), job.job_id.clone())) {
+                job.estimated_start = Some(start);
+            }
+        }
+    }
+
+    async fn fetch_queue(&mut self) -> Result<Vec<Job>> {
+        if self.detect_output_format().await == OutputFormat::Json {
+            match self.fetch_jobs_json().await {
+                Ok(jobs) => return Ok(jobs),
+                // A new enough Slurm can still lack the JSON plugins; stick
+                // with the text parsers from here on.
+                Err(e) if json_unsupported(&e) => self.output_format = Some(OutputFormat::Text),
+                Err(e) => return Err(e),
+            }
+        }
+
+        let squeue_output = self
+            .executor
+            .squeue(
+                self.user.as_deref(),
+                self.partition.as_deref(),
+                self.clusters.as_deref(),
+            )
+            .await?;
+        let mut jobs = SlurmParser::parse_squeue_output(&squeue_output)?;
+
+        // One `scontrol show job` per cluster fills in details for every job
+        let mut clusters: Vec<Option<String>> = Vec::new();
+        for job in &jobs {
+            if !clusters.contains(&job.cluster) {
+                clusters.push(job.cluster.clone());
+            }
+        }
+
+        for cluster in clusters {
+            let Ok(scontrol_output) = self.executor.scontrol_show_jobs(cluster.as_deref()).await
+            else {
+                continue;
+            };
+            let Ok(mut details) = SlurmParser::parse_scontrol_multi(&scontrol_output) else {
+                continue;
+            };
+            for job in jobs.iter_mut().filter(|job| job.cluster == cluster) {
+                if let Some(fields) = details.remove(&job.job_id) {
+                    SlurmParser::enhance_job_with_scontrol_data(job, fields);
+                }
+            }
+        }
+
+        Ok(jobs)
+    }
+
+    async fn fetch_jobs_json(&self) -> Result<Vec<Job>> {
+        let squeue_output = self
+            .executor
+            .squeue_json(
+                self.user.as_deref(),
+                self.partition.as_deref(),
+                self.clusters.as_deref(),
+            )
+            .await?;
+        SlurmParser::parse_squeue_json(&squeue_output)
+    }
+
+    async fn detect_output_format(&mut self) -> OutputFormat {
+        if let Some(format) = self.output_format {
+            return format;
+        }
+
+        let supports_json = self
+            .executor
+            .version()
+            .await
+            .ok()
+            .and_then(|version| SlurmParser::parse_version(&version))
+            .is_some_and(SlurmParser::supports_json);
+        let format = if supports_json {
+            OutputFormat::Json
+        } else {
+            OutputFormat::Text
+        };
+        self.output_format = Some(format);
+        format
+    }
+}
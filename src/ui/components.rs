@@ -1,18 +1,21 @@
+use crate::ui::App;
+use crate::{
+    AppState, ArrayCancel, ArrayCancelScope, ArraySummary, BulkReport, COMMON_SIGNALS, FormField,
+    JobAction, JobEditForm, JobSort, PendingReport, SignalPicker, SubmitForm, View,
+    models::{
+        Job, JobEfficiency, JobField, JobState, JobUsage, Node, NodeState, Partition,
+        PartitionState, SubmitField, WASTEFUL_EFFICIENCY, format_task_ranges,
+    },
+    slurm::SlurmParser,
+};
+use chrono::{DateTime, Local, Utc};
 use ratatui::{
     Frame,
     layout::{Constraint, Direction, Layout, Rect},
     prelude::Alignment,
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Wrap},
-};
-use std::fs;
-
-use crate::slurm::SlurmParser;
-use crate::ui::App;
-use crate::{
-    AppState,
-    models::{Job, JobState},
+    widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Tabs, Wrap},
 };
 
 fn render_text_popup(popup_text: String, app: &App, frame: &mut Frame) {
@@ -39,6 +42,7 @@ pub fn render_app(frame: &mut Frame, app: &App) {
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Length(1), // Status bar
+            Constraint::Length(1), // Tabs
             Constraint::Min(0),    // Main content
             Constraint::Length(3), // Help/actions bar
         ])
@@ -46,6 +50,7 @@ pub fn render_app(frame: &mut Frame, app: &App) {
 
     // Render status bar
     render_status_bar(frame, app, chunks[0]);
+    render_tabs(frame, app, chunks[1]);
 
     // Main content area - split horizontally
     let main_chunks = Layout::default()
@@ -54,10 +59,7 @@ pub fn render_app(frame: &mut Frame, app: &App) {
             Constraint::Percentage(40), // Jobs list
             Constraint::Percentage(60), // Details/logs
         ])
-        .split(chunks[1]);
-
-    // Render jobs list
-    render_jobs_list(frame, app, main_chunks[0]);
+        .split(chunks[2]);
 
     // Right side - split vertically for details, logs, and summary
     let right_chunks = Layout::default()
@@ -69,42 +71,765 @@ pub fn render_app(frame: &mut Frame, app: &App) {
         ])
         .split(main_chunks[1]);
 
-    // Render details, logs, and summary
-    render_job_details(frame, app, right_chunks[0]);
-    render_job_logs(frame, app, right_chunks[1]);
-    render_quick_info(frame, app, right_chunks[2]);
+    match app.view {
+        View::Nodes => {
+            // Nodes list, then node details, its jobs, and a capacity summary
+            render_nodes_list(frame, app, main_chunks[0]);
+            render_node_details(frame, app, right_chunks[0]);
+            render_node_jobs(frame, app, right_chunks[1]);
+            render_node_summary(frame, app, right_chunks[2]);
+        }
+        View::Partitions => {
+            // Partition details take the space of job details and logs
+            render_partitions_list(frame, app, main_chunks[0]);
+            render_partition_details(frame, app, right_chunks[0].union(right_chunks[1]));
+            render_partition_summary(frame, app, right_chunks[2]);
+        }
+        View::Jobs | View::History => {
+            render_jobs_list(frame, app, main_chunks[0]);
+            render_job_details(frame, app, right_chunks[0]);
+            render_job_logs(frame, app, right_chunks[1]);
+            render_quick_info(frame, app, right_chunks[2]);
+        }
+    }
 
     // Render help bar
-    render_help_bar(app.state, frame, chunks[2]);
+    render_help_bar(app, frame, chunks[3]);
 
     match app.state {
         AppState::UserSearchPopup => render_text_popup("Search User:".to_string(), app, frame),
-        AppState::PartitionSearchPopup => {
-            render_text_popup("Search Partition:".to_string(), app, frame)
-        }
         AppState::CancelJobPopup => {
-            let Some(target) = &app.cancel_target else {
-                return;
+            if let Some(array) = &app.array_cancel {
+                render_array_cancel_popup(array, frame);
+            } else if let Some(target) = &app.cancel_target {
+                render_confirm_popup(format!("Cancel job id: {}? (y/n)", target.job_id), frame);
+            }
+        }
+        AppState::JobActionPopup(action) => {
+            if let Some(target) = &app.action_target {
+                let question = match action {
+                    JobAction::Requeue => format!(
+                        "Requeue job id: {}? It restarts from the beginning. (y/n)",
+                        target.job_id
+                    ),
+                    _ => format!("{} job id: {}? (y/n)", action.title(), target.job_id),
+                };
+                render_confirm_popup(question, frame);
+            }
+        }
+        AppState::ClusterSelectPopup => render_cluster_popup(app, frame),
+        AppState::WhyPendingPopup => render_why_pending_popup(app, frame),
+        AppState::ArraySummaryPopup => render_array_summary_popup(app, frame),
+        AppState::AttachNodePopup => render_node_popup(app, frame),
+        AppState::EditJobPopup => render_edit_popup(app, frame),
+        AppState::SubmitFilePopup => render_file_popup(app, frame),
+        AppState::SubmitFormPopup => render_submit_popup(app, frame),
+        AppState::BulkActionPopup(action) => {
+            render_confirm_popup(format_bulk_question(app, action), frame);
+        }
+        AppState::BulkReportPopup => render_bulk_report_popup(app, frame),
+        AppState::SignalPopup => render_signal_popup(app, frame),
+        AppState::ConfirmSignalPopup => {
+            if let Some(picker) = &app.signal_picker {
+                render_confirm_popup(
+                    format!(
+                        "Send SIG{} to the {} of job {}? (y/n)",
+                        picker.signal(),
+                        picker.options().scope.label(),
+                        picker.job.job_id
+                    ),
+                    frame,
+                );
+            }
+        }
+        _ => {}
+    }
+}
+
+fn render_confirm_popup(question: String, frame: &mut Frame) {
+    let popup_area = centered_rect(30, 7, frame.area());
+
+    frame.render_widget(Clear, popup_area);
+
+    let popup = Paragraph::new(question)
+        .style(Style::default().fg(Color::White))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Confirm")
+                .style(Style::default().fg(Color::Yellow)),
+        )
+        .wrap(Wrap { trim: true })
+        .alignment(Alignment::Center);
+
+    frame.render_widget(popup, popup_area);
+}
+
+fn render_array_cancel_popup(array: &ArrayCancel, frame: &mut Frame) {
+    let popup_area = centered_rect(40, 30, frame.area());
+    frame.render_widget(Clear, popup_area);
+
+    let popup = Paragraph::new(format_array_cancel(array))
+        .style(Style::default().fg(Color::White))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Cancel array {}", array.array_job_id))
+                .style(Style::default().fg(Color::Yellow)),
+        )
+        .wrap(Wrap { trim: false });
+
+    frame.render_widget(popup, popup_area);
+}
+
+/// The scope choices, the range being typed, and what confirming cancels.
+fn format_array_cancel(array: &ArrayCancel) -> Vec<Line<'static>> {
+    let choices = [
+        (
+            ArrayCancelScope::Task,
+            format!("this task ({})", array.task_id),
+        ),
+        (
+            ArrayCancelScope::Array,
+            format!("entire array {}", array.array_job_id),
+        ),
+        (ArrayCancelScope::Range, format!("tasks: {}", array.range)),
+    ];
+    let mut lines: Vec<Line> = choices
+        .into_iter()
+        .map(|(scope, label)| {
+            if scope == array.scope {
+                Line::from(Span::styled(
+                    format!("> {}", label),
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ))
+            } else {
+                Line::from(format!("  {}", label))
+            }
+        })
+        .collect();
+    lines.push(Line::from(""));
+    lines.push(match array.target() {
+        Ok((target, 1)) => Line::from(format!("Cancel 1 task ({})? (y/n)", target)),
+        Ok((target, count)) => Line::from(format!("Cancel {} tasks ({})? (y/n)", count, target)),
+        Err(e) => Line::from(Span::styled(e, Style::default().fg(Color::Red))),
+    });
+    lines
+}
+
+fn render_cluster_popup(app: &App, frame: &mut Frame) {
+    let popup_area = centered_rect(30, 40, frame.area());
+    frame.render_widget(Clear, popup_area);
+
+    let items: Vec<ListItem> = app
+        .cluster_options
+        .iter()
+        .enumerate()
+        .map(|(i, cluster)| {
+            let label = cluster.as_deref().unwrap_or("(local)");
+            let style = if i == app.cluster_popup_index {
+                Style::default().bg(Color::Blue).fg(Color::White)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            ListItem::new(label.to_string()).style(style)
+        })
+        .collect();
+
+    let popup = List::new(items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Select Cluster")
+            .style(Style::default().fg(Color::Yellow)),
+    );
+
+    frame.render_widget(popup, popup_area);
+}
+
+fn render_node_popup(app: &App, frame: &mut Frame) {
+    let Some(picker) = &app.node_picker else {
+        return;
+    };
+    let popup_area = centered_rect(30, 40, frame.area());
+    frame.render_widget(Clear, popup_area);
+
+    let items: Vec<ListItem> = picker
+        .nodes
+        .iter()
+        .enumerate()
+        .map(|(i, node)| {
+            let style = if i == picker.selected {
+                Style::default().bg(Color::Blue).fg(Color::White)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            ListItem::new(node.clone()).style(style)
+        })
+        .collect();
+
+    let popup = List::new(items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!("Shell in job {} on", picker.job.display_id()))
+            .style(Style::default().fg(Color::Yellow)),
+    );
+
+    frame.render_widget(popup, popup_area);
+}
+
+fn render_why_pending_popup(app: &App, frame: &mut Frame) {
+    let Some(report) = &app.pending_report else {
+        return;
+    };
+    let popup_area = centered_rect(60, 60, frame.area());
+    frame.render_widget(Clear, popup_area);
+
+    let popup = Paragraph::new(format_pending_report(report))
+        .style(Style::default().fg(Color::White))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(
+                    "Why pending? {} ({})",
+                    report.job.display_id(),
+                    report.job.name
+                ))
+                .style(Style::default().fg(Color::Yellow)),
+        )
+        .wrap(Wrap { trim: false });
+
+    frame.render_widget(popup, popup_area);
+}
+
+fn render_array_summary_popup(app: &App, frame: &mut Frame) {
+    let Some(summary) = &app.array_summary else {
+        return;
+    };
+    let popup_area = centered_rect(60, 50, frame.area());
+    frame.render_widget(Clear, popup_area);
+
+    let popup = Paragraph::new(format_array_summary(summary))
+        .style(Style::default().fg(Color::White))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(
+                    "Array {} ({}): {} tasks",
+                    summary.array_job_id,
+                    summary.job.name,
+                    summary.total()
+                ))
+                .style(Style::default().fg(Color::Yellow)),
+        )
+        .wrap(Wrap { trim: false });
+
+    frame.render_widget(popup, popup_area);
+}
+
+fn format_array_summary(summary: &ArraySummary) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+
+    let mut counts = vec![Span::raw("Tasks:")];
+    for (state, count) in &summary.counts {
+        counts.push(Span::styled(
+            format!("  {} {}", state, count),
+            Style::default().fg(state_color(state)),
+        ));
+    }
+    lines.push(Line::from(counts));
+    if let Some(throttle) = summary.throttle {
+        lines.push(Line::from(format!("At most {} running at once", throttle)));
+    }
+
+    lines.push(Line::from(""));
+    match summary.failed_array() {
+        Some(array) => {
+            lines.push(Line::styled(
+                format!(
+                    "Failed, timed out or out of memory ({}): {}",
+                    summary.failed.len(),
+                    format_task_ranges(&summary.failed)
+                ),
+                Style::default().fg(Color::Red),
+            ));
+            match &summary.job.command {
+                Some(script) => lines.push(Line::from(format!(
+                    "a: resubmit them with sbatch --array={} {}",
+                    array, script
+                ))),
+                None => lines.push(Line::styled(
+                    "Cannot resubmit them: the batch script is unknown",
+                    Style::default().fg(Color::DarkGray),
+                )),
+            }
+        }
+        None => lines.push(Line::styled(
+            "No task failed, timed out or ran out of memory",
+            Style::default().fg(Color::Green),
+        )),
+    }
+
+    for error in &summary.errors {
+        lines.push(Line::styled(
+            error.clone(),
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+    lines
+}
+
+/// E.g. "Cancel 40 jobs: 101, 102, 103, 104, 105 and 35 more? (y/n)".
+fn format_bulk_question(app: &App, action: JobAction) -> String {
+    const LISTED: usize = 5;
+    let targets = &app.bulk_targets;
+    let mut ids: Vec<String> = targets
+        .iter()
+        .take(LISTED)
+        .map(|job| job.display_id())
+        .collect();
+    let mut listed = ids.join(", ");
+    if targets.len() > LISTED {
+        listed = format!("{} and {} more", listed, targets.len() - LISTED);
+    } else if ids.len() > 1 {
+        let last = ids.pop().unwrap_or_default();
+        listed = format!("{} and {}", ids.join(", "), last);
+    }
+    let plural = if targets.len() == 1 { "" } else { "s" };
+    let mut question = format!(
+        "{} {} job{}: {}? (y/n)",
+        action.title(),
+        targets.len(),
+        plural,
+        listed
+    );
+    if app.bulk_skipped > 0 {
+        question.push_str(&format!(
+            " {} other marked job{} cannot be {}.",
+            app.bulk_skipped,
+            if app.bulk_skipped == 1 { "" } else { "s" },
+            past_tense(action)
+        ));
+    }
+    question
+}
+
+fn past_tense(action: JobAction) -> &'static str {
+    match action {
+        JobAction::Cancel => "cancelled",
+        JobAction::Hold => "held",
+        JobAction::Release => "released",
+        JobAction::Requeue => "requeued",
+    }
+}
+
+fn render_bulk_report_popup(app: &App, frame: &mut Frame) {
+    let Some(report) = &app.bulk_report else {
+        return;
+    };
+    let popup_area = centered_rect(60, 50, frame.area());
+    frame.render_widget(Clear, popup_area);
+
+    let popup = Paragraph::new(format_bulk_report(report))
+        .style(Style::default().fg(Color::White))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(
+                    "{}: {} of {} jobs {}",
+                    report.action.title(),
+                    report.succeeded,
+                    report.succeeded + report.failures.len(),
+                    past_tense(report.action)
+                ))
+                .style(Style::default().fg(Color::Yellow)),
+        )
+        .wrap(Wrap { trim: false });
+
+    frame.render_widget(popup, popup_area);
+}
+
+fn format_bulk_report(report: &BulkReport) -> Vec<Line<'static>> {
+    report
+        .failures
+        .iter()
+        .map(|(job_id, error)| {
+            Line::from(vec![
+                Span::styled(
+                    format!("{}: ", job_id),
+                    Style::default().fg(Color::LightRed),
+                ),
+                Span::raw(error.clone()),
+            ])
+        })
+        .collect()
+}
+
+fn render_signal_popup(app: &App, frame: &mut Frame) {
+    let Some(picker) = &app.signal_picker else {
+        return;
+    };
+    let popup_area = centered_rect(40, 50, frame.area());
+    frame.render_widget(Clear, popup_area);
+
+    let popup = Paragraph::new(format_signal_picker(picker))
+        .style(Style::default().fg(Color::White))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Signal job {}", picker.job.display_id()))
+                .style(Style::default().fg(Color::Yellow)),
+        );
+
+    frame.render_widget(popup, popup_area);
+}
+
+fn format_signal_picker(picker: &SignalPicker) -> Vec<Line<'static>> {
+    let mut lines: Vec<Line> = COMMON_SIGNALS
+        .iter()
+        .enumerate()
+        .map(|(i, (signal, meaning))| {
+            let style = if i == picker.signal_index {
+                Style::default().bg(Color::Blue).fg(Color::White)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            Line::from(vec![
+                Span::styled(format!("SIG{:<5}", signal), style),
+                Span::styled(format!(" {}", meaning), Style::default().fg(Color::Gray)),
+            ])
+        })
+        .collect();
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::raw("Send to: "),
+        Span::styled(
+            picker.options().scope.label(),
+            Style::default().fg(Color::Yellow),
+        ),
+        Span::styled(" (tab to change)", Style::default().fg(Color::Gray)),
+    ]));
+    lines
+}
+
+fn render_edit_popup(app: &App, frame: &mut Frame) {
+    let Some(form) = &app.edit_form else {
+        return;
+    };
+    let popup_area = centered_rect(50, 40, frame.area());
+    frame.render_widget(Clear, popup_area);
+
+    let popup = Paragraph::new(format_edit_form(form))
+        .style(Style::default().fg(Color::White))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(
+                    "Edit job {} ({})",
+                    form.job.display_id(),
+                    form.job.name
+                ))
+                .style(Style::default().fg(Color::Yellow)),
+        )
+        .wrap(Wrap { trim: false });
+
+    frame.render_widget(popup, popup_area);
+}
+
+fn format_edit_form(form: &JobEditForm) -> Vec<Line<'static>> {
+    format_form(&form.fields, form.selected, JobField::label, &form.error)
+}
+
+/// One line per field, then the error of the last attempt if any.
+fn format_form<F>(
+    fields: &[FormField<F>],
+    selected_index: usize,
+    label: fn(&F) -> &'static str,
+    error: &Option<String>,
+) -> Vec<Line<'static>> {
+    let width = fields
+        .iter()
+        .map(|f| label(&f.field).len())
+        .max()
+        .unwrap_or(0);
+    let mut lines: Vec<Line> = fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let selected = i == selected_index;
+            // Changed fields are marked so the diff is visible before applying
+            let marker = if field.is_changed() { "*" } else { " " };
+            let cursor = if selected { "_" } else { "" };
+            let label_style = if selected {
+                Style::default().bg(Color::Blue).fg(Color::White)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            let value_style = if field.is_changed() {
+                Style::default().fg(Color::Yellow)
+            } else {
+                Style::default().fg(Color::White)
             };
-            let popup_area = centered_rect(30, 7, frame.area());
+            Line::from(vec![
+                Span::styled(
+                    format!("{}{:<width$}", marker, label(&field.field)),
+                    label_style,
+                ),
+                Span::raw(" : "),
+                Span::styled(format!("{}{}", field.value, cursor), value_style),
+            ])
+        })
+        .collect();
+
+    if let Some(error) = error {
+        lines.push(Line::from(""));
+        lines.push(Line::styled(
+            error.clone(),
+            Style::default().fg(Color::LightRed),
+        ));
+    }
+    lines
+}
+
+fn render_file_popup(app: &App, frame: &mut Frame) {
+    let Some(picker) = &app.file_picker else {
+        return;
+    };
+    let popup_area = centered_rect(50, 50, frame.area());
+    frame.render_widget(Clear, popup_area);
+
+    let items: Vec<ListItem> = picker
+        .entries
+        .iter()
+        .enumerate()
+        .map(|(i, path)| {
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| "..".to_string());
+            let label = if path.is_dir() {
+                format!("{}/", name)
+            } else {
+                name
+            };
+            let style = if i == picker.selected {
+                Style::default().bg(Color::Blue).fg(Color::White)
+            } else if path.is_dir() {
+                Style::default().fg(Color::Cyan)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            ListItem::new(label).style(style)
+        })
+        .collect();
+
+    let title = match picker.dir.strip_prefix(&picker.root) {
+        Ok(relative) if !relative.as_os_str().is_empty() => {
+            format!("Submit: ./{}", relative.display())
+        }
+        _ => "Submit: pick a batch script (*.sh, *.sbatch)".to_string(),
+    };
+    let list = if items.is_empty() {
+        List::new(vec![ListItem::new("No batch scripts here")])
+    } else {
+        List::new(items)
+    };
+    let popup = list.block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .style(Style::default().fg(Color::Yellow)),
+    );
+
+    frame.render_widget(popup, popup_area);
+}
+
+fn render_submit_popup(app: &App, frame: &mut Frame) {
+    let Some(form) = &app.submit_form else {
+        return;
+    };
+    let popup_area = centered_rect(80, 60, frame.area());
+    frame.render_widget(Clear, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(popup_area);
 
-            frame.render_widget(Clear, popup_area);
+    let script_name = form
+        .script
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let fields = Paragraph::new(format_form(
+        &form.fields,
+        form.selected,
+        SubmitField::label,
+        &form.error,
+    ))
+    .style(Style::default().fg(Color::White))
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!("Submit {}", script_name))
+            .style(Style::default().fg(Color::Yellow)),
+    )
+    .wrap(Wrap { trim: false });
+    frame.render_widget(fields, chunks[0]);
+
+    let preview = Paragraph::new(format_directives(form))
+        .style(Style::default().fg(Color::White))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("#SBATCH directives")
+                .style(Style::default().fg(Color::Yellow)),
+        )
+        .wrap(Wrap { trim: false });
+    frame.render_widget(preview, chunks[1]);
+}
+
+/// The script's directives by line, dimmed where the form overrides them.
+fn format_directives(form: &SubmitForm) -> Vec<Line<'static>> {
+    let mut lines: Vec<Line> = form
+        .warning
+        .iter()
+        .map(|warning| Line::styled(warning.clone(), Style::default().fg(Color::Red)))
+        .collect();
+    if form.directives.is_empty() {
+        lines.push(Line::styled(
+            "No #SBATCH directives; sbatch defaults apply",
+            Style::default().fg(Color::Gray),
+        ));
+        return lines;
+    }
+    lines.extend(form.directives.iter().map(|directive| {
+        let text = match &directive.value {
+            Some(value) => format!("{:>3}  --{}={}", directive.line, directive.option, value),
+            None => format!("{:>3}  --{}", directive.line, directive.option),
+        };
+        if form.overrides_directive(directive) {
+            Line::styled(
+                format!("{} (overridden)", text),
+                Style::default()
+                    .fg(Color::DarkGray)
+                    .add_modifier(Modifier::CROSSED_OUT),
+            )
+        } else {
+            Line::from(text)
+        }
+    }));
+    lines
+}
+
+fn format_pending_report(report: &PendingReport) -> Vec<Line<'static>> {
+    let job = &report.job;
+    let mut lines = Vec::new();
+
+    let reason = job.reason.as_deref().unwrap_or("unknown");
+    lines.push(Line::from(format!("Reason: {}", reason)));
+    if let Some(hint) = reason_hint(reason) {
+        lines.push(Line::styled(
+            format!("  {}", hint),
+            Style::default().fg(Color::Gray),
+        ));
+    }
+    if let Some(estimated_start) = &job.estimated_start {
+        lines.push(Line::from(format_expected_start(estimated_start)));
+    }
+    if let Some((rank, pending)) = report.rank {
+        lines.push(Line::from(format!(
+            "Rank: {} of {} pending in {}",
+            rank, pending, job.partition
+        )));
+    }
+
+    lines.push(Line::from(""));
+    match &report.priority {
+        Some(priority) => {
+            lines.push(Line::from(format!("Priority: {}", priority.priority)));
+            let largest = priority.factors().iter().map(|(_, v)| *v).max();
+            for (name, value) in priority.factors() {
+                let share = if priority.priority > 0 {
+                    value as f64 / priority.priority as f64 * 100.0
+                } else {
+                    0.0
+                };
+                let style = if value > 0 && Some(value) == largest {
+                    Style::default().add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default()
+                };
+                lines.push(Line::styled(
+                    format!("  {:<10} {:>8} {:>4.0}%", name, value, share),
+                    style,
+                ));
+            }
+            if let Some(tres) = &priority.tres {
+                lines.push(Line::from(format!("  {:<10} {}", "TRES", tres)));
+            }
+        }
+        None => lines.push(Line::styled(
+            "Priority: no factors from sprio",
+            Style::default().fg(Color::DarkGray),
+        )),
+    }
+
+    if let Some(share) = &report.fairshare {
+        lines.push(Line::from(""));
+        if let Some(factor) = share.fairshare {
+            // Below 0.5 the account has used more than its share lately
+            let color = if factor < 0.5 {
+                Color::LightRed
+            } else {
+                Color::Green
+            };
+            lines.push(Line::styled(
+                format!("Fairshare of {}: {:.3}", share.user, factor),
+                Style::default().fg(color),
+            ));
+        }
+        if let (Some(used), Some(owed)) = (share.effective_usage, share.norm_shares) {
+            lines.push(Line::from(format!(
+                "  Account {} used {:.1}% of recent usage for a {:.1}% share",
+                share.account,
+                used * 100.0,
+                owed * 100.0
+            )));
+        }
+    }
 
-            let popup = Paragraph::new(format!("Cancel job id: {}? (y/n)", target.job_id))
-                .style(Style::default().fg(Color::White))
-                .block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .title("Confirm")
-                        .style(Style::default().fg(Color::Yellow)),
-                )
-                .wrap(Wrap { trim: true })
-                .alignment(Alignment::Center);
+    for error in &report.errors {
+        lines.push(Line::styled(
+            error.clone(),
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+    lines
+}
 
-            frame.render_widget(popup, popup_area);
+/// What a pending reason means for the user, for the common ones.
+fn reason_hint(reason: &str) -> Option<&'static str> {
+    let hint = match reason {
+        "Priority" => "Jobs with a higher priority are ahead of it",
+        "Resources" => "It is next in line, waiting for resources to free up",
+        "Dependency" => "Waiting for the jobs it depends on",
+        "DependencyNeverSatisfied" => "A job it depends on failed, so it will never start",
+        "JobHeldUser" => "Held by its owner until released",
+        "JobHeldAdmin" => "Held by an administrator",
+        "BeginTime" => "Its --begin time has not come yet",
+        "ReqNodeNotAvail" => "Nodes it needs are down, drained or reserved",
+        "PartitionTimeLimit" => "Its time limit is longer than the partition allows",
+        "PartitionNodeLimit" => "It asks for more nodes than the partition allows",
+        "Reservation" => "Waiting for its reservation to start",
+        _ if reason.starts_with("QOS") || reason.starts_with("Assoc") => {
+            "A QOS or account limit is reached; it starts once usage drops"
         }
-        _ => {}
-    }
+        _ => return None,
+    };
+    Some(hint)
 }
 
 fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
@@ -118,18 +843,33 @@ fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
         status_text.push_str(&format!(" - Part: {}", part));
     }
 
+    if let Some(clusters) = &app.current_clusters {
+        status_text.push_str(&format!(" - Clusters: {}", clusters));
+    }
+
     status_text.push_str(&format!(" - Jobs: {}", app.job_list.jobs.len()));
 
     if app.is_loading {
         status_text.push_str(" - Loading...");
     }
 
+    if app.actions_in_flight > 0 {
+        status_text.push_str(" - Working...");
+    }
+
+    let executor_status = app.executor.status();
+    if let Some(note) = &executor_status {
+        status_text.push_str(&format!(" - {}", note));
+    }
+
     if let Some(error) = &app.error_message {
         status_text = format!("ERROR: {}", error);
     }
 
     let status = Paragraph::new(status_text).style(if app.error_message.is_some() {
         Style::default().fg(Color::Red)
+    } else if executor_status.is_some() {
+        Style::default().fg(Color::Yellow)
     } else {
         Style::default()
     });
@@ -137,43 +877,108 @@ fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_widget(status, area);
 }
 
+fn render_tabs(frame: &mut Frame, app: &App, area: Rect) {
+    let titles: Vec<String> = View::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, view)| format!("{} {}", i + 1, view.title()))
+        .collect();
+    let selected = View::ALL.iter().position(|v| *v == app.view).unwrap_or(0);
+
+    let tabs = Tabs::new(titles).select(selected).highlight_style(
+        Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD),
+    );
+
+    frame.render_widget(tabs, area);
+}
+
 fn render_jobs_list(frame: &mut Frame, app: &App, area: Rect) {
-    let jobs: Vec<ListItem> = app
-        .job_list
-        .jobs
+    let (jobs, selected_index, title) = match app.view {
+        View::Jobs => (&app.job_list.jobs, app.selected_job_index, {
+            let mut title = format!("Jobs ({} total", app.job_list.jobs.len());
+            if !app.marked_jobs.is_empty() {
+                title.push_str(&format!(", {} marked", app.marked_jobs.len()));
+            }
+            if app.job_sort == JobSort::ExpectedStart {
+                title.push_str(", by expected start");
+            }
+            title.push(')');
+            title
+        }),
+        View::History => (
+            &app.history_list.jobs,
+            app.history_index,
+            format!(
+                "History (last {}, {} total)",
+                format_window(app.history_window),
+                app.history_list.jobs.len()
+            ),
+        ),
+        View::Nodes | View::Partitions => return,
+    };
+
+    // Only spend a column on the cluster when jobs come from several
+    let show_cluster = jobs.iter().any(|job| job.cluster.is_some());
+    // and on marks while there are any
+    let show_marks = app.view == View::Jobs && !app.marked_jobs.is_empty();
+
+    let jobs: Vec<ListItem> = jobs
         .iter()
         .enumerate()
         .map(|(i, job)| {
-            let style = if i == app.selected_job_index {
+            let style = if i == selected_index {
                 Style::default().bg(Color::Blue).fg(Color::White)
             } else {
                 Style::default()
             };
 
-            let state_color = match job.state {
-                JobState::Running => Color::Green,
-                JobState::Pending => Color::Yellow,
-                JobState::Completed => Color::Cyan,
-                JobState::Failed => Color::Red,
-                JobState::Cancelled => Color::Magenta,
-                _ => Color::Gray,
-            };
+            let state_color = state_color(&job.state);
 
             let job_id = job.display_id();
             let job_name = truncate(&job.name, 15);
-            let time_used = job.time_used.as_deref().unwrap_or("--");
+            // squeue --json has no elapsed column, so derive it from the start time
+            let time_used = job
+                .time_used
+                .clone()
+                .or_else(|| job.duration().map(format_elapsed))
+                .unwrap_or_else(|| "--".to_string());
+
+            // Finished jobs that left most of their allocation unused
+            let name_style = if job
+                .efficiency
+                .as_ref()
+                .is_some_and(JobEfficiency::is_wasteful)
+            {
+                Style::default().fg(Color::LightRed)
+            } else {
+                Style::default()
+            };
 
-            ListItem::new(Line::from(vec![
+            let mut spans = Vec::new();
+            if show_marks {
+                let mark = if app.is_marked(job) { "● " } else { "  " };
+                spans.push(Span::styled(mark, Style::default().fg(Color::Magenta)));
+            }
+            if show_cluster {
+                let cluster = truncate(job.cluster.as_deref().unwrap_or("-"), 10);
+                spans.push(Span::styled(
+                    format!("{:<10} ", cluster),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            spans.extend([
                 Span::styled(format!("{:<12} ", job_id), Style::default()),
-                Span::styled(format!("{:<15} ", job_name), Style::default()),
+                Span::styled(format!("{:<15} ", job_name), name_style),
                 Span::styled(format!("{} ", job.state), Style::default().fg(state_color)),
-                Span::styled(time_used.to_string(), Style::default()),
-            ]))
-            .style(style)
+                Span::styled(time_used, Style::default()),
+            ]);
+
+            ListItem::new(Line::from(spans)).style(style)
         })
         .collect();
 
-    let title = format!("Jobs ({} total)", app.job_list.jobs.len());
     let jobs_list = List::new(jobs)
         .block(Block::default().title(title).borders(Borders::ALL))
         .highlight_style(Style::default().add_modifier(Modifier::BOLD));
@@ -183,9 +988,35 @@ fn render_jobs_list(frame: &mut Frame, app: &App, area: Rect) {
 
 fn render_job_details(frame: &mut Frame, app: &App, area: Rect) {
     let details = if let Some(job) = app.get_selected_job() {
-        Paragraph::new(format_job_details(job))
+        let mut lines: Vec<Line> = format_job_details(job)
+            .lines()
+            .map(|line| Line::from(line.to_string()))
+            .collect();
+        if let Some((_, original)) = app.resubmission_of(job) {
+            lines.push(Line::from(format!("Resubmission of: {}", original)));
+        }
+        if let Some((_, new)) = app.resubmitted_as(job) {
+            lines.push(Line::styled(
+                format!("Resubmitted as: {}", new),
+                Style::default().fg(Color::Cyan),
+            ));
+        }
+        if let Some(usage) = app.job_usage.as_ref().filter(|u| u.job_id == job.job_id) {
+            lines.extend(format_job_usage(job, usage));
+        }
+        if let Some(efficiency) = &job.efficiency {
+            lines.extend(format_job_efficiency(efficiency));
+        }
+        Paragraph::new(lines)
             .block(Block::default().title("Job Details").borders(Borders::ALL))
             .wrap(Wrap { trim: true })
+    } else if app.view == View::History {
+        Paragraph::new(format!(
+            "No finished jobs in the last {}",
+            format_window(app.history_window)
+        ))
+        .block(Block::default().title("Job Details").borders(Borders::ALL))
+        .wrap(Wrap { trim: true })
     } else if app.job_list.jobs.is_empty() {
         let lines = vec![
             Line::from(""),
@@ -216,10 +1047,12 @@ fn render_job_details(frame: &mut Frame, app: &App, area: Rect) {
 }
 
 fn render_job_logs(frame: &mut Frame, app: &App, area: Rect) {
-    let content = if let Some(job) = app.get_selected_job() {
-        read_job_logs(job)
+    let content = if app.get_selected_job().is_none() {
+        "Select a job to view logs"
+    } else if let Some(logs) = app.job_logs.as_ref().filter(|_| !app.logs_stale()) {
+        logs.content.as_str()
     } else {
-        "Select a job to view logs".to_string()
+        "Loading logs..."
     };
 
     let logs = Paragraph::new(content)
@@ -234,25 +1067,349 @@ fn render_quick_info(frame: &mut Frame, app: &App, area: Rect) {
     let pending_count = app.pending_jobs().len();
     let completed_count = app.completed_jobs().len();
 
-    let content = format!(
+    let mut content = format!(
         "Running: {} | Pending: {} | Completed: {}",
         running_count, pending_count, completed_count
     );
 
+    for (cluster, jobs) in jobs_by_cluster(&app.job_list.jobs) {
+        let running = jobs.iter().filter(|job| job.is_running()).count();
+        let pending = jobs
+            .iter()
+            .filter(|job| matches!(job.state, JobState::Pending))
+            .count();
+        content.push_str(&format!(
+            "\n{}: {} jobs (R {} | PD {})",
+            cluster,
+            jobs.len(),
+            running,
+            pending
+        ));
+    }
+
     let quick_info =
         Paragraph::new(content).block(Block::default().title("Summary").borders(Borders::ALL));
 
     frame.render_widget(quick_info, area);
 }
 
-fn render_help_bar(app_state: AppState, frame: &mut Frame, area: Rect) {
-    let help_text = match app_state {
+fn render_nodes_list(frame: &mut Frame, app: &App, area: Rect) {
+    let show_cluster = app.node_list.iter().any(|node| node.cluster.is_some());
+
+    let nodes: Vec<ListItem> = app
+        .node_list
+        .iter()
+        .enumerate()
+        .map(|(i, node)| {
+            let style = if i == app.node_index {
+                Style::default().bg(Color::Blue).fg(Color::White)
+            } else {
+                Style::default()
+            };
+
+            let mut spans = Vec::new();
+            if show_cluster {
+                let cluster = truncate(node.cluster.as_deref().unwrap_or("-"), 10);
+                spans.push(Span::styled(
+                    format!("{:<10} ", cluster),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            spans.extend([
+                Span::styled(
+                    format!("{:<12} ", truncate(&node.name, 12)),
+                    Style::default(),
+                ),
+                Span::styled(
+                    format!("{:<6} ", node.state.to_string()),
+                    Style::default().fg(node_state_color(&node.state)),
+                ),
+                Span::styled(
+                    format!("{:>7} ", format!("{}/{}", node.cpus_alloc, node.cpus_total)),
+                    Style::default(),
+                ),
+                Span::styled(
+                    node.partitions.join(","),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]);
+
+            ListItem::new(Line::from(spans)).style(style)
+        })
+        .collect();
+
+    let title = format!("Nodes ({} total)", app.node_list.len());
+    let nodes_list = List::new(nodes)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .highlight_style(Style::default().add_modifier(Modifier::BOLD));
+
+    frame.render_widget(nodes_list, area);
+}
+
+fn node_state_color(state: &NodeState) -> Color {
+    match state {
+        NodeState::Idle => Color::Green,
+        NodeState::Mixed => Color::Yellow,
+        NodeState::Allocated | NodeState::Completing => Color::Cyan,
+        NodeState::Down => Color::Red,
+        NodeState::Drained | NodeState::Draining => Color::Magenta,
+        NodeState::Reserved | NodeState::Maintenance => Color::Blue,
+        NodeState::Unknown(_) => Color::Gray,
+    }
+}
+
+fn render_node_details(frame: &mut Frame, app: &App, area: Rect) {
+    let content = match app.get_selected_node() {
+        Some(node) => format_node_details(node),
+        None if app.node_list.is_empty() => "No nodes found".to_string(),
+        None => "Select a node to view details".to_string(),
+    };
+
+    let details = Paragraph::new(content)
+        .block(Block::default().title("Node Details").borders(Borders::ALL))
+        .wrap(Wrap { trim: true });
+
+    frame.render_widget(details, area);
+}
+
+fn render_node_jobs(frame: &mut Frame, app: &App, area: Rect) {
+    let Some(node) = app.get_selected_node() else {
+        let empty = Paragraph::new("Select a node to view its jobs")
+            .block(Block::default().title("Jobs on Node").borders(Borders::ALL));
+        frame.render_widget(empty, area);
+        return;
+    };
+
+    let jobs = app.jobs_on_node(node);
+    let items: Vec<ListItem> = if jobs.is_empty() {
+        // The Jobs tab filters apply here too
+        vec![ListItem::new("No listed jobs running here")]
+    } else {
+        jobs.iter()
+            .map(|job| {
+                ListItem::new(Line::from(vec![
+                    Span::styled(format!("{:<12} ", job.display_id()), Style::default()),
+                    Span::styled(
+                        format!("{:<15} ", truncate(&job.name, 15)),
+                        Style::default(),
+                    ),
+                    Span::styled(
+                        format!("{:<10} ", truncate(&job.user, 10)),
+                        Style::default(),
+                    ),
+                    Span::styled(
+                        job.time_used.clone().unwrap_or_else(|| "--".to_string()),
+                        Style::default(),
+                    ),
+                ]))
+            })
+            .collect()
+    };
+
+    let title = format!("Jobs on {} ({})", node.name, jobs.len());
+    let list = List::new(items).block(Block::default().title(title).borders(Borders::ALL));
+
+    frame.render_widget(list, area);
+}
+
+fn render_node_summary(frame: &mut Frame, app: &App, area: Rect) {
+    let nodes = &app.node_list;
+    let count = |wanted: &[NodeState]| {
+        nodes
+            .iter()
+            .filter(|node| wanted.contains(&node.state))
+            .count()
+    };
+    let unavailable = nodes.iter().filter(|node| node.is_unavailable()).count();
+    let cpus_alloc: u32 = nodes.iter().map(|node| node.cpus_alloc).sum();
+    let cpus_total: u32 = nodes.iter().map(|node| node.cpus_total).sum();
+
+    let content = format!(
+        "Idle: {} | Mixed: {} | Allocated: {} | Down/Drain: {}\nCPUs: {}/{} allocated",
+        count(&[NodeState::Idle]),
+        count(&[NodeState::Mixed]),
+        count(&[NodeState::Allocated, NodeState::Completing]),
+        unavailable,
+        cpus_alloc,
+        cpus_total
+    );
+
+    let summary =
+        Paragraph::new(content).block(Block::default().title("Summary").borders(Borders::ALL));
+
+    frame.render_widget(summary, area);
+}
+
+fn render_partitions_list(frame: &mut Frame, app: &App, area: Rect) {
+    let show_cluster = app
+        .partition_list
+        .iter()
+        .any(|partition| partition.cluster.is_some());
+
+    let partitions: Vec<ListItem> = app
+        .partition_list
+        .iter()
+        .enumerate()
+        .map(|(i, partition)| {
+            let style = if i == app.partition_index {
+                Style::default().bg(Color::Blue).fg(Color::White)
+            } else {
+                Style::default()
+            };
+
+            let mut spans = Vec::new();
+            if show_cluster {
+                let cluster = truncate(partition.cluster.as_deref().unwrap_or("-"), 10);
+                spans.push(Span::styled(
+                    format!("{:<10} ", cluster),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            // The filtered-on partition is marked, the default one starred
+            let marker = if app.current_partition.as_ref() == Some(&partition.name) {
+                "> "
+            } else {
+                "  "
+            };
+            let name = format!(
+                "{}{}",
+                partition.name,
+                if partition.is_default { "*" } else { "" }
+            );
+            spans.extend([
+                Span::styled(marker, Style::default().fg(Color::Yellow)),
+                Span::styled(format!("{:<12} ", truncate(&name, 12)), Style::default()),
+                Span::styled(
+                    format!("{:<6} ", partition.state.to_string()),
+                    Style::default().fg(partition_state_color(&partition.state)),
+                ),
+                Span::styled(
+                    format!(
+                        "{:>9} ",
+                        format!("{}/{} idle", partition.nodes_idle, partition.nodes_total)
+                    ),
+                    Style::default(),
+                ),
+                Span::styled(
+                    format!("R {} PD {}", partition.running_jobs, partition.pending_jobs),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]);
+
+            ListItem::new(Line::from(spans)).style(style)
+        })
+        .collect();
+
+    let title = format!("Partitions ({} total)", app.partition_list.len());
+    let list = List::new(partitions)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .highlight_style(Style::default().add_modifier(Modifier::BOLD));
+
+    frame.render_widget(list, area);
+}
+
+fn partition_state_color(state: &PartitionState) -> Color {
+    match state {
+        PartitionState::Up => Color::Green,
+        PartitionState::Drain => Color::Magenta,
+        PartitionState::Down | PartitionState::Inactive => Color::Red,
+        PartitionState::Unknown(_) => Color::Gray,
+    }
+}
+
+fn render_partition_details(frame: &mut Frame, app: &App, area: Rect) {
+    let content = match app.get_selected_partition() {
+        Some(partition) => format_partition_details(partition),
+        None if app.partition_list.is_empty() => "No partitions found".to_string(),
+        None => "Select a partition to view details".to_string(),
+    };
+
+    let details = Paragraph::new(content)
+        .block(
+            Block::default()
+                .title("Partition Details")
+                .borders(Borders::ALL),
+        )
+        .wrap(Wrap { trim: true });
+
+    frame.render_widget(details, area);
+}
+
+fn render_partition_summary(frame: &mut Frame, app: &App, area: Rect) {
+    let content = format!(
+        "Filtering on: {}",
+        app.current_partition
+            .as_deref()
+            .unwrap_or("(all partitions)")
+    );
+
+    let summary =
+        Paragraph::new(content).block(Block::default().title("Summary").borders(Borders::ALL));
+
+    frame.render_widget(summary, area);
+}
+
+fn render_help_bar(app: &App, frame: &mut Frame, area: Rect) {
+    let help_text = match app.state {
+        AppState::Normal if app.view == View::Partitions => {
+            "q: quit | ↑↓: navigate | Enter: filter on partition (again to clear) | tab: switch view | r: refresh | m: cluster".to_string()
+        }
+        AppState::Normal if app.view == View::Jobs && !app.marked_jobs.is_empty() => {
+            format!(
+                "{} marked | c/h/l/R: cancel/hold/release/requeue marked | space: mark | v: mark range | *: mark all | esc: clear marks | ↑↓: navigate | q: quit",
+                app.marked_jobs.len()
+            )
+        }
         AppState::Normal => {
-            "q: quit | ↑↓: navigate | r: refresh | c: cancel job | p: search partition | u: search user"
+            format!(
+                "q: quit | ↑↓: navigate | tab: switch view | r: refresh | c: cancel job{}{} | space: mark | w: why pending | s: sort | p: partitions | u: search user | m: cluster",
+                job_action_help(
+                    app.get_selected_job(),
+                    app.view,
+                    app.executor.shows_job_details()
+                ),
+                if app.executor.is_remote() { "" } else { " | n: new job" }
+            )
+        }
+        AppState::CancelJobPopup if app.array_cancel.is_some() => {
+            "tab: task, array or range | type a range like 3-17,20 | y/Enter: confirm | esc: reject"
+                .to_string()
+        }
+        AppState::CancelJobPopup
+        | AppState::JobActionPopup(_)
+        | AppState::BulkActionPopup(_)
+        | AppState::ConfirmSignalPopup => {
+            "y: confirm | n: reject | esc: reject".to_string()
+        }
+        AppState::UserSearchPopup => "esc: close | Enter: submit".to_string(),
+        AppState::ClusterSelectPopup => "↑↓: navigate | Enter: select | esc: close".to_string(),
+        AppState::WhyPendingPopup => "esc: close".to_string(),
+        AppState::ArraySummaryPopup
+            if app
+                .array_summary
+                .as_ref()
+                .is_some_and(|summary| summary.failed_array().is_some()) =>
+        {
+            "a: resubmit failed tasks | esc: close".to_string()
+        }
+        AppState::ArraySummaryPopup => "esc: close".to_string(),
+        AppState::AttachNodePopup => {
+            "↑↓: node | Enter: open a shell there | esc: close".to_string()
+        }
+        AppState::BulkReportPopup => "esc: close".to_string(),
+        AppState::SignalPopup => {
+            "↑↓: signal | tab: send to | Enter: send | esc: close".to_string()
+        }
+        AppState::EditJobPopup => {
+            "↑↓/tab: field | type to edit | Enter: apply changes | esc: cancel".to_string()
+        }
+        AppState::SubmitFilePopup => {
+            "↑↓: navigate | Enter: open directory or pick script | esc: close".to_string()
+        }
+        AppState::SubmitFormPopup => {
+            "↑↓/tab: field | type to override | Enter: submit with sbatch | esc: back to files"
+                .to_string()
         }
-        AppState::CancelJobPopup => "y: confirm | n: reject | esc: reject",
-        AppState::PartitionSearchPopup => "esc: close | Enter: submit",
-        AppState::UserSearchPopup => "esc: close | Enter: submit",
     };
     let help = Paragraph::new(help_text)
         .block(Block::default().borders(Borders::ALL))
@@ -261,6 +1418,54 @@ fn render_help_bar(app_state: AppState, frame: &mut Frame, area: Rect) {
     frame.render_widget(help, area);
 }
 
+/// Help for the edit, resubmit, signal, shell, hold, release, requeue and
+/// array summary keys that apply to `job` now. Hold, release and requeue
+/// are only offered in `View::Jobs`; editing, resubmitting and the array
+/// summary only with `details` from `scontrol show job`.
+fn job_action_help(job: Option<&Job>, view: View, details: bool) -> String {
+    let Some(job) = job else {
+        return String::new();
+    };
+    let edit = if !details {
+        ""
+    } else if job.is_finished() {
+        " | a: resubmit"
+    } else {
+        " | e: edit"
+    };
+    let signal = if job.is_running() {
+        " | S: signal | t: shell"
+    } else {
+        ""
+    };
+    let actions: String = [
+        (JobAction::Hold, "h: hold"),
+        (JobAction::Release, "l: release"),
+        (JobAction::Requeue, "R: requeue"),
+    ]
+    .into_iter()
+    .filter(|(action, _)| view == View::Jobs && action.applies_to(job))
+    .map(|(_, help)| format!(" | {}", help))
+    .collect();
+    let array = if details && job.is_array_job() {
+        " | A: array"
+    } else {
+        ""
+    };
+    format!("{}{}{}{}", edit, signal, actions, array)
+}
+
+fn state_color(state: &JobState) -> Color {
+    match state {
+        JobState::Running => Color::Green,
+        JobState::Pending => Color::Yellow,
+        JobState::Completed => Color::Cyan,
+        JobState::Failed | JobState::OutOfMemory => Color::Red,
+        JobState::Cancelled => Color::Magenta,
+        _ => Color::Gray,
+    }
+}
+
 fn format_job_details(job: &Job) -> String {
     let mut details = Vec::new();
 
@@ -273,6 +1478,7 @@ fn format_job_details(job: &Job) -> String {
         JobState::Timeout => "Timeout",
         JobState::NodeFail => "Node Fail",
         JobState::Preempted => "Preempted",
+        JobState::OutOfMemory => "Out of Memory",
         JobState::Unknown(_) => "Unknown",
     };
 
@@ -282,6 +1488,10 @@ fn format_job_details(job: &Job) -> String {
     details.push(format!("State: {} ({})", job.state, state_description));
     details.push(format!("Partition: {}", job.partition));
 
+    if let Some(cluster) = &job.cluster {
+        details.push(format!("Cluster: {}", cluster));
+    }
+
     if let Some(nodes) = job.nodes {
         details.push(format!("Nodes: {}", nodes));
     }
@@ -290,18 +1500,28 @@ fn format_job_details(job: &Job) -> String {
         details.push(format!("Node List: {}", node_list));
     }
 
+    if let Some(cpus) = job.cpus {
+        details.push(format!("CPUs: {}", cpus));
+    }
+
+    if let Some(memory) = &job.memory {
+        details.push(format!("Memory: {} requested", memory));
+    }
+
     if let Some(submit_time) = &job.submit_time {
-        details.push(format!(
-            "Submitted: {}",
-            submit_time.format("%Y-%m-%d %H:%M:%S")
-        ));
+        details.push(format!("Submitted: {}", format_time(submit_time)));
     }
 
     if let Some(start_time) = &job.start_time {
-        details.push(format!(
-            "Started: {}",
-            start_time.format("%Y-%m-%d %H:%M:%S")
-        ));
+        details.push(format!("Started: {}", format_time(start_time)));
+    }
+
+    if let Some(estimated_start) = &job.estimated_start {
+        details.push(format_expected_start(estimated_start));
+    }
+
+    if let Some(end_time) = &job.end_time {
+        details.push(format!("Ended: {}", format_time(end_time)));
     }
 
     if let Some(duration) = job.duration() {
@@ -316,6 +1536,10 @@ fn format_job_details(job: &Job) -> String {
         details.push(format!("Work Dir: {}", working_dir));
     }
 
+    if let Some(command) = &job.command {
+        details.push(format!("Script: {}", command));
+    }
+
     if let Some(std_out) = &job.std_out {
         details.push(format!("Log File: {}", std_out));
     }
@@ -324,38 +1548,291 @@ fn format_job_details(job: &Job) -> String {
         details.push(format!("Reason: {}", reason));
     }
 
+    if job.is_finished()
+        && let Some(exit_code) = job.exit_code
+    {
+        details.push(format!("Exit Code: {}", exit_code));
+    }
+
     details.join("\n")
 }
 
-fn read_job_logs(job: &Job) -> String {
-    let log_paths = SlurmParser::get_job_log_paths(job);
+fn format_node_details(node: &Node) -> String {
+    let mut details = Vec::new();
+
+    details.push(format!("Node: {}", node.name));
+    details.push(format!("State: {}", node.state));
 
-    // Try each potential log path
-    for path in &log_paths {
-        if let Ok(content) = fs::read_to_string(path) {
-            if content.is_empty() {
-                return format!("Log file exists but is empty: {}", path);
-            }
+    if let Some(cluster) = &node.cluster {
+        details.push(format!("Cluster: {}", cluster));
+    }
+
+    if !node.partitions.is_empty() {
+        details.push(format!("Partitions: {}", node.partitions.join(", ")));
+    }
 
-            // Show last 20 lines (tail-like behavior)
-            let lines: Vec<&str> = content.lines().collect();
-            let start = lines.len().saturating_sub(20);
-            let tail_lines = &lines[start..];
+    details.push(format!(
+        "CPUs: {} allocated, {} idle, {} total",
+        node.cpus_alloc, node.cpus_idle, node.cpus_total
+    ));
 
-            return format!(
-                "Log file: {}\n{}\n{}",
-                path,
-                "-".repeat(50),
-                tail_lines.join("\n")
-            );
+    if let Some(cpu_load) = node.cpu_load {
+        details.push(format!("CPU Load: {:.2}", cpu_load));
+    }
+
+    if let Some(memory) = node.memory {
+        let mut line = format!("Memory: {} MB", memory);
+        if let Some(alloc) = node.memory_alloc {
+            line.push_str(&format!(", {} MB allocated", alloc));
+        }
+        if let Some(free) = node.memory_free {
+            line.push_str(&format!(", {} MB free", free));
+        }
+        details.push(line);
+    }
+
+    if let Some(gres) = &node.gres {
+        details.push(format!("GRES: {}", gres));
+    }
+
+    if !node.features.is_empty() {
+        details.push(format!("Features: {}", node.features.join(", ")));
+    }
+
+    if let Some(reason) = &node.reason {
+        details.push(format!("Reason: {}", reason));
+    }
+
+    details.join("\n")
+}
+
+fn format_partition_details(partition: &Partition) -> String {
+    let mut details = Vec::new();
+
+    let name = if partition.is_default {
+        format!("{} (default)", partition.name)
+    } else {
+        partition.name.clone()
+    };
+    details.push(format!("Partition: {}", name));
+    details.push(format!("State: {}", partition.state));
+
+    if let Some(cluster) = &partition.cluster {
+        details.push(format!("Cluster: {}", cluster));
+    }
+
+    if let Some(time_limit) = &partition.time_limit {
+        details.push(format!("Time Limit: {}", time_limit));
+    }
+
+    if let Some(default_time) = &partition.default_time {
+        details.push(format!("Default Time: {}", default_time));
+    }
+
+    if let Some(default_memory) = &partition.default_memory {
+        details.push(format!("Default Memory: {}", default_memory));
+    }
+
+    if let Some(max_memory) = &partition.max_memory {
+        details.push(format!("Max Memory: {}", max_memory));
+    }
+
+    details.push(format!(
+        "Nodes: {} allocated, {} idle, {} other, {} total",
+        partition.nodes_alloc, partition.nodes_idle, partition.nodes_other, partition.nodes_total
+    ));
+    details.push(format!(
+        "CPUs: {} allocated, {} idle, {} other, {} total",
+        partition.cpus_alloc, partition.cpus_idle, partition.cpus_other, partition.cpus_total
+    ));
+    details.push(format!(
+        "Jobs: {} running, {} pending",
+        partition.running_jobs, partition.pending_jobs
+    ));
+
+    details.join("\n")
+}
+
+/// sstat figures for a running job, with peak memory held against the
+/// request so a job about to run out stands out.
+fn format_job_usage(job: &Job, usage: &JobUsage) -> Vec<Line<'static>> {
+    if usage.steps.is_empty() {
+        return vec![Line::styled(
+            "Usage: not available (sstat)",
+            Style::default().fg(Color::DarkGray),
+        )];
+    }
+
+    let mut lines = vec![Line::from("Usage:")];
+    for step in &usage.steps {
+        let size = |bytes: Option<u64>| bytes.map(format_bytes).unwrap_or_else(|| "--".to_string());
+        lines.push(Line::from(format!(
+            "  {}: CPU {} | MaxRSS {} | MaxVM {} | Disk R {} W {}",
+            step.step_id,
+            step.ave_cpu.as_deref().unwrap_or("--"),
+            size(step.max_rss),
+            size(step.max_vm_size),
+            size(step.disk_read),
+            size(step.disk_write),
+        )));
+    }
+
+    let requested = job.memory.as_deref().and_then(SlurmParser::parse_size);
+    if let (Some(peak), Some(requested)) = (usage.peak_rss(), requested.filter(|r| *r > 0)) {
+        let percent = peak as f64 / requested as f64 * 100.0;
+        let color = if percent >= 90.0 {
+            Color::Red
+        } else if percent >= 75.0 {
+            Color::Yellow
+        } else {
+            Color::Green
+        };
+        lines.push(Line::styled(
+            format!(
+                "Peak memory: {} of {} requested ({:.0}%)",
+                format_bytes(peak),
+                format_bytes(requested),
+                percent
+            ),
+            Style::default().fg(color),
+        ));
+    }
+
+    lines
+}
+
+fn format_job_efficiency(efficiency: &JobEfficiency) -> Vec<Line<'static>> {
+    let style = |percent: f64| {
+        if percent < WASTEFUL_EFFICIENCY {
+            Style::default().fg(Color::LightRed)
+        } else {
+            Style::default().fg(Color::Green)
+        }
+    };
+
+    let mut lines = Vec::new();
+    if let Some(percent) = efficiency.cpu_efficiency {
+        lines.push(Line::styled(
+            format!(
+                "CPU efficiency: {:.1}% ({} of {} core-time)",
+                percent,
+                format_elapsed(chrono::Duration::seconds(efficiency.cpu_used_secs as i64)),
+                format_elapsed(chrono::Duration::seconds(
+                    efficiency.cpu_allocated_secs as i64
+                )),
+            ),
+            style(percent),
+        ));
+    }
+    match (
+        efficiency.memory_efficiency,
+        efficiency.memory_used,
+        efficiency.memory_requested,
+    ) {
+        (Some(percent), Some(used), Some(requested)) => lines.push(Line::styled(
+            format!(
+                "Memory efficiency: {:.1}% ({} of {} requested)",
+                percent,
+                format_bytes(used),
+                format_bytes(requested)
+            ),
+            style(percent),
+        )),
+        _ => lines.push(Line::styled(
+            "Memory efficiency: not available (no MaxRSS)",
+            Style::default().fg(Color::DarkGray),
+        )),
+    }
+    lines
+}
+
+/// Time until an expected start: "in 2d 3h", "in 1h 20m", "in 5m".
+/// `time` in this machine's time zone, as Slurm's text output shows it.
+fn format_time(time: &DateTime<Utc>) -> String {
+    time.with_timezone(&Local)
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string()
+}
+
+fn format_expected_start(estimated_start: &DateTime<Utc>) -> String {
+    format!(
+        "Expected start: {} ({})",
+        format_time(estimated_start),
+        format_countdown(*estimated_start - Utc::now())
+    )
+}
+
+fn format_countdown(until: chrono::Duration) -> String {
+    let minutes = until.num_minutes();
+    let (days, hours, minutes) = (minutes / 1440, minutes / 60 % 24, minutes % 60);
+    if until.num_seconds() < 60 {
+        "any moment now".to_string()
+    } else if days > 0 {
+        format!("in {}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("in {}h {}m", hours, minutes)
+    } else {
+        format!("in {}m", minutes)
+    }
+}
+
+/// Render a byte count the way Slurm does: "512K", "3.60G".
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [(&str, u64); 4] = [
+        ("T", 1 << 40),
+        ("G", 1 << 30),
+        ("M", 1 << 20),
+        ("K", 1 << 10),
+    ];
+    for (unit, size) in UNITS {
+        if bytes >= size {
+            return format!("{:.2}{}", bytes as f64 / size as f64, unit);
+        }
+    }
+    bytes.to_string()
+}
+
+/// Jobs grouped by cluster in first-seen order; empty unless jobs were
+/// listed with `-M`.
+fn jobs_by_cluster(jobs: &[Job]) -> Vec<(&str, Vec<&Job>)> {
+    let mut groups: Vec<(&str, Vec<&Job>)> = Vec::new();
+    for job in jobs {
+        let Some(cluster) = job.cluster.as_deref() else {
+            continue;
+        };
+        match groups.iter_mut().find(|(name, _)| *name == cluster) {
+            Some((_, group)) => group.push(job),
+            None => groups.push((cluster, vec![job])),
         }
     }
+    groups
+}
+
+/// Render an elapsed time the way squeue does: "5:23", "1:02:03", "2-01:02:03".
+fn format_elapsed(duration: chrono::Duration) -> String {
+    let total_seconds = duration.num_seconds().max(0);
+    let days = total_seconds / 86400;
+    let hours = (total_seconds % 86400) / 3600;
+    let minutes = (total_seconds % 3600) / 60;
+    let seconds = total_seconds % 60;
+    if days > 0 {
+        format!("{}-{:02}:{:02}:{:02}", days, hours, minutes, seconds)
+    } else if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
 
-    // No logs found
-    if log_paths.is_empty() {
-        "No log file paths available".to_string()
+/// Render a history window as "24h", "7d" or "90m".
+fn format_window(window: std::time::Duration) -> String {
+    let minutes = window.as_secs() / 60;
+    if minutes.is_multiple_of(24 * 60) {
+        format!("{}d", minutes / (24 * 60))
+    } else if minutes.is_multiple_of(60) {
+        format!("{}h", minutes / 60)
     } else {
-        format!("No logs found. Checked paths:\n{}", log_paths.join("\n"))
+        format!("{}m", minutes)
     }
 }
 
@@ -390,7 +1867,7 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
 
 #[cfg(test)]
 mod tests {
-    use super::truncate;
+    use super::{format_bytes, format_countdown, format_elapsed, truncate};
 
     #[test]
     fn truncate_handles_multibyte_names() {
@@ -408,4 +1885,30 @@ mod tests {
         assert_eq!(truncate("🚀🚀🚀🚀🚀🚀🚀🚀🚀🚀🚀", 10), "🚀🚀🚀🚀🚀🚀🚀...");
         assert_eq!(truncate("job_🎉", 10), "job_🎉");
     }
+
+    #[test]
+    fn format_bytes_uses_binary_units() {
+        assert_eq!(format_bytes(512), "512");
+        assert_eq!(format_bytes(1536), "1.50K");
+        assert_eq!(format_bytes(4 << 30), "4.00G");
+    }
+
+    #[test]
+    fn format_countdown_rounds_to_two_units() {
+        let seconds = chrono::Duration::seconds;
+        assert_eq!(format_countdown(seconds(-30)), "any moment now");
+        assert_eq!(format_countdown(seconds(5 * 60 + 10)), "in 5m");
+        assert_eq!(format_countdown(seconds(80 * 60)), "in 1h 20m");
+        assert_eq!(format_countdown(seconds(51 * 3600)), "in 2d 3h");
+    }
+
+    #[test]
+    fn format_elapsed_matches_squeue() {
+        assert_eq!(format_elapsed(chrono::Duration::seconds(323)), "5:23");
+        assert_eq!(format_elapsed(chrono::Duration::seconds(3723)), "1:02:03");
+        assert_eq!(
+            format_elapsed(chrono::Duration::seconds(2 * 86400 + 3723)),
+            "2-01:02:03"
+        );
+    }
 }
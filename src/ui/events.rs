@@ -1,19 +1,40 @@
-use crate::app::{App, AppState};
+use crate::app::{App, AppState, ArrayCancelScope, JobAction, View};
 use crate::render_app;
-use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+use ratatui::crossterm::{
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyModifiers,
+    },
+    execute,
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+};
 use ratatui::{Terminal, backend::CrosstermBackend};
 use std::{
     error::Error,
     io,
+    process::ExitStatus,
     time::{Duration, Instant},
 };
+use tokio::process::Command;
+#[cfg(unix)]
+use tokio::signal::unix::{SignalKind, signal};
 
 pub async fn handle_key_event(app: &mut App, key: KeyEvent) -> Result<Option<()>, Box<dyn Error>> {
     match app.state {
         AppState::Normal => event_normal_state(app, key).await,
         AppState::UserSearchPopup => event_user_search_popup(app, key).await,
         AppState::CancelJobPopup => event_cancel_popup(app, key).await,
-        AppState::PartitionSearchPopup => event_partition_search_popup(app, key).await,
+        AppState::ClusterSelectPopup => event_cluster_popup(app, key).await,
+        AppState::WhyPendingPopup => event_why_pending_popup(app, key).await,
+        AppState::JobActionPopup(_) => event_action_popup(app, key).await,
+        AppState::BulkActionPopup(_) => event_bulk_popup(app, key).await,
+        AppState::BulkReportPopup => event_bulk_report_popup(app, key).await,
+        AppState::EditJobPopup => event_edit_popup(app, key).await,
+        AppState::SignalPopup => event_signal_popup(app, key).await,
+        AppState::ConfirmSignalPopup => event_confirm_signal_popup(app, key).await,
+        AppState::SubmitFilePopup => event_file_popup(app, key).await,
+        AppState::SubmitFormPopup => event_submit_popup(app, key).await,
+        AppState::ArraySummaryPopup => event_array_summary_popup(app, key).await,
+        AppState::AttachNodePopup => event_attach_popup(app, key).await,
     }
 }
 
@@ -44,7 +65,7 @@ pub async fn handle_text_event(app: &mut App, key: KeyEvent) -> Option<Option<St
 pub async fn reset_popup_state_to_normal(app: &mut App) -> Result<(), Box<dyn Error>> {
     app.input.clear();
     app.state = AppState::Normal;
-    app.refresh_jobs().await?;
+    app.start_view_refresh();
     Ok(())
 }
 
@@ -56,7 +77,20 @@ async fn event_normal_state(app: &mut App, key: KeyEvent) -> Result<Option<()>,
             return Ok(Some(()));
         }
         (KeyCode::Char('r'), _) => {
-            app.refresh_jobs().await?;
+            // The Nodes tab also lists the jobs running on each node
+            if app.view == View::Nodes {
+                app.start_refresh();
+            }
+            app.start_view_refresh();
+        }
+        (KeyCode::Tab, _) => {
+            app.switch_view(app.view.next());
+        }
+        (KeyCode::Char(c @ '1'..='9'), _) => {
+            let index = c as usize - '1' as usize;
+            if let Some(view) = View::ALL.get(index) {
+                app.switch_view(*view);
+            }
         }
         (KeyCode::Up, _) | (KeyCode::Char('k'), _) => {
             app.select_previous_job();
@@ -68,11 +102,62 @@ async fn event_normal_state(app: &mut App, key: KeyEvent) -> Result<Option<()>,
             app.state = AppState::UserSearchPopup;
         }
         (KeyCode::Char('p'), _) => {
-            app.state = AppState::PartitionSearchPopup;
+            app.switch_view(View::Partitions);
+        }
+        (KeyCode::Enter, _) if app.view == View::Partitions => {
+            app.confirm_partition();
+        }
+        (KeyCode::Char('s'), _) if app.view == View::Jobs => {
+            app.toggle_job_sort();
+        }
+        (KeyCode::Char(' '), _) => {
+            app.toggle_mark();
+        }
+        (KeyCode::Char('v'), _) => {
+            app.mark_range();
+        }
+        (KeyCode::Char('*'), _) => {
+            app.toggle_mark_all();
+        }
+        (KeyCode::Esc, _) => {
+            app.clear_marks();
         }
         (KeyCode::Char('c'), _) => {
             app.open_cancel_popup();
         }
+        (KeyCode::Char('e'), _) => {
+            app.open_edit_popup();
+        }
+        (KeyCode::Char('n'), _) => {
+            app.open_submit_popup();
+        }
+        (KeyCode::Char('a'), _) => {
+            app.open_resubmit_popup();
+        }
+        (KeyCode::Char('A'), _) => {
+            app.open_array_summary_popup();
+        }
+        (KeyCode::Char('t'), _) => {
+            app.open_attach_popup();
+        }
+        (KeyCode::Char('S'), _) => {
+            app.open_signal_popup();
+        }
+        (KeyCode::Char('h'), _) => {
+            app.open_action_popup(JobAction::Hold);
+        }
+        (KeyCode::Char('l'), _) => {
+            app.open_action_popup(JobAction::Release);
+        }
+        (KeyCode::Char('R'), _) => {
+            app.open_action_popup(JobAction::Requeue);
+        }
+        (KeyCode::Char('w'), _) => {
+            app.open_why_pending_popup();
+        }
+        (KeyCode::Char('m'), _) => {
+            app.open_cluster_popup();
+        }
         _ => {}
     }
     Ok(None)
@@ -90,25 +175,203 @@ async fn event_user_search_popup(
     Ok(None)
 }
 
-async fn event_partition_search_popup(
+async fn event_cancel_popup(app: &mut App, key: KeyEvent) -> Result<Option<()>, Box<dyn Error>> {
+    if let Some(array) = app.array_cancel.as_mut() {
+        match key.code {
+            KeyCode::Tab | KeyCode::Right => {
+                array.next_scope();
+                return Ok(None);
+            }
+            KeyCode::BackTab | KeyCode::Left => {
+                array.previous_scope();
+                return Ok(None);
+            }
+            KeyCode::Enter => {
+                app.confirm_cancel();
+                return Ok(None);
+            }
+            KeyCode::Backspace if array.scope == ArrayCancelScope::Range => {
+                array.pop();
+                return Ok(None);
+            }
+            KeyCode::Char(c) if array.scope == ArrayCancelScope::Range && c != 'y' && c != 'n' => {
+                array.push(c);
+                return Ok(None);
+            }
+            _ => {}
+        }
+    }
+    match key.code {
+        KeyCode::Char('y') => {
+            app.confirm_cancel();
+        }
+        KeyCode::Char('n') | KeyCode::Esc => {
+            app.dismiss_cancel_popup();
+        }
+        _ => {}
+    }
+    Ok(None)
+}
+
+async fn event_action_popup(app: &mut App, key: KeyEvent) -> Result<Option<()>, Box<dyn Error>> {
+    match key.code {
+        KeyCode::Char('y') => {
+            app.confirm_action();
+        }
+        KeyCode::Char('n') | KeyCode::Esc => {
+            app.dismiss_action_popup();
+        }
+        _ => {}
+    }
+    Ok(None)
+}
+
+async fn event_edit_popup(app: &mut App, key: KeyEvent) -> Result<Option<()>, Box<dyn Error>> {
+    let Some(form) = app.edit_form.as_mut() else {
+        return Ok(None);
+    };
+    match key.code {
+        KeyCode::Enter => app.apply_edit(),
+        KeyCode::Esc => app.dismiss_edit_popup(),
+        KeyCode::Down | KeyCode::Tab => form.next_field(),
+        KeyCode::Up | KeyCode::BackTab => form.previous_field(),
+        KeyCode::Backspace => form.pop(),
+        KeyCode::Char(c) => form.push(c),
+        _ => {}
+    }
+    Ok(None)
+}
+
+async fn event_file_popup(app: &mut App, key: KeyEvent) -> Result<Option<()>, Box<dyn Error>> {
+    let Some(picker) = app.file_picker.as_mut() else {
+        return Ok(None);
+    };
+    match key.code {
+        KeyCode::Up | KeyCode::Char('k') => picker.previous(),
+        KeyCode::Down | KeyCode::Char('j') => picker.next(),
+        KeyCode::Enter => app.pick_file(),
+        KeyCode::Esc => app.dismiss_submit_popup(),
+        _ => {}
+    }
+    Ok(None)
+}
+
+async fn event_submit_popup(app: &mut App, key: KeyEvent) -> Result<Option<()>, Box<dyn Error>> {
+    let Some(form) = app.submit_form.as_mut() else {
+        return Ok(None);
+    };
+    match key.code {
+        KeyCode::Enter => app.submit_job(),
+        KeyCode::Esc => app.back_to_file_picker(),
+        KeyCode::Down | KeyCode::Tab => form.next_field(),
+        KeyCode::Up | KeyCode::BackTab => form.previous_field(),
+        KeyCode::Backspace => form.pop(),
+        KeyCode::Char(c) => form.push(c),
+        _ => {}
+    }
+    Ok(None)
+}
+
+async fn event_signal_popup(app: &mut App, key: KeyEvent) -> Result<Option<()>, Box<dyn Error>> {
+    let Some(picker) = app.signal_picker.as_mut() else {
+        return Ok(None);
+    };
+    match key.code {
+        KeyCode::Up | KeyCode::Char('k') => picker.previous_signal(),
+        KeyCode::Down | KeyCode::Char('j') => picker.next_signal(),
+        KeyCode::Tab | KeyCode::Right | KeyCode::Left => picker.next_scope(),
+        KeyCode::Enter => app.choose_signal(),
+        KeyCode::Esc => app.dismiss_signal_popup(),
+        _ => {}
+    }
+    Ok(None)
+}
+
+async fn event_confirm_signal_popup(
     app: &mut App,
     key: KeyEvent,
 ) -> Result<Option<()>, Box<dyn Error>> {
-    let partition_search = handle_text_event(app, key).await;
-    if let Some(partition) = partition_search {
-        app.current_partition = partition;
-        reset_popup_state_to_normal(app).await?;
+    match key.code {
+        KeyCode::Char('y') => {
+            app.confirm_signal();
+        }
+        KeyCode::Char('n') | KeyCode::Esc => {
+            app.dismiss_signal_popup();
+        }
+        _ => {}
     }
     Ok(None)
 }
 
-async fn event_cancel_popup(app: &mut App, key: KeyEvent) -> Result<Option<()>, Box<dyn Error>> {
+async fn event_bulk_popup(app: &mut App, key: KeyEvent) -> Result<Option<()>, Box<dyn Error>> {
     match key.code {
         KeyCode::Char('y') => {
-            app.confirm_cancel().await?;
+            app.confirm_bulk();
         }
         KeyCode::Char('n') | KeyCode::Esc => {
-            app.dismiss_cancel_popup();
+            app.dismiss_bulk_popup();
+        }
+        _ => {}
+    }
+    Ok(None)
+}
+
+async fn event_bulk_report_popup(
+    app: &mut App,
+    key: KeyEvent,
+) -> Result<Option<()>, Box<dyn Error>> {
+    if matches!(key.code, KeyCode::Esc | KeyCode::Enter) {
+        app.dismiss_bulk_popup();
+    }
+    Ok(None)
+}
+
+async fn event_cluster_popup(app: &mut App, key: KeyEvent) -> Result<Option<()>, Box<dyn Error>> {
+    match key.code {
+        KeyCode::Up | KeyCode::Char('k') => app.select_previous_cluster(),
+        KeyCode::Down | KeyCode::Char('j') => app.select_next_cluster(),
+        KeyCode::Enter => app.confirm_cluster(),
+        KeyCode::Esc => app.state = AppState::Normal,
+        _ => {}
+    }
+    Ok(None)
+}
+
+async fn event_why_pending_popup(
+    app: &mut App,
+    key: KeyEvent,
+) -> Result<Option<()>, Box<dyn Error>> {
+    if matches!(key.code, KeyCode::Esc | KeyCode::Char('w') | KeyCode::Enter) {
+        app.dismiss_why_pending_popup();
+    }
+    Ok(None)
+}
+
+async fn event_attach_popup(app: &mut App, key: KeyEvent) -> Result<Option<()>, Box<dyn Error>> {
+    let Some(picker) = app.node_picker.as_mut() else {
+        app.dismiss_attach_popup();
+        return Ok(None);
+    };
+    match key.code {
+        KeyCode::Up | KeyCode::Char('k') => picker.previous(),
+        KeyCode::Down | KeyCode::Char('j') => picker.next(),
+        KeyCode::Enter => app.attach_selected_node(),
+        KeyCode::Esc => app.dismiss_attach_popup(),
+        _ => {}
+    }
+    Ok(None)
+}
+
+async fn event_array_summary_popup(
+    app: &mut App,
+    key: KeyEvent,
+) -> Result<Option<()>, Box<dyn Error>> {
+    match key.code {
+        KeyCode::Char('a') => {
+            app.resubmit_failed_tasks();
+        }
+        KeyCode::Esc | KeyCode::Char('A') | KeyCode::Enter => {
+            app.dismiss_array_summary();
         }
         _ => {}
     }
@@ -135,8 +398,24 @@ pub async fn run_event_loop(
             return Ok(());
         }
 
+        if let Some(request) = app.shell_request.take() {
+            let status = run_on_terminal(terminal, &request.command).await?;
+            app.shell_exited(&request, status);
+        }
+
+        while let Some(event) = app.try_receive_event() {
+            app.handle_app_event(event);
+        }
+
         if app.should_refresh() {
-            app.refresh_jobs().await?;
+            app.start_refresh();
+        } else {
+            if app.logs_stale() {
+                app.start_logs_fetch();
+            }
+            if app.usage_stale() {
+                app.start_usage_fetch();
+            }
         }
 
         if last_tick.elapsed() >= tick_rate {
@@ -144,3 +423,48 @@ pub async fn run_event_loop(
         }
     }
 }
+
+/// Hand the terminal to `command` until it exits: leave raw mode and the
+/// alternate screen as `main` does on exit, then take them back.
+async fn run_on_terminal(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    command: &[String],
+) -> Result<io::Result<ExitStatus>, Box<dyn Error>> {
+    disable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture
+    )?;
+    terminal.show_cursor()?;
+
+    let status = run_shell(command).await;
+
+    enable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        EnterAlternateScreen,
+        EnableMouseCapture
+    )?;
+    terminal.clear()?;
+    Ok(status)
+}
+
+/// Run `command` to completion. The child shares our process group and the
+/// terminal is out of raw mode, so a Ctrl-C or Ctrl-\ typed before srun
+/// takes the tty is sent to lazyslurm as well as to the child. On Unix,
+/// lazyslurm listens for SIGINT and SIGQUIT so that they do not stop it,
+/// while the child keeps the default handlers. tokio never uninstalls a
+/// signal handler, so from the first attach on, an external `kill -INT` or
+/// `kill -QUIT` no longer stops lazyslurm either.
+pub async fn run_shell(command: &[String]) -> io::Result<ExitStatus> {
+    #[cfg(unix)]
+    let _signals = (
+        signal(SignalKind::interrupt())?,
+        signal(SignalKind::quit())?,
+    );
+    match command.split_first() {
+        Some((program, args)) => Command::new(program).args(args).status().await,
+        None => Err(io::Error::new(io::ErrorKind::InvalidInput, "no command")),
+    }
+}
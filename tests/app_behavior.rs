@@ -1,11 +1,17 @@
 use std::sync::Arc;
 
-use lazyslurm::slurm::SlurmFixture;
-use lazyslurm::ui::App;
+use chrono::{Local, Utc};
+use lazyslurm::models::{Job, JobState, NodeState};
+use lazyslurm::slurm::{CancelOptions, SignalScope, SlurmExecutor, SlurmFixture};
+use lazyslurm::ui::{
+    App, AppState, ArrayCancelScope, JobAction, OutputFormat, ShellRequest, SubmitForm, View,
+};
 
 fn fixture_app(name: &str) -> (App, Arc<SlurmFixture>) {
     let fixture = Arc::new(SlurmFixture::new(format!("tests/fixtures/{name}")));
-    let app = App::with_executor(fixture.clone());
+    let mut app = App::with_executor(fixture.clone());
+    // The fixture applies -u like squeue does; don't filter on $USER
+    app.current_user = None;
     (app, fixture)
 }
 
@@ -22,7 +28,8 @@ async fn cancel_applies_to_snapshotted_job_even_if_selection_moves() {
     app.select_next_job();
     app.refresh_jobs().await.unwrap();
 
-    app.confirm_cancel().await.unwrap();
+    app.confirm_cancel();
+    app.wait_for_fetches().await;
 
     assert_eq!(*fixture.cancelled.lock().unwrap(), vec![target_id]);
 }
@@ -34,7 +41,8 @@ async fn dismissing_cancel_popup_cancels_nothing() {
 
     app.open_cancel_popup();
     app.dismiss_cancel_popup();
-    app.confirm_cancel().await.unwrap();
+    app.confirm_cancel();
+    app.wait_for_fetches().await;
 
     assert!(fixture.cancelled.lock().unwrap().is_empty());
     assert!(app.cancel_target.is_none());
@@ -73,3 +81,1392 @@ async fn selection_clamps_when_job_list_shrinks() {
     assert_eq!(app.selected_job_index, 0);
     assert!(app.selected_job.is_none());
 }
+
+#[tokio::test]
+async fn history_tab_lists_finished_jobs_most_recent_first() {
+    let (mut app, _) = fixture_app("basic");
+    app.refresh_jobs().await.unwrap();
+
+    app.switch_view(View::History);
+
+    app.wait_for_fetches().await;
+
+    let ids: Vec<&str> = app
+        .history_list
+        .jobs
+        .iter()
+        .map(|j| j.job_id.as_str())
+        .collect();
+    // The still-running 12345 is left to the Jobs tab
+    assert_eq!(ids, vec!["12302", "12305", "12304", "12303", "12301"]);
+    assert_eq!(app.get_selected_job().unwrap().job_id, "12302");
+    assert_eq!(app.get_selected_job().unwrap().exit_code, Some(1));
+
+    app.select_next_job();
+    assert_eq!(app.get_selected_job().unwrap().state, JobState::OutOfMemory);
+
+    // The Jobs tab keeps its own selection
+    app.switch_view(View::Jobs);
+    app.wait_for_fetches().await;
+    assert_eq!(app.get_selected_job().unwrap().job_id, "12345");
+}
+
+#[tokio::test]
+async fn json_output_is_used_when_slurm_supports_it() {
+    let (mut app, _) = fixture_app("json_basic");
+    app.refresh_jobs().await.unwrap();
+
+    assert_eq!(app.output_format, Some(OutputFormat::Json));
+    assert!(app.error_message.is_none());
+    let job = app.selected_job.as_ref().unwrap();
+    assert_eq!(job.name, "train, eval (fold 1)");
+    assert_eq!(job.working_dir.as_deref(), Some("/home/alice/My Project"));
+}
+
+#[tokio::test]
+async fn json_output_falls_back_to_text_only_when_it_is_unusable() {
+    let (mut app, _) = fixture_app("json_fallback");
+
+    // The controller being down is reported and --json is kept
+    app.refresh_jobs().await.unwrap();
+    assert_eq!(app.output_format, Some(OutputFormat::Json));
+    assert_eq!(
+        app.error_message.as_deref(),
+        Some("Failed to fetch jobs: Unable to contact slurm controller (connect failure)")
+    );
+
+    // Output that does not parse switches to the text parsers for good
+    app.refresh_jobs().await.unwrap();
+    assert_eq!(app.output_format, Some(OutputFormat::Text));
+    assert!(app.error_message.is_none());
+    assert_eq!(app.job_list.jobs.len(), 3);
+}
+
+#[tokio::test]
+async fn text_output_is_used_without_a_json_capable_slurm() {
+    // The basic fixture has no version.txt, like a Slurm too old to report one
+    let (mut app, _) = fixture_app("basic");
+    app.refresh_jobs().await.unwrap();
+
+    assert_eq!(app.output_format, Some(OutputFormat::Text));
+    assert_eq!(app.job_list.jobs.len(), 3);
+}
+
+#[tokio::test]
+async fn jobs_from_several_clusters_keep_their_cluster() {
+    let (mut app, _) = fixture_app("multi_cluster");
+    app.current_clusters = Some("alpha,beta".to_string());
+    app.refresh_jobs().await.unwrap();
+
+    let clusters: Vec<_> = app
+        .job_list
+        .jobs
+        .iter()
+        .map(|job| (job.job_id.as_str(), job.cluster.as_deref()))
+        .collect();
+    assert_eq!(
+        clusters,
+        vec![
+            ("501", Some("alpha")),
+            ("502", Some("alpha")),
+            ("501", Some("beta")),
+        ]
+    );
+
+    // The same job id on another cluster is a different job
+    app.select_next_job();
+    app.select_next_job();
+    app.refresh_jobs().await.unwrap();
+    let selected = app.selected_job.as_ref().unwrap();
+    assert_eq!(selected.cluster.as_deref(), Some("beta"));
+    assert_eq!(selected.name, "preprocess");
+}
+
+#[tokio::test]
+async fn cluster_popup_offers_sacctmgr_clusters() {
+    let (mut app, _) = fixture_app("multi_cluster");
+    app.refresh_jobs().await.unwrap();
+
+    app.open_cluster_popup();
+    app.wait_for_fetches().await;
+    assert_eq!(
+        app.cluster_options,
+        vec![
+            None,
+            Some("all".to_string()),
+            Some("alpha".to_string()),
+            Some("beta".to_string()),
+        ]
+    );
+    assert_eq!(app.cluster_popup_index, 0);
+
+    app.select_next_cluster();
+    app.select_next_cluster();
+    app.select_next_cluster();
+    app.confirm_cluster();
+    app.wait_for_fetches().await;
+
+    assert_eq!(app.current_clusters.as_deref(), Some("beta"));
+    assert_eq!(app.state, AppState::Normal);
+}
+
+#[tokio::test]
+async fn every_listed_job_gets_scontrol_details() {
+    let (mut app, _) = fixture_app("array_jobs");
+    app.refresh_jobs().await.unwrap();
+
+    let work_dirs: Vec<_> = app
+        .job_list
+        .jobs
+        .iter()
+        .map(|job| (job.job_id.as_str(), job.working_dir.as_deref()))
+        .collect();
+    assert_eq!(
+        work_dirs,
+        vec![
+            ("23673084_1", Some("/home/carol/sweep")),
+            ("23673084_2", Some("/home/carol/sweep")),
+            // Pending tasks are collapsed into one scontrol record that squeue
+            // would print as 23673084_[3-4%2]
+            ("23673084_3", None),
+            ("23673084_4", None),
+            ("99999", Some("/home/dave")),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn background_refresh_delivers_jobs_as_an_event() {
+    let (mut app, _) = fixture_app("basic");
+
+    app.start_refresh();
+    assert!(app.is_loading);
+    let event = app.receive_event().await.unwrap();
+    app.handle_app_event(event);
+
+    assert!(!app.is_loading);
+    assert!(!app.refresh_in_flight);
+    assert_eq!(app.job_list.jobs.len(), 3);
+}
+
+#[tokio::test]
+async fn background_refresh_overtaken_by_a_newer_one_is_dropped() {
+    let (mut app, _) = fixture_app("basic");
+
+    app.start_refresh();
+    // The user switches to an empty filter before the refresh lands
+    app.executor = Arc::new(SlurmFixture::new("tests/fixtures/empty"));
+    app.refresh_jobs().await.unwrap();
+    let event = app.receive_event().await.unwrap();
+    app.handle_app_event(event);
+
+    assert!(app.job_list.jobs.is_empty());
+    assert!(!app.refresh_in_flight);
+}
+
+#[tokio::test]
+async fn logs_load_in_the_background_for_the_job_still_selected() {
+    let (mut app, _) = fixture_app("basic");
+    app.refresh_jobs().await.unwrap();
+    assert_eq!(app.job_logs.as_ref().unwrap().job_id, "12345");
+
+    app.select_next_job();
+    assert!(app.logs_stale());
+    app.start_logs_fetch();
+    // Asking again while they load does not fetch twice
+    app.start_logs_fetch();
+    // Pending jobs have no usage to poll
+    app.start_usage_fetch();
+    assert!(app.job_usage.is_none());
+    assert_eq!(app.fetches_in_flight, 1);
+
+    // The selection moves back before the logs land
+    app.select_previous_job();
+    app.wait_for_fetches().await;
+    assert_eq!(app.job_logs.as_ref().unwrap().job_id, "12345");
+    assert!(!app.logs_stale());
+    assert!(app.logs_loading.is_none());
+}
+
+#[tokio::test]
+async fn fixture_applies_user_and_partition_filters() {
+    let (mut app, _) = fixture_app("basic");
+
+    app.current_user = Some("alice".to_string());
+    app.refresh_jobs().await.unwrap();
+    let ids: Vec<_> = app
+        .job_list
+        .jobs
+        .iter()
+        .map(|j| j.job_id.as_str())
+        .collect();
+    assert_eq!(ids, vec!["12345", "12347"]);
+
+    app.current_partition = Some("gpu".to_string());
+    app.refresh_jobs().await.unwrap();
+    let ids: Vec<_> = app
+        .job_list
+        .jobs
+        .iter()
+        .map(|j| j.job_id.as_str())
+        .collect();
+    assert_eq!(ids, vec!["12347"]);
+}
+
+#[tokio::test]
+async fn json_fixture_applies_user_filter() {
+    let (mut app, _) = fixture_app("json_basic");
+    app.current_user = Some("bob".to_string());
+    app.refresh_jobs().await.unwrap();
+
+    assert_eq!(app.output_format, Some(OutputFormat::Json));
+    let ids: Vec<_> = app
+        .job_list
+        .jobs
+        .iter()
+        .map(|j| j.job_id.as_str())
+        .collect();
+    assert_eq!(ids, vec!["12346"]);
+}
+
+#[tokio::test]
+async fn selection_follows_job_through_state_transitions() {
+    let (mut app, _) = fixture_app("transitions");
+
+    // squeue.0.txt
+    app.refresh_jobs().await.unwrap();
+    assert_eq!(app.job_list.jobs[0].state, JobState::Pending);
+    app.select_next_job();
+    app.select_next_job();
+    assert_eq!(app.selected_job.as_ref().unwrap().job_id, "202");
+
+    // squeue.1.txt: a new job lands above the selection and 200 starts
+    app.refresh_jobs().await.unwrap();
+    assert_eq!(app.selected_job_index, 3);
+    let selected = app.selected_job.as_ref().unwrap();
+    assert_eq!(selected.job_id, "202");
+    assert_eq!(selected.state, JobState::Unknown("CG".to_string()));
+    assert_eq!(app.job_list.jobs[1].state, JobState::Running);
+
+    // squeue.2.txt: the selected job has left the queue
+    app.refresh_jobs().await.unwrap();
+    assert_eq!(app.selected_job_index, 2);
+    assert_eq!(app.selected_job.as_ref().unwrap().job_id, "201");
+
+    // The last snapshot sticks
+    app.refresh_jobs().await.unwrap();
+    assert_eq!(app.job_list.jobs.len(), 3);
+}
+
+#[tokio::test]
+async fn cancelled_job_disappears_from_the_next_refresh() {
+    let (mut app, fixture) = fixture_app("basic");
+    app.refresh_jobs().await.unwrap();
+
+    app.select_next_job();
+    app.open_cancel_popup();
+    app.confirm_cancel();
+    app.wait_for_fetches().await;
+    app.refresh_jobs().await.unwrap();
+
+    assert_eq!(*fixture.cancelled.lock().unwrap(), vec!["12346"]);
+    let ids: Vec<_> = app
+        .job_list
+        .jobs
+        .iter()
+        .map(|j| j.job_id.as_str())
+        .collect();
+    assert_eq!(ids, vec!["12345", "12347"]);
+}
+
+#[tokio::test]
+async fn cancelling_an_array_removes_all_its_tasks() {
+    let (mut app, fixture) = fixture_app("array_jobs");
+    fixture
+        .scancel("23673084", None, &CancelOptions::default())
+        .await
+        .unwrap();
+    app.refresh_jobs().await.unwrap();
+
+    let ids: Vec<_> = app
+        .job_list
+        .jobs
+        .iter()
+        .map(|j| j.job_id.as_str())
+        .collect();
+    assert_eq!(ids, vec!["99999"]);
+}
+
+#[tokio::test]
+async fn cancel_popup_offers_the_task_the_array_or_a_typed_range() {
+    let (mut app, fixture) = fixture_app("array_jobs");
+    app.refresh_jobs().await.unwrap();
+    app.select_next_job();
+    app.open_cancel_popup();
+
+    let array = app.array_cancel.as_mut().unwrap();
+    assert_eq!(array.known_tasks, vec![1, 2, 3, 4]);
+    assert_eq!(array.target(), Ok(("23673084_2".to_string(), 1)));
+    array.next_scope();
+    assert_eq!(array.target(), Ok(("23673084".to_string(), 4)));
+    array.next_scope();
+    assert_eq!(array.scope, ArrayCancelScope::Range);
+    for c in "2-5".chars() {
+        array.push(c);
+    }
+    assert_eq!(
+        array.target(),
+        Err("array 23673084 has no task 5 in the queue".to_string())
+    );
+    // A huge range is refused without being expanded
+    for c in "0000000".chars() {
+        array.push(c);
+    }
+    assert_eq!(
+        array.target(),
+        Err("array 23673084 has no task 5 in the queue".to_string())
+    );
+    for _ in 0..7 {
+        array.pop();
+    }
+
+    // An unusable range keeps the popup open
+    app.confirm_cancel();
+    app.wait_for_fetches().await;
+    assert_eq!(app.state, AppState::CancelJobPopup);
+    assert!(fixture.cancelled.lock().unwrap().is_empty());
+
+    let array = app.array_cancel.as_mut().unwrap();
+    array.pop();
+    array.push('3');
+    assert_eq!(array.target(), Ok(("23673084_[2-3]".to_string(), 2)));
+    app.confirm_cancel();
+    app.wait_for_fetches().await;
+
+    assert_eq!(app.state, AppState::Normal);
+    assert_eq!(*fixture.cancelled.lock().unwrap(), vec!["23673084_[2-3]"]);
+    let ids: Vec<_> = app
+        .job_list
+        .jobs
+        .iter()
+        .map(|j| j.job_id.as_str())
+        .collect();
+    assert_eq!(ids, vec!["23673084_1", "23673084_4", "99999"]);
+
+    // Plain jobs keep the simple confirmation
+    app.select_next_job();
+    app.select_next_job();
+    app.open_cancel_popup();
+    assert!(app.array_cancel.is_none());
+}
+
+#[tokio::test]
+async fn nodes_tab_shows_jobs_running_on_the_selected_node() {
+    let (mut app, _) = fixture_app("nodes");
+    app.refresh_jobs().await.unwrap();
+    app.switch_view(View::Nodes);
+    app.wait_for_fetches().await;
+
+    let names: Vec<_> = app.node_list.iter().map(|n| n.name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec!["node01", "node02", "node03", "node04", "gpu01", "gpu02"]
+    );
+    assert!(app.get_selected_job().is_none());
+
+    // node02 is in big_mpi's node[01-02] and runs postprocess
+    app.select_next_job();
+    let node = app.get_selected_node().unwrap();
+    assert_eq!(node.state, NodeState::Allocated);
+    let ids: Vec<_> = app
+        .jobs_on_node(node)
+        .iter()
+        .map(|j| j.job_id.as_str())
+        .collect();
+    assert_eq!(ids, vec!["4101", "4103"]);
+
+    // gpu01 sits in two partitions but is listed once
+    app.node_index = 4;
+    let node = app.get_selected_node().unwrap();
+    assert_eq!(node.partitions, vec!["gpu", "long"]);
+    assert_eq!(node.memory_alloc, Some(64000));
+    assert_eq!(app.jobs_on_node(node).len(), 1);
+}
+
+#[tokio::test]
+async fn nodes_tab_follows_the_partition_filter() {
+    let (mut app, _) = fixture_app("nodes");
+    app.current_partition = Some("gpu".to_string());
+    app.switch_view(View::Nodes);
+    app.wait_for_fetches().await;
+
+    let states: Vec<_> = app
+        .node_list
+        .iter()
+        .map(|n| (n.name.as_str(), n.state.clone()))
+        .collect();
+    assert_eq!(
+        states,
+        vec![("gpu01", NodeState::Mixed), ("gpu02", NodeState::Down)]
+    );
+    assert_eq!(
+        app.node_list[1].reason.as_deref(),
+        Some("powered down for maintenance")
+    );
+}
+
+#[tokio::test]
+async fn partitions_tab_counts_our_jobs_and_sets_the_filter() {
+    let (mut app, _) = fixture_app("nodes");
+    app.current_user = Some("alice".to_string());
+    app.switch_view(View::Partitions);
+    app.wait_for_fetches().await;
+
+    let counts: Vec<_> = app
+        .partition_list
+        .iter()
+        .map(|p| (p.name.as_str(), p.running_jobs, p.pending_jobs))
+        .collect();
+    assert_eq!(counts, vec![("debug", 2, 0), ("gpu", 0, 0), ("long", 0, 0)]);
+    assert!(app.partition_list[0].is_default);
+
+    // Enter filters the Jobs tab on the highlighted partition
+    app.select_next_job();
+    app.confirm_partition();
+    app.wait_for_fetches().await;
+    assert_eq!(app.view, View::Jobs);
+    assert_eq!(app.current_partition.as_deref(), Some("gpu"));
+    assert!(app.job_list.jobs.is_empty());
+
+    // The counts ignore the partition filter, and choosing it again clears it
+    app.switch_view(View::Partitions);
+    app.wait_for_fetches().await;
+    assert_eq!(app.get_selected_partition().unwrap().name, "gpu");
+    assert_eq!(app.partition_list[0].running_jobs, 2);
+    app.confirm_partition();
+    app.wait_for_fetches().await;
+    assert_eq!(app.current_partition, None);
+    assert_eq!(app.job_list.jobs.len(), 2);
+}
+
+#[tokio::test]
+async fn selected_running_job_gets_live_usage() {
+    let (mut app, _) = fixture_app("basic");
+    app.refresh_jobs().await.unwrap();
+
+    let usage = app.job_usage.as_ref().unwrap();
+    assert_eq!(usage.job_id, "12345");
+    let steps: Vec<_> = usage.steps.iter().map(|s| s.step_id.as_str()).collect();
+    assert_eq!(steps, vec!["extern", "batch"]);
+    // 7.26G of the 8G asked for
+    assert_eq!(usage.peak_rss(), Some(7_611_980 * 1024));
+
+    // Pending jobs have no usage to poll
+    app.select_next_job();
+    assert!(app.usage_stale());
+    app.refresh_usage().await;
+    assert!(app.job_usage.is_none());
+    assert!(!app.usage_stale());
+}
+
+#[tokio::test]
+async fn pending_jobs_get_expected_start_and_sort_by_it() {
+    let (mut app, _) = fixture_app("pending");
+    app.refresh_jobs().await.unwrap();
+
+    let starts: Vec<_> = app
+        .job_list
+        .jobs
+        .iter()
+        .map(|j| {
+            // squeue prints local times
+            let start = j.estimated_start.map(|t| t.with_timezone(&Local));
+            (
+                j.job_id.as_str(),
+                start.map(|t| t.naive_local().to_string()),
+            )
+        })
+        .collect();
+    assert_eq!(
+        starts,
+        vec![
+            ("5001", None),
+            ("5002", Some("2024-01-16 03:30:00".to_string())),
+            ("5003", Some("2024-01-15 12:00:00".to_string())),
+            // Not estimated yet
+            ("5004", None),
+        ]
+    );
+
+    // The selection stays on the same job when the order changes
+    app.select_next_job();
+    app.toggle_job_sort();
+    app.wait_for_fetches().await;
+    let ids: Vec<_> = app
+        .job_list
+        .jobs
+        .iter()
+        .map(|j| j.job_id.as_str())
+        .collect();
+    assert_eq!(ids, vec!["5001", "5003", "5002", "5004"]);
+    assert_eq!(app.get_selected_job().unwrap().job_id, "5002");
+
+    app.toggle_job_sort();
+
+    app.wait_for_fetches().await;
+    let ids: Vec<_> = app
+        .job_list
+        .jobs
+        .iter()
+        .map(|j| j.job_id.as_str())
+        .collect();
+    assert_eq!(ids, vec!["5001", "5002", "5003", "5004"]);
+}
+
+#[tokio::test]
+async fn why_pending_ranks_the_job_and_shows_fairshare() {
+    let (mut app, _) = fixture_app("pending");
+    app.refresh_jobs().await.unwrap();
+
+    // Running jobs have nothing to explain
+    app.open_why_pending_popup();
+    app.wait_for_fetches().await;
+    assert_eq!(app.state, AppState::Normal);
+
+    app.select_next_job();
+    app.open_why_pending_popup();
+    app.wait_for_fetches().await;
+    assert_eq!(app.state, AppState::WhyPendingPopup);
+    let report = app.pending_report.as_ref().unwrap();
+    assert_eq!(report.job.job_id, "5002");
+    // bob, carol and alice's other job all outrank it in gpu
+    assert_eq!(report.rank, Some((4, 4)));
+    let priority = report.priority.as_ref().unwrap();
+    assert_eq!((priority.priority, priority.fairshare), (3120, 2000));
+    let fairshare = report.fairshare.as_ref().unwrap();
+    assert_eq!(fairshare.user, "alice");
+    assert_eq!(fairshare.fairshare, Some(0.263158));
+    assert!(report.errors.is_empty());
+
+    app.dismiss_why_pending_popup();
+    assert_eq!(app.state, AppState::Normal);
+    assert!(app.pending_report.is_none());
+}
+
+#[tokio::test]
+async fn job_actions_only_open_for_jobs_in_a_fitting_state() {
+    let (mut app, fixture) = fixture_app("pending");
+    app.refresh_jobs().await.unwrap();
+
+    // 5001 is running: it can be requeued but not held or released
+    app.open_action_popup(JobAction::Hold);
+    assert_eq!(app.state, AppState::Normal);
+    app.open_action_popup(JobAction::Release);
+    assert_eq!(app.state, AppState::Normal);
+    app.open_action_popup(JobAction::Requeue);
+    assert_eq!(app.state, AppState::JobActionPopup(JobAction::Requeue));
+    app.dismiss_action_popup();
+
+    // 5002 is pending: it can be held but not released or requeued
+    app.select_next_job();
+    app.open_action_popup(JobAction::Release);
+    assert_eq!(app.state, AppState::Normal);
+    app.open_action_popup(JobAction::Requeue);
+    assert_eq!(app.state, AppState::Normal);
+    app.open_action_popup(JobAction::Hold);
+    app.confirm_action();
+    app.wait_for_fetches().await;
+
+    // 5004 is held: it can only be released
+    app.select_next_job();
+    app.select_next_job();
+    app.open_action_popup(JobAction::Hold);
+    assert_eq!(app.state, AppState::Normal);
+    app.open_action_popup(JobAction::Release);
+    app.confirm_action();
+    app.wait_for_fetches().await;
+
+    assert_eq!(*fixture.held.lock().unwrap(), vec!["5002"]);
+    assert_eq!(*fixture.released.lock().unwrap(), vec!["5004"]);
+    assert!(fixture.requeued.lock().unwrap().is_empty());
+    assert_eq!(app.state, AppState::Normal);
+}
+
+#[tokio::test]
+async fn requeue_is_only_offered_for_batch_jobs_slurmctld_still_lists() {
+    let (mut app, _) = fixture_app("basic");
+    app.refresh_jobs().await.unwrap();
+    app.switch_view(View::History);
+    app.wait_for_fetches().await;
+
+    // sacct records may be long gone from the controller
+    app.history_index = app
+        .history_list
+        .jobs
+        .iter()
+        .position(|job| job.job_id == "12302")
+        .unwrap();
+    app.open_action_popup(JobAction::Requeue);
+    assert_eq!(app.state, AppState::Normal);
+
+    let mut job = Job::new(
+        "700".to_string(),
+        "train".to_string(),
+        "alice".to_string(),
+        JobState::Running,
+    );
+    assert!(JobAction::Requeue.applies_to(&job));
+    job.state = JobState::Unknown("S".to_string());
+    assert!(JobAction::Requeue.applies_to(&job));
+    job.state = JobState::Unknown("CF".to_string());
+    assert!(!JobAction::Requeue.applies_to(&job));
+    // srun and salloc jobs cannot be requeued
+    job.state = JobState::Running;
+    job.batch = Some(false);
+    assert!(!JobAction::Requeue.applies_to(&job));
+}
+
+#[tokio::test]
+async fn job_action_applies_to_snapshotted_job_even_if_selection_moves() {
+    let (mut app, fixture) = fixture_app("pending");
+    app.refresh_jobs().await.unwrap();
+
+    app.open_action_popup(JobAction::Requeue);
+
+    app.select_next_job();
+    app.refresh_jobs().await.unwrap();
+
+    app.confirm_action();
+    app.wait_for_fetches().await;
+
+    assert_eq!(*fixture.requeued.lock().unwrap(), vec!["5001"]);
+    assert!(app.action_target.is_none());
+}
+
+#[tokio::test]
+async fn edit_form_is_prefilled_from_scontrol_and_applies_the_diff() {
+    let (mut app, fixture) = fixture_app("pending");
+    app.refresh_jobs().await.unwrap();
+    app.select_next_job();
+
+    // The form opens once scontrol has answered
+    app.open_edit_popup();
+    assert_eq!(app.state, AppState::Normal);
+    assert!(app.popup_loading);
+    app.wait_for_fetches().await;
+    assert_eq!(app.state, AppState::EditJobPopup);
+    assert!(!app.popup_loading);
+    let form = app.edit_form.as_mut().unwrap();
+    let prefilled: Vec<_> = form
+        .fields
+        .iter()
+        .map(|f| (f.field.key(), f.value.as_str()))
+        .collect();
+    assert_eq!(
+        prefilled,
+        vec![
+            ("TimeLimit", "04:00:00"),
+            ("Partition", "gpu"),
+            ("QOS", "normal"),
+            ("NumNodes", "1"),
+            ("JobName", "sweep_late"),
+            ("Comment", "lr_sweep"),
+            ("Nice", "0"),
+        ]
+    );
+
+    // Shorten the time limit so the job backfills
+    form.fields[0].value.clear();
+    "1:30:00".chars().for_each(|c| form.push(c));
+    // Typing into another field and undoing it is no change
+    form.previous_field();
+    form.push('x');
+    form.pop();
+
+    app.apply_edit();
+    app.wait_for_fetches().await;
+
+    assert_eq!(app.state, AppState::Normal);
+    assert!(app.edit_form.is_none());
+    assert_eq!(
+        *fixture.updated.lock().unwrap(),
+        vec![(
+            "5002".to_string(),
+            vec![("TimeLimit".to_string(), "1:30:00".to_string())]
+        )]
+    );
+}
+
+#[tokio::test]
+async fn edit_form_keeps_spaced_values_and_blanks_unset_ones() {
+    let (mut app, _) = fixture_app("pending");
+    app.refresh_jobs().await.unwrap();
+    let comment = |app: &App| {
+        let form = app.edit_form.as_ref().unwrap();
+        form.fields
+            .iter()
+            .find(|f| f.field.key() == "Comment")
+            .map(|f| f.value.clone())
+    };
+
+    app.sync_selection(Some("5004"));
+    app.open_edit_popup();
+    app.wait_for_fetches().await;
+    assert_eq!(comment(&app).as_deref(), Some("needs rerun after fix"));
+    app.dismiss_edit_popup();
+
+    app.sync_selection(Some("5003"));
+    app.open_edit_popup();
+    app.wait_for_fetches().await;
+    assert_eq!(comment(&app).as_deref(), Some(""));
+}
+
+#[tokio::test]
+async fn invalid_edit_keeps_the_form_open_on_the_field() {
+    let (mut app, fixture) = fixture_app("pending");
+    app.refresh_jobs().await.unwrap();
+    app.select_next_job();
+    app.open_edit_popup();
+    app.wait_for_fetches().await;
+
+    let form = app.edit_form.as_mut().unwrap();
+    form.fields[3].value = "0".to_string();
+    form.fields[6].value = "lots".to_string();
+    app.apply_edit();
+    app.wait_for_fetches().await;
+
+    assert_eq!(app.state, AppState::EditJobPopup);
+    let form = app.edit_form.as_ref().unwrap();
+    assert_eq!(form.selected, 3);
+    assert_eq!(
+        form.error.as_deref(),
+        Some("Nodes: expected a node count or a range like 2-4")
+    );
+    assert!(fixture.updated.lock().unwrap().is_empty());
+
+    // Nothing changed: closing without calling scontrol
+    app.dismiss_edit_popup();
+    app.open_edit_popup();
+    app.wait_for_fetches().await;
+    app.apply_edit();
+    app.wait_for_fetches().await;
+    assert_eq!(app.state, AppState::Normal);
+    assert!(fixture.updated.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn signal_goes_to_the_snapshotted_job_and_picked_scope() {
+    let (mut app, fixture) = fixture_app("pending");
+    app.refresh_jobs().await.unwrap();
+
+    app.open_signal_popup();
+    assert_eq!(app.state, AppState::SignalPopup);
+    let picker = app.signal_picker.as_mut().unwrap();
+    // sstat's step 0 is offered besides the batch script and whole job
+    assert_eq!(
+        picker.scopes,
+        vec![
+            SignalScope::Batch,
+            SignalScope::Full,
+            SignalScope::Steps,
+            SignalScope::Step("0".to_string())
+        ]
+    );
+    assert_eq!(picker.signal(), "USR1");
+    picker.next_signal();
+    picker.previous_signal();
+    picker.next_scope();
+    app.choose_signal();
+    assert_eq!(app.state, AppState::ConfirmSignalPopup);
+
+    // The selection moves while the confirmation is open
+    app.select_next_job();
+    app.refresh_jobs().await.unwrap();
+    app.confirm_signal();
+    app.wait_for_fetches().await;
+
+    assert_eq!(
+        *fixture.signalled.lock().unwrap(),
+        vec![(
+            "5001".to_string(),
+            CancelOptions::signal("USR1", SignalScope::Full)
+        )]
+    );
+    assert!(fixture.cancelled.lock().unwrap().is_empty());
+    assert!(app.signal_picker.is_none());
+    assert_eq!(app.state, AppState::Normal);
+
+    // Only running jobs take signals
+    app.open_signal_popup();
+    assert_eq!(app.state, AppState::Normal);
+}
+
+#[tokio::test]
+async fn jobs_are_marked_one_by_one_by_range_or_all_at_once() {
+    let (mut app, fixture) = fixture_app("pending");
+    app.refresh_jobs().await.unwrap();
+    let marked = |app: &App| -> Vec<String> {
+        let mut ids: Vec<_> = app.marked_jobs.iter().map(|(_, id)| id.clone()).collect();
+        ids.sort();
+        ids
+    };
+
+    app.toggle_mark();
+    app.select_next_job();
+    app.select_next_job();
+    app.mark_range();
+    assert_eq!(marked(&app), vec!["5001", "5002", "5003"]);
+
+    // Space unmarks, and a range runs back up from the new anchor
+    app.toggle_mark();
+    app.select_next_job();
+    app.mark_range();
+    assert_eq!(marked(&app), vec!["5001", "5002", "5003", "5004"]);
+
+    app.toggle_mark_all();
+    assert!(app.marked_jobs.is_empty());
+    app.toggle_mark_all();
+    assert_eq!(app.marked_jobs.len(), 4);
+
+    // Marks go with the jobs that leave the list
+    fixture
+        .scancel("5004", None, &CancelOptions::default())
+        .await
+        .unwrap();
+    app.refresh_jobs().await.unwrap();
+    assert_eq!(marked(&app), vec!["5001", "5002", "5003"]);
+    app.clear_marks();
+    assert!(app.marked_jobs.is_empty());
+}
+
+#[tokio::test]
+async fn bulk_cancel_confirms_once_for_the_snapshotted_marked_jobs() {
+    let (mut app, fixture) = fixture_app("pending");
+    app.refresh_jobs().await.unwrap();
+    app.toggle_mark_all();
+
+    app.open_cancel_popup();
+    assert_eq!(app.state, AppState::BulkActionPopup(JobAction::Cancel));
+    assert_eq!(app.bulk_targets.len(), 4);
+
+    // Marks changing under the popup do not change what is cancelled
+    app.toggle_mark();
+    app.select_next_job();
+    app.refresh_jobs().await.unwrap();
+    app.confirm_bulk();
+    app.wait_for_fetches().await;
+
+    let mut cancelled = fixture.cancelled.lock().unwrap().clone();
+    cancelled.sort();
+    assert_eq!(cancelled, vec!["5001", "5002", "5003", "5004"]);
+    assert!(app.marked_jobs.is_empty());
+    assert!(app.bulk_report.is_none());
+    assert_eq!(app.state, AppState::Normal);
+}
+
+#[tokio::test]
+async fn bulk_actions_skip_marked_jobs_they_do_not_apply_to() {
+    let (mut app, fixture) = fixture_app("pending");
+    app.refresh_jobs().await.unwrap();
+    app.toggle_mark_all();
+
+    app.open_action_popup(JobAction::Hold);
+    let targets: Vec<_> = app.bulk_targets.iter().map(|j| j.job_id.as_str()).collect();
+    assert_eq!(targets, vec!["5002", "5003"]);
+    // The running job and the one already held
+    assert_eq!(app.bulk_skipped, 2);
+    app.confirm_bulk();
+    app.wait_for_fetches().await;
+
+    let mut held = fixture.held.lock().unwrap().clone();
+    held.sort();
+    assert_eq!(held, vec!["5002", "5003"]);
+
+    // Nothing marked can be released now that the marks are cleared
+    app.toggle_mark();
+    app.open_action_popup(JobAction::Release);
+    assert_eq!(app.state, AppState::Normal);
+    assert_eq!(
+        app.error_message.as_deref(),
+        Some("Cannot release any of the 1 marked jobs")
+    );
+}
+
+#[tokio::test]
+async fn submitted_script_is_picked_overridden_and_selected_once_listed() {
+    let (mut app, fixture) = fixture_app("submit");
+    app.submit_dir = std::fs::canonicalize("tests/fixtures/submit/scripts").unwrap();
+    app.refresh_jobs().await.unwrap();
+    app.open_submit_popup();
+    assert_eq!(app.state, AppState::SubmitFilePopup);
+
+    let names = |app: &App| -> Vec<String> {
+        let picker = app.file_picker.as_ref().unwrap();
+        picker
+            .entries
+            .iter()
+            .map(|path| {
+                path.file_name()
+                    .map_or("..".to_string(), |name| name.to_string_lossy().to_string())
+            })
+            .collect()
+    };
+    // Directories first; other files are left out
+    assert_eq!(names(&app), vec!["eval", "prep.sbatch", "train.sh"]);
+    app.pick_file();
+    assert_eq!(names(&app), vec!["..", "eval.sh"]);
+    app.pick_file();
+    assert_eq!(names(&app)[0], "eval");
+
+    app.file_picker.as_mut().unwrap().selected = 2;
+    app.pick_file();
+    assert_eq!(app.state, AppState::SubmitFormPopup);
+    let form = app.submit_form.as_mut().unwrap();
+    let prefilled: Vec<_> = form.fields.iter().map(|f| f.value.as_str()).collect();
+    assert_eq!(
+        prefilled,
+        vec!["resnet", "gpu", "04:00:00", "", "", "", "gpu:1", ""]
+    );
+
+    // An invalid override keeps the form open on its field
+    form.selected = 2;
+    form.fields[2].value = "soon".to_string();
+    app.submit_job();
+    app.wait_for_fetches().await;
+    let form = app.submit_form.as_mut().unwrap();
+    assert_eq!(app.state, AppState::SubmitFormPopup);
+    assert_eq!(
+        form.error.as_deref(),
+        Some("Time limit: expected minutes, [D-]HH:MM:SS or UNLIMITED")
+    );
+    form.fields[2].value = "1:00:00".to_string();
+    form.fields[5].value = "16G".to_string();
+    let time = form.directives.iter().find(|d| d.option == "time").unwrap();
+    assert!(form.overrides_directive(time));
+    app.submit_job();
+    // sbatch runs in the background; the form waits for it, and a second
+    // Enter meanwhile does not submit again
+    assert_eq!(app.state, AppState::SubmitFormPopup);
+    assert_eq!(app.actions_in_flight, 1);
+    app.submit_job();
+    app.wait_for_fetches().await;
+
+    assert_eq!(app.state, AppState::Normal);
+    let submitted = fixture.submitted.lock().unwrap().clone();
+    assert_eq!(submitted.len(), 1);
+    assert!(submitted[0].0.ends_with("scripts/train.sh"));
+    assert_eq!(
+        submitted[0].1,
+        vec![
+            ("time".to_string(), "1:00:00".to_string()),
+            ("mem".to_string(), "16G".to_string()),
+        ]
+    );
+
+    // Not listed yet, so the selection waits for the next refresh
+    assert_eq!(app.selected_job.as_ref().unwrap().job_id, "300");
+    app.refresh_jobs().await.unwrap();
+    assert_eq!(app.selected_job.as_ref().unwrap().job_id, "302");
+    assert!(app.submitted_job.is_none());
+}
+
+#[tokio::test]
+async fn resubmit_rebuilds_the_request_and_records_the_lineage() {
+    let (mut app, fixture) = fixture_app("basic");
+    app.refresh_jobs().await.unwrap();
+    app.switch_view(View::History);
+    app.wait_for_fetches().await;
+
+    // Running jobs cannot be resubmitted, and 12301 has left the controller
+    app.history_index = app
+        .history_list
+        .jobs
+        .iter()
+        .position(|job| job.job_id == "12301")
+        .unwrap();
+    app.open_resubmit_popup();
+    app.wait_for_fetches().await;
+    assert_eq!(app.state, AppState::Normal);
+    assert!(
+        app.error_message
+            .as_deref()
+            .unwrap()
+            .starts_with("Cannot resubmit job 12301: the controller no longer knows it")
+    );
+
+    app.history_index = app
+        .history_list
+        .jobs
+        .iter()
+        .position(|job| job.job_id == "12302")
+        .unwrap();
+    app.open_resubmit_popup();
+    app.wait_for_fetches().await;
+    assert_eq!(app.state, AppState::SubmitFormPopup);
+    let form = app.submit_form.as_mut().unwrap();
+    assert!(form.script.ends_with("train/train.sh"));
+    let prefilled: Vec<_> = form.fields.iter().map(|f| f.value.as_str()).collect();
+    assert_eq!(
+        prefilled,
+        vec![
+            "train_model",
+            "gpu",
+            "04:00:00",
+            "1",
+            "4",
+            "32G",
+            "gpu:1",
+            ""
+        ]
+    );
+
+    // Tweak the time limit before sending it off again
+    form.fields[2].value = "6:00:00".to_string();
+    app.submit_job();
+    app.wait_for_fetches().await;
+    assert_eq!(app.state, AppState::Normal);
+    assert_eq!(app.view, View::Jobs);
+
+    let submitted = fixture.submitted.lock().unwrap().clone();
+    let options: Vec<String> = submitted[0]
+        .1
+        .iter()
+        .map(|(option, value)| format!("--{}={}", option, value))
+        .collect();
+    assert_eq!(submitted[0].0, "/home/alice/train/train.sh");
+    assert_eq!(
+        options,
+        vec![
+            "--chdir=/home/alice/train",
+            "--ntasks=2",
+            "--account=research",
+            "--qos=normal",
+            "--job-name=train_model",
+            "--partition=gpu",
+            "--time=6:00:00",
+            "--nodes=1",
+            "--cpus-per-task=4",
+            "--mem=32G",
+            "--gres=gpu:1",
+        ]
+    );
+
+    let original = app
+        .history_list
+        .jobs
+        .iter()
+        .find(|job| job.job_id == "12302")
+        .unwrap()
+        .clone();
+    assert_eq!(
+        app.resubmitted_as(&original),
+        Some(&(None, "12400".to_string()))
+    );
+    let mut new = original.clone();
+    new.job_id = "12400".to_string();
+    assert_eq!(
+        app.resubmission_of(&new),
+        Some(&(None, "12302".to_string()))
+    );
+}
+
+#[tokio::test]
+async fn resubmit_sends_only_what_the_script_does_not_ask_for() {
+    let (mut app, _) = fixture_app("basic");
+    app.refresh_jobs().await.unwrap();
+    app.switch_view(View::History);
+    app.wait_for_fetches().await;
+    app.history_index = app
+        .history_list
+        .jobs
+        .iter()
+        .position(|job| job.job_id == "12302")
+        .unwrap();
+    app.open_resubmit_popup();
+    app.wait_for_fetches().await;
+    let job = app
+        .submit_form
+        .as_ref()
+        .unwrap()
+        .resubmit
+        .as_ref()
+        .unwrap()
+        .job
+        .clone();
+    // The fixture's script does not exist, so nothing is left to it
+    assert_eq!(
+        app.submit_form.as_ref().unwrap().warning.as_deref(),
+        Some("The script cannot be read from here, so everything job 12302 asked for is sent")
+    );
+
+    let script = "#!/bin/bash\n\
+                  #SBATCH --job-name=train_model\n\
+                  #SBATCH -p gpu\n\
+                  #SBATCH --time=4:00:00\n\
+                  #SBATCH --ntasks=2\n\
+                  #SBATCH --cpus-per-task=4\n\
+                  #SBATCH --mem=16G\n\
+                  #SBATCH --gres=gpu:1\n\
+                  python train.py\n";
+    let form = SubmitForm::resubmit(job, "train.sh".into(), Some(script), Some(Utc::now()));
+    let options: Vec<String> = form
+        .overrides()
+        .unwrap()
+        .iter()
+        .map(|(option, value)| format!("--{}={}", option, value))
+        .collect();
+    // The script asks for less memory than the job ran with
+    assert_eq!(
+        options,
+        vec![
+            "--chdir=/home/alice/train",
+            "--account=research",
+            "--qos=normal",
+            "--mem=32G",
+        ]
+    );
+    assert!(
+        form.warning
+            .as_deref()
+            .unwrap()
+            .starts_with("The script has changed since job 12302 was submitted")
+    );
+}
+
+#[tokio::test]
+async fn array_summary_counts_tasks_and_resubmits_the_failed_ones() {
+    let (mut app, fixture) = fixture_app("array_sweep");
+    app.refresh_jobs().await.unwrap();
+
+    // Not part of an array
+    while app.selected_job.as_ref().unwrap().job_id != "601" {
+        app.select_next_job();
+    }
+    app.open_array_summary_popup();
+    app.wait_for_fetches().await;
+    assert_eq!(app.state, AppState::Normal);
+
+    while app.selected_job.as_ref().unwrap().job_id != "500_8" {
+        app.select_previous_job();
+    }
+    app.open_array_summary_popup();
+    app.wait_for_fetches().await;
+    assert_eq!(app.state, AppState::ArraySummaryPopup);
+    let summary = app.array_summary.as_ref().unwrap();
+    assert!(summary.errors.is_empty(), "{:?}", summary.errors);
+    assert_eq!(summary.total(), 12);
+    assert_eq!(
+        summary.counts,
+        vec![
+            (JobState::Completed, 3),
+            (JobState::Pending, 3),
+            (JobState::Failed, 2),
+            (JobState::Timeout, 1),
+            (JobState::OutOfMemory, 1),
+            (JobState::Cancelled, 1),
+            (JobState::Running, 1),
+        ]
+    );
+    assert_eq!(summary.failed, vec![1, 3, 4, 5]);
+    assert_eq!(summary.throttle, Some(4));
+    assert_eq!(summary.failed_array().as_deref(), Some("1,3-5%4"));
+
+    app.resubmit_failed_tasks();
+    app.wait_for_fetches().await;
+    assert_eq!(app.state, AppState::Normal);
+    assert!(app.array_summary.is_none());
+
+    let submitted = fixture.submitted.lock().unwrap().clone();
+    let options: Vec<String> = submitted[0]
+        .1
+        .iter()
+        .map(|(option, value)| format!("--{}={}", option, value))
+        .collect();
+    assert_eq!(submitted[0].0, "/home/erin/sweep/sweep.sh");
+    assert_eq!(
+        options,
+        vec![
+            "--chdir=/home/erin/sweep",
+            "--ntasks=1",
+            "--account=physics",
+            "--qos=normal",
+            "--job-name=sweep",
+            "--partition=compute",
+            "--time=01:00:00",
+            "--nodes=1",
+            "--cpus-per-task=2",
+            "--mem=4G",
+            "--array=1,3-5%4",
+        ]
+    );
+
+    // Each failed task leads to its task of the new array
+    let mut timed_out = Job::new(
+        "500_3".to_string(),
+        "sweep".to_string(),
+        "erin".to_string(),
+        JobState::Timeout,
+    );
+    timed_out.array_job_id = Some("500".to_string());
+    timed_out.array_task_id = Some(3);
+    assert_eq!(
+        app.resubmitted_as(&timed_out),
+        Some(&(None, "700_3".to_string()))
+    );
+    let mut completed = timed_out.clone();
+    completed.job_id = "500_0".to_string();
+    completed.array_task_id = Some(0);
+    assert_eq!(app.resubmitted_as(&completed), None);
+
+    // The new array's pending tasks are listed under its id
+    let mut pending = Job::new(
+        "700_[1,3-5%4]".to_string(),
+        "sweep".to_string(),
+        "erin".to_string(),
+        JobState::Pending,
+    );
+    pending.array_job_id = Some("700".to_string());
+    assert_eq!(
+        app.resubmission_of(&pending),
+        Some(&(None, "500".to_string()))
+    );
+}
+
+#[tokio::test]
+async fn failed_tasks_resubmit_with_what_the_script_asks_for_now() {
+    let (mut app, fixture) = fixture_app("array_sweep");
+    app.refresh_jobs().await.unwrap();
+    while app.selected_job.as_ref().unwrap().job_id != "500_8" {
+        app.select_previous_job();
+    }
+    app.open_array_summary_popup();
+    app.wait_for_fetches().await;
+
+    // After the out-of-memory task, the script asks for more than 4G
+    let script = std::env::temp_dir().join(format!("lazyslurm-{}-sweep.sh", std::process::id()));
+    std::fs::write(
+        &script,
+        "#!/bin/bash\n\
+         #SBATCH --job-name=sweep\n\
+         #SBATCH -p compute\n\
+         #SBATCH --time=1:00:00\n\
+         #SBATCH --cpus-per-task=2\n\
+         #SBATCH --mem=16G\n\
+         python sweep.py $SLURM_ARRAY_TASK_ID\n",
+    )
+    .unwrap();
+    let summary = app.array_summary.as_mut().unwrap();
+    summary.job.command = Some(script.display().to_string());
+
+    app.resubmit_failed_tasks();
+    app.wait_for_fetches().await;
+
+    let submitted = fixture.submitted.lock().unwrap().clone();
+    let options: Vec<String> = submitted[0]
+        .1
+        .iter()
+        .map(|(option, value)| format!("--{}={}", option, value))
+        .collect();
+    assert_eq!(
+        options,
+        vec![
+            "--chdir=/home/erin/sweep",
+            "--account=physics",
+            "--qos=normal",
+            "--array=1,3-5%4",
+        ]
+    );
+    std::fs::remove_file(&script).unwrap();
+}
+
+#[tokio::test]
+async fn shell_attaches_to_the_running_job_on_the_picked_node() {
+    let (mut app, fixture) = fixture_app("nodes");
+    app.refresh_jobs().await.unwrap();
+
+    // Nothing to attach to while pending
+    app.sync_selection(Some("4104"));
+    app.open_attach_popup();
+    assert!(app.shell_request.is_none());
+
+    // One node: straight to the shell
+    app.sync_selection(Some("4102"));
+    app.open_attach_popup();
+    assert_eq!(app.state, AppState::Normal);
+    let request = app.shell_request.take().unwrap();
+    assert_eq!(request.job_id, "4102");
+    assert_eq!(
+        request.command,
+        vec!["srun", "--jobid=4102", "--overlap", "--pty", "bash"]
+    );
+
+    // Several nodes: pick one first
+    app.sync_selection(Some("4101"));
+    app.open_attach_popup();
+    assert_eq!(app.state, AppState::AttachNodePopup);
+    assert!(app.shell_request.is_none());
+    let picker = app.node_picker.as_mut().unwrap();
+    assert_eq!(picker.nodes, vec!["node01", "node02"]);
+    picker.next();
+    app.attach_selected_node();
+    assert_eq!(app.state, AppState::Normal);
+    let request = app.shell_request.take().unwrap();
+    assert_eq!(
+        request.command,
+        vec![
+            "srun",
+            "--jobid=4101",
+            "--overlap",
+            "-w",
+            "node02",
+            "--pty",
+            "bash"
+        ]
+    );
+    assert_eq!(
+        *fixture.attached.lock().unwrap(),
+        vec![
+            ("4102".to_string(), None),
+            ("4101".to_string(), Some("node02".to_string())),
+        ]
+    );
+
+    // A shell that failed is reported once the TUI is back
+    let failed = std::process::Command::new("sh")
+        .args(["-c", "exit 3"])
+        .status()
+        .unwrap();
+    app.shell_exited(&request, Ok(failed));
+    app.wait_for_fetches().await;
+    assert_eq!(
+        app.error_message.as_deref(),
+        Some("The shell in job 4101 ended with exit status: 3")
+    );
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn an_interrupt_typed_into_the_shell_does_not_stop_the_app() {
+    use std::os::unix::process::ExitStatusExt;
+
+    let (mut app, _) = fixture_app("nodes");
+    app.refresh_jobs().await.unwrap();
+
+    // Ctrl-C before srun takes the tty reaches the whole process group
+    let command: Vec<String> = ["sh", "-c", "kill -INT $PPID; kill -INT $$"]
+        .map(String::from)
+        .to_vec();
+    let status = lazyslurm::ui::events::run_shell(&command).await.unwrap();
+    assert_eq!(status.signal(), Some(2));
+
+    let request = ShellRequest {
+        job_id: "4102".to_string(),
+        command,
+    };
+    app.shell_exited(&request, Ok(status));
+    app.wait_for_fetches().await;
+    assert_eq!(
+        app.error_message.as_deref(),
+        Some("The shell in job 4102 ended with signal: 2 (SIGINT)")
+    );
+}
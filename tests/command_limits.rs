@@ -0,0 +1,133 @@
+//! Tests for per-command timeouts and retries. A `sh -c '<script>'` wrapper
+//! stands in for a slow or flaky controller: the Slurm command line it is
+//! handed becomes `$0` and is ignored.
+
+use std::time::{Duration, Instant};
+
+use lazyslurm::slurm::{CommandLimits, CommandWrapper, SlurmExecutor, SlurmProcess};
+
+fn scratch_path(name: &str) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("lazyslurm-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn scripted(script: &str, limits: CommandLimits) -> SlurmProcess {
+    let wrapper = CommandWrapper::new(vec!["sh".into(), "-c".into(), script.into()]).unwrap();
+    SlurmProcess::wrapped(wrapper).with_limits(limits)
+}
+
+#[tokio::test]
+async fn hung_command_is_killed_after_the_timeout() {
+    let exec = scripted(
+        "sleep 10",
+        CommandLimits {
+            timeout: Duration::from_millis(200),
+            retries: 0,
+            backoff: Duration::ZERO,
+        },
+    );
+
+    let started = Instant::now();
+    let err = exec.squeue(None, None, None).await.unwrap_err();
+
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert_eq!(err.to_string(), "squeue timed out after 0.2s");
+}
+
+#[tokio::test]
+async fn transient_errors_are_retried_with_backoff() {
+    let attempts = scratch_path("attempts.log");
+    let script = format!(
+        "echo attempt >> '{}'; echo 'squeue: error: Socket timed out on send/recv operation' >&2; exit 1",
+        attempts.display()
+    );
+    let exec = scripted(
+        &script,
+        CommandLimits {
+            timeout: Duration::from_secs(5),
+            retries: 2,
+            backoff: Duration::from_millis(10),
+        },
+    );
+
+    let err = exec.squeue(None, None, None).await.unwrap_err();
+
+    assert!(err.to_string().contains("Socket timed out"), "{err}");
+    let attempts = std::fs::read_to_string(&attempts).unwrap();
+    assert_eq!(attempts.lines().count(), 3);
+    assert!(exec.status().is_none());
+}
+
+#[tokio::test]
+async fn other_errors_are_not_retried() {
+    let attempts = scratch_path("fatal.log");
+    let script = format!(
+        "echo attempt >> '{}'; echo 'squeue: error: Invalid user: nobody' >&2; exit 1",
+        attempts.display()
+    );
+    let exec = scripted(
+        &script,
+        CommandLimits {
+            timeout: Duration::from_secs(5),
+            retries: 2,
+            backoff: Duration::from_millis(10),
+        },
+    );
+
+    exec.squeue(Some("nobody"), None, None).await.unwrap_err();
+
+    let attempts = std::fs::read_to_string(&attempts).unwrap();
+    assert_eq!(attempts.lines().count(), 1);
+}
+
+#[tokio::test]
+async fn retries_are_reported_while_backing_off() {
+    let exec = scripted(
+        "echo 'slurm_load_jobs error: Unable to contact slurm controller' >&2; exit 1",
+        CommandLimits {
+            timeout: Duration::from_secs(5),
+            retries: 1,
+            backoff: Duration::from_millis(500),
+        },
+    );
+
+    let call = tokio::spawn({
+        let exec = exec.clone();
+        async move { exec.squeue(None, None, None).await }
+    });
+    tokio::time::sleep(Duration::from_millis(250)).await;
+
+    assert_eq!(
+        exec.status().as_deref(),
+        Some("controller slow / retrying in 1s (1/1)")
+    );
+    call.await.unwrap().unwrap_err();
+    assert!(exec.status().is_none());
+}
+
+#[tokio::test]
+async fn a_call_finishing_keeps_the_notice_of_another_still_retrying() {
+    let exec = scripted(
+        "case \"$0\" in *squeue*) echo 'slurm_load_jobs error: Unable to contact slurm controller' >&2; exit 1;; esac",
+        CommandLimits {
+            timeout: Duration::from_secs(5),
+            retries: 1,
+            backoff: Duration::from_millis(500),
+        },
+    );
+
+    let call = tokio::spawn({
+        let exec = exec.clone();
+        async move { exec.squeue(None, None, None).await }
+    });
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    exec.sinfo(None, None).await.unwrap();
+
+    assert_eq!(
+        exec.status().as_deref(),
+        Some("controller slow / retrying in 1s (1/1)")
+    );
+    call.await.unwrap().unwrap_err();
+    assert!(exec.status().is_none());
+}
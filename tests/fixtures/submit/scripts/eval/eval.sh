@@ -0,0 +1,2 @@
+#!/bin/bash
+srun python eval.py
@@ -0,0 +1,11 @@
+#!/bin/bash
+#SBATCH --job-name=resnet
+#SBATCH -p gpu
+#SBATCH --time 04:00:00   # four hours is plenty
+#SBATCH --gres=gpu:1
+#SBATCH --exclusive
+## #SBATCH --mem=8G is commented out
+
+module load cuda
+#SBATCH --nodes=4 comes after the first command and is ignored
+srun python train.py
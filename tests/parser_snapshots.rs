@@ -2,10 +2,19 @@
 //!
 //! These tests pin parser behavior against captured fixture outputs. To accept
 //! intentional changes, run `cargo insta review` (or `cargo insta accept`).
+//! Slurm prints local times, so snapshots with times assume `TZ=UTC`, as in
+//! CI and `just test`.
 
 use std::path::PathBuf;
+use std::time::Duration;
 
-use lazyslurm::slurm::{SlurmExecutor, SlurmFixture, SlurmParser};
+use lazyslurm::models::{
+    Job, JobState, TaskRangeError, format_task_ranges, parse_memory_mb, parse_sbatch_directives,
+    parse_task_ranges, parse_task_ranges_upto, parse_task_throttle, parse_time_limit,
+};
+use lazyslurm::slurm::{
+    CancelOptions, MAX_HOSTLIST_HOSTS, SlurmExecutor, SlurmFixture, SlurmParser,
+};
 
 fn fixture_dir(name: &str) -> PathBuf {
     PathBuf::from(env!("CARGO_MANIFEST_DIR"))
@@ -17,7 +26,7 @@ fn fixture_dir(name: &str) -> PathBuf {
 #[tokio::test]
 async fn parse_squeue_basic() {
     let exec = SlurmFixture::new(fixture_dir("basic"));
-    let raw = exec.squeue(None, None).await.unwrap();
+    let raw = exec.squeue(None, None, None).await.unwrap();
     let jobs = SlurmParser::parse_squeue_output(&raw).unwrap();
     insta::assert_yaml_snapshot!(jobs);
 }
@@ -25,7 +34,7 @@ async fn parse_squeue_basic() {
 #[tokio::test]
 async fn parse_squeue_empty() {
     let exec = SlurmFixture::new(fixture_dir("empty"));
-    let raw = exec.squeue(None, None).await.unwrap();
+    let raw = exec.squeue(None, None, None).await.unwrap();
     let jobs = SlurmParser::parse_squeue_output(&raw).unwrap();
     insta::assert_yaml_snapshot!(jobs);
 }
@@ -33,7 +42,7 @@ async fn parse_squeue_empty() {
 #[tokio::test]
 async fn parse_squeue_array_jobs() {
     let exec = SlurmFixture::new(fixture_dir("array_jobs"));
-    let raw = exec.squeue(None, None).await.unwrap();
+    let raw = exec.squeue(None, None, None).await.unwrap();
     let jobs = SlurmParser::parse_squeue_output(&raw).unwrap();
     insta::assert_yaml_snapshot!(jobs);
 }
@@ -41,7 +50,7 @@ async fn parse_squeue_array_jobs() {
 #[tokio::test]
 async fn parse_scontrol_running_job() {
     let exec = SlurmFixture::new(fixture_dir("basic"));
-    let raw = exec.scontrol_show_job("12345").await.unwrap();
+    let raw = exec.scontrol_show_job("12345", None).await.unwrap();
     let mut fields: Vec<(String, String)> = SlurmParser::parse_scontrol_output(&raw)
         .unwrap()
         .into_iter()
@@ -51,10 +60,35 @@ async fn parse_scontrol_running_job() {
     insta::assert_yaml_snapshot!(fields);
 }
 
+#[test]
+fn scontrol_values_run_up_to_the_next_key() {
+    let raw = "JobId=7 JobName=my long job\n   Comment=needs rerun after fix Nice=0 Power=\n";
+    let fields = SlurmParser::parse_scontrol_output(raw).unwrap();
+    assert_eq!(fields["JobName"], "my long job");
+    assert_eq!(fields["Comment"], "needs rerun after fix");
+    assert_eq!(fields["Nice"], "0");
+    assert_eq!(fields["Power"], "");
+}
+
+#[test]
+fn scontrol_values_keep_their_own_key_value_pairs() {
+    let raw = "JobId=7 JobName=train\n   \
+               Comment=sweep lr=0.1 batch_size=32 Nice=0\n   \
+               Command=/home/u/train.sh LR=0.1 --epochs=5\n   \
+               WorkDir=/home/u/runs/a=b\n";
+    let fields = SlurmParser::parse_scontrol_output(raw).unwrap();
+    assert_eq!(fields["Comment"], "sweep lr=0.1 batch_size=32");
+    assert_eq!(fields["Nice"], "0");
+    assert_eq!(fields["Command"], "/home/u/train.sh LR=0.1 --epochs=5");
+    assert_eq!(fields["WorkDir"], "/home/u/runs/a=b");
+    assert!(!fields.contains_key("lr"));
+    assert!(!fields.contains_key("LR"));
+}
+
 #[tokio::test]
 async fn parse_scontrol_pending_job() {
     let exec = SlurmFixture::new(fixture_dir("basic"));
-    let raw = exec.scontrol_show_job("12346").await.unwrap();
+    let raw = exec.scontrol_show_job("12346", None).await.unwrap();
     let mut fields: Vec<(String, String)> = SlurmParser::parse_scontrol_output(&raw)
         .unwrap()
         .into_iter()
@@ -66,11 +100,11 @@ async fn parse_scontrol_pending_job() {
 #[tokio::test]
 async fn enhance_job_with_scontrol() {
     let exec = SlurmFixture::new(fixture_dir("basic"));
-    let raw_squeue = exec.squeue(None, None).await.unwrap();
+    let raw_squeue = exec.squeue(None, None, None).await.unwrap();
     let mut jobs = SlurmParser::parse_squeue_output(&raw_squeue).unwrap();
 
     for job in jobs.iter_mut() {
-        if let Ok(raw_scontrol) = exec.scontrol_show_job(&job.job_id).await
+        if let Ok(raw_scontrol) = exec.scontrol_show_job(&job.job_id, None).await
             && let Ok(fields) = SlurmParser::parse_scontrol_output(&raw_scontrol)
         {
             SlurmParser::enhance_job_with_scontrol_data(job, fields);
@@ -80,11 +114,366 @@ async fn enhance_job_with_scontrol() {
     insta::assert_yaml_snapshot!(jobs);
 }
 
+#[tokio::test]
+async fn parse_sacct_basic() {
+    let exec = SlurmFixture::new(fixture_dir("basic"));
+    let raw = exec
+        .sacct(None, None, None, Duration::from_secs(24 * 60 * 60))
+        .await
+        .unwrap();
+    let jobs = SlurmParser::parse_sacct_output(&raw).unwrap();
+    insta::assert_yaml_snapshot!(jobs);
+}
+
+#[tokio::test]
+async fn parse_squeue_json() {
+    let exec = SlurmFixture::new(fixture_dir("json_basic"));
+    let raw = exec.squeue_json(None, None, None).await.unwrap();
+    let jobs = SlurmParser::parse_squeue_json(&raw).unwrap();
+    insta::assert_yaml_snapshot!(jobs);
+}
+
+#[tokio::test]
+async fn parse_squeue_json_legacy() {
+    let exec = SlurmFixture::new(fixture_dir("json_legacy"));
+    let raw = exec.squeue_json(None, None, None).await.unwrap();
+    let jobs = SlurmParser::parse_squeue_json(&raw).unwrap();
+    insta::assert_yaml_snapshot!(jobs);
+}
+
+#[test]
+fn parse_version_gates_json() {
+    let version = SlurmParser::parse_version("slurm 23.02.6\n").unwrap();
+    assert_eq!(version, (23, 2));
+    assert!(SlurmParser::supports_json(version));
+    assert!(SlurmParser::supports_json(
+        SlurmParser::parse_version("slurm 21.08.8-2").unwrap()
+    ));
+    assert!(!SlurmParser::supports_json(
+        SlurmParser::parse_version("slurm-wlm 19.05.5").unwrap()
+    ));
+}
+
 #[tokio::test]
 async fn fixture_scancel_records_calls() {
     let exec = SlurmFixture::new(fixture_dir("basic"));
-    exec.scancel("12345").await.unwrap();
-    exec.scancel("12347").await.unwrap();
+    exec.scancel("12345", None, &CancelOptions::default())
+        .await
+        .unwrap();
+    exec.scancel("12347", None, &CancelOptions::default())
+        .await
+        .unwrap();
     let cancelled = exec.cancelled.lock().unwrap().clone();
     assert_eq!(cancelled, vec!["12345", "12347"]);
 }
+
+#[tokio::test]
+async fn parse_squeue_multi_cluster() {
+    let exec = SlurmFixture::new(fixture_dir("multi_cluster"));
+    let raw = exec.squeue(None, None, Some("alpha,beta")).await.unwrap();
+    let jobs = SlurmParser::parse_squeue_output(&raw).unwrap();
+    insta::assert_yaml_snapshot!(jobs);
+}
+
+#[tokio::test]
+async fn parse_scontrol_multi_keys_jobs_like_squeue() {
+    let exec = SlurmFixture::new(fixture_dir("array_jobs"));
+    let raw = exec.scontrol_show_jobs(None).await.unwrap();
+    let jobs = SlurmParser::parse_scontrol_multi(&raw).unwrap();
+
+    let mut keys: Vec<_> = jobs.keys().cloned().collect();
+    keys.sort();
+    assert_eq!(
+        keys,
+        vec![
+            "23673084",
+            "23673084_1",
+            "23673084_2",
+            "23673084_[3-4%2]",
+            "23673085",
+            "23673086",
+            "99999",
+        ]
+    );
+    assert_eq!(jobs["23673084_2"]["NodeList"], "node04");
+    assert_eq!(jobs["99999"]["WorkDir"], "/home/dave");
+}
+
+#[tokio::test]
+async fn parse_sinfo_nodes() {
+    let exec = SlurmFixture::new(fixture_dir("nodes"));
+    let raw = exec.sinfo(None, None).await.unwrap();
+    let nodes = SlurmParser::parse_sinfo_output(&raw).unwrap();
+    insta::assert_yaml_snapshot!(nodes);
+}
+
+#[tokio::test]
+async fn enhance_node_with_scontrol() {
+    let exec = SlurmFixture::new(fixture_dir("nodes"));
+    let raw = exec.sinfo(None, None).await.unwrap();
+    let mut nodes = SlurmParser::parse_sinfo_output(&raw).unwrap();
+    let raw = exec.scontrol_show_nodes(None).await.unwrap();
+    let mut details = SlurmParser::parse_scontrol_nodes(&raw).unwrap();
+    assert_eq!(details.len(), 6);
+
+    for node in &mut nodes {
+        SlurmParser::enhance_node_with_scontrol_data(node, details.remove(&node.name).unwrap());
+    }
+    insta::assert_yaml_snapshot!(nodes);
+}
+
+#[test]
+fn squeue_start_keeps_the_estimate_of_a_job_in_several_partitions() {
+    let output = "JOBID|USER|PARTITION|START_TIME\n\
+                  7001|alice|gpu,debug|2024-01-15T12:00:00\n\
+                  7002|alice|gpu|N/A\n";
+    let starts = SlurmParser::parse_squeue_start(output).unwrap();
+    assert_eq!(starts.len(), 1);
+    assert!(starts.contains_key(&(None, "7001".to_string())));
+}
+
+#[test]
+fn expand_hostlist_handles_ranges_and_padding() {
+    assert_eq!(
+        SlurmParser::expand_hostlist("node[01-03,07],login1"),
+        vec!["node01", "node02", "node03", "node07", "login1"]
+    );
+    assert_eq!(
+        SlurmParser::expand_hostlist("rack[1-2]-gpu[8-9]"),
+        vec!["rack1-gpu8", "rack1-gpu9", "rack2-gpu8", "rack2-gpu9"]
+    );
+    assert_eq!(SlurmParser::expand_hostlist("gpu01"), vec!["gpu01"]);
+    assert!(SlurmParser::expand_hostlist("").is_empty());
+}
+
+#[test]
+fn expand_hostlist_keeps_oversized_ranges_as_written() {
+    assert_eq!(
+        SlurmParser::expand_hostlist("node[0-99999999],login[1-2]"),
+        vec!["node[0-99999999]", "login1", "login2"]
+    );
+    // The cap is on the whole list, not each range
+    let hosts = SlurmParser::expand_hostlist("a[1-60000],b[1-6000]");
+    assert_eq!(hosts.len(), 60_001);
+    assert_eq!(hosts.last().unwrap(), "b[1-6000]");
+    assert_eq!(
+        SlurmParser::expand_hostlist("n[1-256][1-256]").len(),
+        MAX_HOSTLIST_HOSTS
+    );
+}
+
+#[tokio::test]
+async fn parse_partitions_with_scontrol_limits() {
+    let exec = SlurmFixture::new(fixture_dir("nodes"));
+    let raw = exec.sinfo_partitions(None).await.unwrap();
+    let mut partitions = SlurmParser::parse_sinfo_partitions(&raw).unwrap();
+    let raw = exec.scontrol_show_partitions(None).await.unwrap();
+    let mut details = SlurmParser::parse_scontrol_partitions(&raw).unwrap();
+
+    for partition in &mut partitions {
+        let fields = details.remove(&partition.name).unwrap();
+        SlurmParser::enhance_partition_with_scontrol_data(partition, fields);
+    }
+    insta::assert_yaml_snapshot!(partitions);
+}
+
+#[tokio::test]
+async fn parse_sstat_steps() {
+    let exec = SlurmFixture::new(fixture_dir("basic"));
+    let raw = exec.sstat("12345", None).await.unwrap();
+    let steps = SlurmParser::parse_sstat_output(&raw).unwrap();
+    insta::assert_yaml_snapshot!(steps);
+}
+
+#[test]
+fn parse_size_reads_slurm_units() {
+    assert_eq!(SlurmParser::parse_size("1012K"), Some(1012 * 1024));
+    assert_eq!(SlurmParser::parse_size("8G"), Some(8 << 30));
+    assert_eq!(SlurmParser::parse_size("0.50M"), Some(512 * 1024));
+    assert_eq!(SlurmParser::parse_size("0"), Some(0));
+    assert_eq!(SlurmParser::parse_size("lots"), None);
+}
+
+#[test]
+fn parse_time_limit_reads_sbatch_formats() {
+    let minutes = |n: u64| Some(Duration::from_secs(n * 60));
+    assert_eq!(parse_time_limit("30"), minutes(30));
+    assert_eq!(
+        parse_time_limit("30:15"),
+        Some(Duration::from_secs(30 * 60 + 15))
+    );
+    assert_eq!(parse_time_limit("04:00:00"), minutes(240));
+    assert_eq!(parse_time_limit("2-12"), minutes(60 * 60));
+    assert_eq!(parse_time_limit("1-00:30"), minutes(24 * 60 + 30));
+    assert_eq!(parse_time_limit("1-00:00:00"), minutes(24 * 60));
+    assert_eq!(parse_time_limit("UNLIMITED"), None);
+    assert_eq!(parse_time_limit("1:2:3:4"), None);
+    assert_eq!(parse_time_limit("-5"), None);
+    assert_eq!(parse_time_limit(""), None);
+}
+
+#[test]
+fn parse_task_ranges_reads_array_specs() {
+    assert_eq!(parse_task_ranges("3-5,20"), Some(vec![3, 4, 5, 20]));
+    assert_eq!(parse_task_ranges("1-9:4"), Some(vec![1, 5, 9]));
+    assert_eq!(parse_task_ranges("4,2-4%2"), Some(vec![2, 3, 4]));
+    assert_eq!(parse_task_ranges("7"), Some(vec![7]));
+    assert_eq!(parse_task_ranges("5-3"), None);
+    assert_eq!(parse_task_ranges("1-"), None);
+    assert_eq!(parse_task_ranges("1-9:0"), None);
+    assert_eq!(parse_task_ranges(""), None);
+    assert_eq!(parse_task_ranges("0-4000000000"), None);
+}
+
+#[test]
+fn parse_task_ranges_upto_stops_at_the_first_task_past_the_limit() {
+    assert_eq!(parse_task_ranges_upto("1-10:5", 7), Ok(vec![1, 6]));
+    assert_eq!(
+        parse_task_ranges_upto("1-10:3,2", 7),
+        Err(TaskRangeError::Beyond(10))
+    );
+    assert_eq!(
+        parse_task_ranges_upto("0-4000000000", 4),
+        Err(TaskRangeError::Beyond(5))
+    );
+    assert_eq!(
+        parse_task_ranges_upto("3-x", 4),
+        Err(TaskRangeError::Malformed)
+    );
+
+    // squeue folds pending tasks into one line
+    let mut job = Job::new(
+        "100_[4-6,9]".to_string(),
+        "sweep".to_string(),
+        "carol".to_string(),
+        JobState::Pending,
+    );
+    job.array_job_id = Some("100".to_string());
+    assert_eq!(job.array_task_ids(), vec![4, 5, 6, 9]);
+}
+
+#[test]
+fn format_task_ranges_compresses_task_ids() {
+    assert_eq!(format_task_ranges(&[5, 1, 3, 4, 4]), "1,3-5");
+    assert_eq!(format_task_ranges(&[7, 8]), "7,8");
+    assert_eq!(format_task_ranges(&[0, 1, 2, 10, 999]), "0-2,10,999");
+    assert_eq!(format_task_ranges(&[]), "");
+
+    let tasks = parse_task_ranges("0-99:3,100-140").unwrap();
+    assert_eq!(parse_task_ranges(&format_task_ranges(&tasks)), Some(tasks));
+
+    assert_eq!(parse_task_throttle("0-999%50"), Some(50));
+    assert_eq!(parse_task_throttle("100_[4-9%5]"), Some(5));
+    assert_eq!(parse_task_throttle("0-999"), None);
+    assert_eq!(parse_task_throttle("0-9%0"), None);
+}
+
+#[test]
+fn sbatch_directives_are_read_up_to_the_first_command() {
+    let script =
+        std::fs::read_to_string(fixture_dir("submit").join("scripts").join("train.sh")).unwrap();
+    let directives: Vec<_> = parse_sbatch_directives(&script)
+        .into_iter()
+        .map(|d| (d.line, d.option, d.value))
+        .collect();
+    let some = |value: &str| Some(value.to_string());
+    assert_eq!(
+        directives,
+        vec![
+            (2, "job-name".to_string(), some("resnet")),
+            (3, "partition".to_string(), some("gpu")),
+            (4, "time".to_string(), some("04:00:00")),
+            (5, "gres".to_string(), some("gpu:1")),
+            (6, "exclusive".to_string(), None),
+        ]
+    );
+
+    assert_eq!(parse_memory_mb("4000"), Some(4000));
+    assert_eq!(parse_memory_mb("16G"), Some(16 * 1024));
+    assert_eq!(parse_memory_mb("512k"), Some(1));
+    assert_eq!(parse_memory_mb("lots"), None);
+}
+
+#[test]
+fn sacct_efficiency_flags_wasteful_jobs() {
+    let raw = std::fs::read_to_string(fixture_dir("basic").join("sacct.txt")).unwrap();
+    let jobs = SlurmParser::parse_sacct_output(&raw).unwrap();
+    let efficiency = |id: &str| {
+        jobs.iter()
+            .find(|job| job.job_id == id)
+            .and_then(|job| job.efficiency.clone())
+    };
+
+    // Used 2:01:30 of 8 CPUs × 2:03:17 and 9.5G of 32G
+    let train = efficiency("12302").unwrap();
+    assert_eq!(train.cpu_efficiency.map(f64::round), Some(12.0));
+    assert_eq!(train.memory_efficiency.map(f64::round), Some(30.0));
+    assert!(train.is_wasteful());
+
+    assert!(!efficiency("12301").unwrap().is_wasteful());
+    // 3% of its CPUs, but over in under a minute
+    assert!(!efficiency("12303").unwrap().is_wasteful());
+    // Still running: nothing to judge yet
+    assert!(efficiency("12345").is_none());
+}
+
+#[test]
+fn sacct_per_cpu_memory_request() {
+    let raw = "JobID|State|Elapsed|NNodes|AllocCPUS|ReqMem|TotalCPU|MaxRSS\n\
+               7|COMPLETED|01:00:00|2|8|1000Mc|04:00:00|\n\
+               7.0|COMPLETED|01:00:00|2|8||04:00:00|1000M\n";
+    let jobs = SlurmParser::parse_sacct_output(raw).unwrap();
+    let efficiency = jobs[0].efficiency.clone().unwrap();
+
+    // 1000M per CPU on 8 CPUs over 2 nodes is 4000M per node
+    assert_eq!(efficiency.memory_requested, Some(4000 << 20));
+    assert_eq!(efficiency.memory_efficiency, Some(25.0));
+    assert_eq!(efficiency.cpu_efficiency, Some(50.0));
+}
+
+#[tokio::test]
+async fn parse_sprio_factors() {
+    let exec = SlurmFixture::new(fixture_dir("pending"));
+    let raw = exec.sprio(Some("gpu"), None).await.unwrap();
+    let priorities = SlurmParser::parse_sprio_output(&raw).unwrap();
+    insta::assert_yaml_snapshot!(priorities);
+}
+
+#[tokio::test]
+async fn parse_sshare_skips_account_rows() {
+    let exec = SlurmFixture::new(fixture_dir("pending"));
+    let raw = exec.sshare(None, None).await.unwrap();
+    let shares = SlurmParser::parse_sshare_output(&raw).unwrap();
+    insta::assert_yaml_snapshot!(shares);
+}
+
+#[test]
+fn squeue_json_names_a_pending_array_range_like_the_text_output() {
+    let raw = r#"{"jobs": [
+      {"job_id": 100, "name": "sweep", "user_name": "erin", "job_state": ["PENDING"],
+       "array_job_id": {"set": true, "infinite": false, "number": 100},
+       "array_task_id": {"set": false, "infinite": false, "number": 0},
+       "array_task_string": "4-9",
+       "array_max_tasks": {"set": true, "infinite": false, "number": 5},
+       "submit_time": {"set": true, "infinite": false, "number": 1705314000}},
+      {"job_id": 200, "name": "sweep", "user_name": "erin", "job_state": ["PENDING"],
+       "array_job_id": {"set": true, "infinite": false, "number": 200},
+       "array_task_id": {"set": false, "infinite": false, "number": 0},
+       "array_task_string": "1,3%2",
+       "array_max_tasks": {"set": true, "infinite": false, "number": 2}}
+    ]}"#;
+    let jobs = SlurmParser::parse_squeue_json(raw).unwrap();
+
+    assert_eq!(jobs[0].job_id, "100_[4-9%5]");
+    assert_eq!(jobs[0].array_job_id.as_deref(), Some("100"));
+    assert_eq!(jobs[0].array_task_ids(), vec![4, 5, 6, 7, 8, 9]);
+    assert_eq!(parse_task_throttle(&jobs[0].job_id), Some(5));
+    assert_eq!(jobs[1].job_id, "200_[1,3%2]");
+    assert_eq!(jobs[1].array_task_ids(), vec![1, 3]);
+
+    // Unix timestamps are UTC whatever this machine's time zone
+    assert_eq!(
+        jobs[0].submit_time.unwrap().to_rfc3339(),
+        "2024-01-15T10:20:00+00:00"
+    );
+}
@@ -0,0 +1,182 @@
+//! Tests for `--record`: a session recorded from one executor must replay
+//! identically through [`SlurmFixture`].
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use lazyslurm::slurm::{CancelOptions, SlurmExecutor, SlurmFixture, SlurmRecorder};
+use lazyslurm::ui::{App, View};
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("lazyslurm-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+async fn snapshot(executor: Arc<dyn SlurmExecutor>) -> (serde_json::Value, serde_json::Value) {
+    let mut app = App::with_executor(executor);
+    app.current_user = None;
+    app.refresh_jobs().await.unwrap();
+    app.switch_view(View::History);
+    app.wait_for_fetches().await;
+    (
+        serde_json::to_value(&app.job_list.jobs).unwrap(),
+        serde_json::to_value(&app.history_list.jobs).unwrap(),
+    )
+}
+
+#[tokio::test]
+async fn recorded_session_replays_through_the_fixture_executor() {
+    let dir = scratch_dir("record-text");
+    let source = Arc::new(SlurmFixture::new("tests/fixtures/basic"));
+    let recorder = Arc::new(SlurmRecorder::new(source.clone(), &dir).unwrap());
+
+    let live = snapshot(recorder).await;
+    let replayed = snapshot(Arc::new(SlurmFixture::new(&dir))).await;
+
+    assert_eq!(live, replayed);
+    assert!(dir.join("squeue.txt").exists());
+    assert!(dir.join("scontrol.txt").exists());
+    assert!(dir.join("sacct.txt").exists());
+}
+
+#[tokio::test]
+async fn recorded_multi_cluster_session_replays_through_the_fixture_executor() {
+    let dir = scratch_dir("record-clusters-replay");
+    let source = Arc::new(SlurmFixture::new("tests/fixtures/multi_cluster"));
+    let recorder = Arc::new(SlurmRecorder::new(source, &dir).unwrap());
+
+    let jobs = |executor: Arc<dyn SlurmExecutor>| async move {
+        let mut app = App::with_executor(executor);
+        app.current_user = None;
+        app.current_clusters = Some("alpha,beta".to_string());
+        app.refresh_jobs().await.unwrap();
+        assert!(app.error_message.is_none(), "{:?}", app.error_message);
+        app.job_list.jobs
+    };
+    let live = jobs(recorder).await;
+    let replayed = jobs(Arc::new(SlurmFixture::new(&dir))).await;
+
+    // Job 501 on each cluster got its own cluster's details
+    let work_dirs: Vec<_> = live
+        .iter()
+        .map(|job| (job.cluster.as_deref(), job.working_dir.as_deref()))
+        .collect();
+    assert_eq!(
+        work_dirs,
+        vec![
+            (Some("alpha"), Some("/home/alice/alpha")),
+            (Some("alpha"), Some("/home/alice/alpha")),
+            (Some("beta"), Some("/home/bob/beta")),
+        ]
+    );
+    assert_eq!(
+        serde_json::to_value(&live).unwrap(),
+        serde_json::to_value(&replayed).unwrap()
+    );
+    assert!(dir.join("alpha,beta/squeue.txt").exists());
+    assert!(dir.join("beta/scontrol.txt").exists());
+}
+
+#[tokio::test]
+async fn recorded_json_session_replays_through_the_fixture_executor() {
+    let dir = scratch_dir("record-json");
+    let source = Arc::new(SlurmFixture::new("tests/fixtures/json_basic"));
+    let recorder = Arc::new(SlurmRecorder::new(source, &dir).unwrap());
+
+    let mut live = App::with_executor(recorder);
+    live.current_user = None;
+    live.refresh_jobs().await.unwrap();
+    let mut replayed = App::with_executor(Arc::new(SlurmFixture::new(&dir)));
+    replayed.current_user = None;
+    replayed.refresh_jobs().await.unwrap();
+
+    assert!(replayed.error_message.is_none());
+    assert_eq!(
+        serde_json::to_value(&live.job_list.jobs).unwrap(),
+        serde_json::to_value(&replayed.job_list.jobs).unwrap()
+    );
+}
+
+#[tokio::test]
+async fn manifest_lists_every_call_with_its_arguments() {
+    let dir = scratch_dir("record-manifest");
+    let source = Arc::new(SlurmFixture::new("tests/fixtures/basic"));
+    let recorder = SlurmRecorder::new(source, &dir).unwrap();
+
+    recorder
+        .squeue(Some("alice"), Some("gpu"), None)
+        .await
+        .unwrap();
+    // basic has no version.txt
+    recorder.version().await.unwrap_err();
+    recorder
+        .scancel("12345", Some("alpha"), &CancelOptions::default())
+        .await
+        .unwrap();
+
+    let manifest: Vec<serde_json::Value> = std::fs::read_to_string(dir.join("manifest.jsonl"))
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(manifest[0]["lazyslurm_version"], env!("CARGO_PKG_VERSION"));
+    let calls = &manifest[1..];
+    assert_eq!(calls.len(), 3);
+    assert_eq!(
+        calls[0],
+        serde_json::json!({
+            "call": "squeue",
+            "args": {"user": "alice", "partition": "gpu"},
+            "file": "squeue.txt",
+            "error": null,
+        })
+    );
+    assert_eq!(calls[1]["call"], "version");
+    assert!(calls[1]["file"].is_null());
+    assert!(
+        calls[1]["error"]
+            .as_str()
+            .unwrap()
+            .starts_with("Failed to read fixture")
+    );
+    assert_eq!(
+        calls[2]["args"],
+        serde_json::json!({"job_id": "12345", "cluster": "alpha"})
+    );
+    assert!(!dir.join("version.txt").exists());
+}
+
+#[tokio::test]
+async fn responses_are_kept_apart_per_cluster() {
+    let dir = scratch_dir("record-clusters");
+    let source = Arc::new(SlurmFixture::new("tests/fixtures/basic"));
+    let recorder = SlurmRecorder::new(source, &dir).unwrap();
+
+    let local = recorder.scontrol_show_jobs(None).await.unwrap();
+    let alpha = recorder.scontrol_show_jobs(Some("alpha")).await.unwrap();
+
+    assert_eq!(
+        std::fs::read_to_string(dir.join("scontrol.txt")).unwrap(),
+        local
+    );
+    assert_eq!(
+        std::fs::read_to_string(dir.join("alpha/scontrol.txt")).unwrap(),
+        alpha
+    );
+}
+
+#[tokio::test]
+async fn a_failed_write_is_reported_without_failing_the_call() {
+    let dir = scratch_dir("record-write-failure");
+    let source = Arc::new(SlurmFixture::new("tests/fixtures/basic"));
+    let recorder = SlurmRecorder::new(source, &dir).unwrap();
+    assert!(recorder.status().is_none());
+
+    // A file where the per-job directory should go
+    std::fs::write(dir.join("scontrol"), "").unwrap();
+    let output = recorder.scontrol_show_job("12345", None).await.unwrap();
+
+    assert!(output.starts_with("JobId=12345"));
+    assert!(recorder.status().unwrap().starts_with("Recording failed"));
+}
@@ -0,0 +1,201 @@
+//! Tests for running Slurm through a command wrapper. A fake `ssh` script in
+//! `tests/fixtures/remote/bin` stands in for the real thing: it treats its
+//! "host" argument as a log file and runs the remote command line locally
+//! against fake Slurm tools that serve the `basic` fixture.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use lazyslurm::slurm::{
+    CancelOptions, CommandWrapper, SignalScope, SlurmExecutor, SlurmProcess, split_command_line,
+};
+use lazyslurm::ui::{App, AppState};
+
+fn fake_ssh() -> String {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/remote/bin/ssh")
+        .display()
+        .to_string()
+}
+
+fn scratch_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("lazyslurm-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn remote(log: &Path) -> SlurmProcess {
+    let wrapper = CommandWrapper::new(vec![
+        fake_ssh(),
+        "-o".to_string(),
+        "BatchMode=yes".to_string(),
+        log.display().to_string(),
+        "--".to_string(),
+    ])
+    .unwrap();
+    SlurmProcess::wrapped(wrapper)
+}
+
+#[test]
+fn remote_command_line_is_shell_quoted() {
+    let wrapper = CommandWrapper::parse("ssh login1 --").unwrap();
+    let line = wrapper.remote_command_line(
+        "tail",
+        &[
+            "-n".to_string(),
+            "20".to_string(),
+            "/home/o'brien/my logs/$HOME.out".to_string(),
+        ],
+    );
+    assert_eq!(line, r"tail -n 20 '/home/o'\''brien/my logs/$HOME.out'");
+}
+
+#[test]
+fn split_command_line_honours_quotes() {
+    let words = split_command_line(r#"ssh -o "ProxyJump=gw 1" 'login 1' a\ b --"#).unwrap();
+    assert_eq!(
+        words,
+        vec!["ssh", "-o", "ProxyJump=gw 1", "login 1", "a b", "--"]
+    );
+    assert!(split_command_line("ssh 'login1").is_err());
+}
+
+#[test]
+fn ssh_wrapper_reuses_a_control_master_connection() {
+    let wrapper = CommandWrapper::parse("ssh -o BatchMode=yes login1 --").unwrap();
+    let prefix = wrapper.prefix().join(" ");
+    assert!(prefix.starts_with("ssh -o ControlMaster=auto -o ControlPath="));
+    assert!(prefix.ends_with("-o BatchMode=yes login1 --"));
+
+    // An explicit ControlPath is left alone
+    let wrapper = CommandWrapper::parse("ssh -o ControlPath=~/.ssh/cm-%C login1 --").unwrap();
+    assert_eq!(wrapper.prefix().len(), 5);
+
+    // Non-ssh wrappers are used verbatim
+    let wrapper = CommandWrapper::parse("docker exec lazyslurm_dev sh -c").unwrap();
+    assert_eq!(
+        wrapper.prefix().join(" "),
+        "docker exec lazyslurm_dev sh -c"
+    );
+}
+
+#[tokio::test]
+async fn app_lists_jobs_through_the_wrapper() {
+    let log = scratch_path("list.log");
+    let mut app = App::with_executor(Arc::new(remote(&log)));
+    app.current_user = Some("alice smith".to_string());
+
+    app.refresh_jobs().await.unwrap();
+
+    assert!(app.error_message.is_none(), "{:?}", app.error_message);
+    assert_eq!(app.job_list.jobs.len(), 3);
+    assert_eq!(
+        app.job_list.jobs[0].working_dir.as_deref(),
+        Some("/home/alice")
+    );
+
+    let log = std::fs::read_to_string(&log).unwrap();
+    let control_path = match std::env::var("XDG_RUNTIME_DIR") {
+        Ok(dir) if !dir.is_empty() => format!("{}/lazyslurm-%C", dir.trim_end_matches('/')),
+        _ => "~/.ssh/lazyslurm-%C".to_string(),
+    };
+    assert!(log.contains(&format!(
+        "options ControlMaster=auto ControlPath={control_path} "
+    )));
+    assert!(log.contains("command squeue -u 'alice smith' --format=%i,%j,%u,%t,%M,%N,%P"));
+    assert!(log.contains("command scontrol show job\n"));
+}
+
+#[tokio::test]
+async fn job_logs_are_read_through_the_wrapper() {
+    let log = scratch_path("tail.log");
+    let job_log = scratch_path("it's a job.out");
+    let lines: Vec<String> = (1..=30).map(|i| format!("step {i}")).collect();
+    std::fs::write(&job_log, lines.join("\n") + "\n").unwrap();
+
+    let exec = remote(&log);
+    let tail = exec
+        .tail_file(&job_log.display().to_string(), 3)
+        .await
+        .unwrap();
+
+    assert_eq!(tail, "step 28\nstep 29\nstep 30");
+    let log = std::fs::read_to_string(&log).unwrap();
+    assert!(log.contains(r"it'\''s a job.out'"), "{log}");
+}
+
+#[tokio::test]
+async fn cancel_runs_through_the_wrapper() {
+    let log = scratch_path("cancel.log");
+    remote(&log)
+        .scancel("12347", None, &CancelOptions::default())
+        .await
+        .unwrap();
+
+    let log = std::fs::read_to_string(&log).unwrap();
+    assert!(log.contains("scancel 12347"), "{log}");
+}
+
+#[tokio::test]
+async fn signal_and_scope_are_passed_to_scancel() {
+    let log = scratch_path("signal.log");
+    let exec = remote(&log);
+    exec.scancel(
+        "12347",
+        None,
+        &CancelOptions::signal("USR1", SignalScope::Batch),
+    )
+    .await
+    .unwrap();
+    exec.scancel(
+        "12347",
+        None,
+        &CancelOptions::signal("TERM", SignalScope::Step("0".to_string())),
+    )
+    .await
+    .unwrap();
+
+    let log = std::fs::read_to_string(&log).unwrap();
+    assert!(log.contains("scancel --signal=USR1 --batch 12347"), "{log}");
+    assert!(log.contains("scancel --signal=TERM 12347.0"), "{log}");
+}
+
+#[test]
+fn shell_is_attached_through_ssh_with_a_terminal() {
+    let wrapper = CommandWrapper::parse("ssh login1 --").unwrap();
+    let command = SlurmProcess::wrapped(wrapper)
+        .shell_command("42", Some("west"), Some("gpu01"))
+        .unwrap();
+    assert_eq!(command[..2], ["ssh", "-t"]);
+    assert_eq!(
+        command[command.len() - 3..command.len() - 1],
+        ["login1", "--"]
+    );
+    assert_eq!(
+        command.last().unwrap(),
+        "srun -M west --jobid=42 --overlap -w gpu01 --pty bash"
+    );
+
+    // Local Slurm runs srun directly
+    let command = SlurmProcess::new().shell_command("42", None, None).unwrap();
+    assert_eq!(
+        command,
+        vec!["srun", "--jobid=42", "--overlap", "--pty", "bash"]
+    );
+}
+
+#[test]
+fn submit_popup_is_not_offered_through_the_wrapper() {
+    let log = scratch_path("remote-submit.log");
+    let mut app = App::with_executor(Arc::new(remote(&log)));
+
+    // The picker would list this machine's scripts, not the cluster's
+    app.open_submit_popup();
+    assert_eq!(app.state, AppState::Normal);
+    assert!(app.file_picker.is_none());
+    assert!(
+        app.error_message
+            .as_deref()
+            .is_some_and(|message| message.starts_with("Cannot submit with --remote"))
+    );
+}
@@ -0,0 +1,206 @@
+//! Tests for the slurmrestd backend against a tiny local HTTP stand-in that
+//! serves recorded payloads from `tests/fixtures/rest/`.
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+use lazyslurm::slurm::{CancelOptions, SignalScope, SlurmExecutor, SlurmParser, SlurmRest};
+use lazyslurm::ui::{App, AppState, OutputFormat};
+
+#[derive(Debug, Clone, PartialEq)]
+struct Request {
+    method: String,
+    path: String,
+    token: Option<String>,
+}
+
+/// Serve `tests/fixtures/rest/<path>.json` for GETs under `/slurm/v0.0.40/`,
+/// acknowledge DELETEs, and record every request.
+async fn serve_recorded() -> (String, Arc<Mutex<Vec<Request>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let log = requests.clone();
+
+    tokio::spawn(async move {
+        loop {
+            let Ok((mut stream, _)) = listener.accept().await else {
+                return;
+            };
+            let mut raw = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !raw.windows(4).any(|w| w == b"\r\n\r\n") {
+                let n = stream.read(&mut buf).await.unwrap();
+                if n == 0 {
+                    break;
+                }
+                raw.extend_from_slice(&buf[..n]);
+            }
+            let head = String::from_utf8_lossy(&raw).to_string();
+            let mut lines = head.lines();
+            let mut request_line = lines.next().unwrap_or("").split_whitespace();
+            let method = request_line.next().unwrap_or("").to_string();
+            let path = request_line.next().unwrap_or("").to_string();
+            let token = lines
+                .filter_map(|l| l.split_once(':'))
+                .find(|(k, _)| k.eq_ignore_ascii_case("x-slurm-user-token"))
+                .map(|(_, v)| v.trim().to_string());
+            log.lock().unwrap().push(Request {
+                method: method.clone(),
+                path: path.clone(),
+                token,
+            });
+
+            let fixture = path
+                .strip_prefix("/slurm/v0.0.40/")
+                .map(|p| fixture_dir().join(format!("{p}.json")));
+            let (status, body) = match (method.as_str(), fixture) {
+                ("DELETE", Some(_)) => ("200 OK", r#"{"errors":[]}"#.to_string()),
+                ("GET", Some(file)) if file.exists() => {
+                    ("200 OK", std::fs::read_to_string(file).unwrap())
+                }
+                _ => (
+                    "404 Not Found",
+                    r#"{"errors":[{"error":"Unknown path","description":"no such endpoint"}]}"#
+                        .to_string(),
+                ),
+            };
+            let response = format!(
+                "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            let _ = stream.write_all(response.as_bytes()).await;
+        }
+    });
+
+    (base_url, requests)
+}
+
+fn fixture_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("rest")
+}
+
+#[tokio::test]
+async fn rest_jobs_are_parsed_with_the_json_models() {
+    let (url, requests) = serve_recorded().await;
+    let rest = SlurmRest::new(url, Some("secret-jwt".to_string()));
+
+    let raw = rest.squeue_json(None, None, None).await.unwrap();
+    let jobs = SlurmParser::parse_squeue_json(&raw).unwrap();
+
+    assert_eq!(jobs.len(), 3);
+    assert_eq!(jobs[0].name, "train, eval (fold 1)");
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests[0].path, "/slurm/v0.0.40/jobs");
+    assert_eq!(requests[0].token.as_deref(), Some("secret-jwt"));
+}
+
+#[tokio::test]
+async fn rest_applies_user_and_partition_filters() {
+    let (url, _) = serve_recorded().await;
+    let rest = SlurmRest::new(url, None);
+
+    let raw = rest.squeue_json(Some("alice"), None, None).await.unwrap();
+    let jobs = SlurmParser::parse_squeue_json(&raw).unwrap();
+    assert_eq!(jobs.len(), 1);
+    assert_eq!(jobs[0].user, "alice");
+
+    let raw = rest.squeue_json(None, Some("gpu"), None).await.unwrap();
+    let jobs = SlurmParser::parse_squeue_json(&raw).unwrap();
+    assert_eq!(jobs.len(), 1);
+    assert_eq!(jobs[0].partition, "gpu");
+}
+
+#[tokio::test]
+async fn rest_cancel_sends_delete() {
+    let (url, requests) = serve_recorded().await;
+    let rest = SlurmRest::new(url, None);
+
+    rest.scancel("12345", None, &CancelOptions::default())
+        .await
+        .unwrap();
+
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests[0].method, "DELETE");
+    assert_eq!(requests[0].path, "/slurm/v0.0.40/job/12345");
+}
+
+#[tokio::test]
+async fn rest_signal_is_sent_as_delete_parameters() {
+    let (url, requests) = serve_recorded().await;
+    let rest = SlurmRest::new(url, None);
+
+    rest.scancel(
+        "12345",
+        None,
+        &CancelOptions::signal("USR1", SignalScope::Batch),
+    )
+    .await
+    .unwrap();
+    let err = rest
+        .scancel(
+            "12345",
+            None,
+            &CancelOptions::signal("USR1", SignalScope::Step("0".to_string())),
+        )
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("not available"), "{err}");
+
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].method, "DELETE");
+    assert_eq!(
+        requests[0].path,
+        "/slurm/v0.0.40/job/12345?signal=SIGUSR1&flags=BATCH_JOB"
+    );
+}
+
+#[tokio::test]
+async fn rest_errors_carry_the_slurmrestd_description() {
+    let (url, _) = serve_recorded().await;
+    let rest = SlurmRest::new(url, None).with_api_version("v0.0.99");
+
+    let err = rest.squeue_json(None, None, None).await.unwrap_err();
+    assert!(err.to_string().contains("no such endpoint"), "{err}");
+}
+
+#[tokio::test]
+async fn app_uses_json_path_with_rest_backend() {
+    let (url, _) = serve_recorded().await;
+    let mut app = App::with_executor(Arc::new(SlurmRest::new(url, None)));
+    app.current_user = None;
+
+    app.refresh_jobs().await.unwrap();
+
+    assert_eq!(app.output_format, Some(OutputFormat::Json));
+    assert!(app.error_message.is_none(), "{:?}", app.error_message);
+    assert_eq!(app.job_list.jobs.len(), 3);
+}
+
+#[tokio::test]
+async fn job_detail_popups_are_not_offered_with_rest_backend() {
+    let (url, requests) = serve_recorded().await;
+    let mut app = App::with_executor(Arc::new(SlurmRest::new(url, None)));
+    app.current_user = None;
+    app.refresh_jobs().await.unwrap();
+    assert!(app.selected_job.is_some());
+
+    // Editing, resubmitting and the array summary build on scontrol's records
+    app.open_edit_popup();
+    app.open_resubmit_popup();
+    app.open_array_summary_popup();
+    app.wait_for_fetches().await;
+
+    assert_eq!(app.state, AppState::Normal);
+    assert_eq!(app.actions_in_flight, 0);
+    assert!(app.error_message.is_none(), "{:?}", app.error_message);
+    let requests = requests.lock().unwrap();
+    assert!(requests.iter().all(|r| !r.path.contains("/job/")));
+}
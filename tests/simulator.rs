@@ -0,0 +1,635 @@
+//! Tests for the in-memory Slurm simulator behind `--demo`. Its output goes
+//! through the same parsers as real Slurm output, so these also check that
+//! the formats line up.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use lazyslurm::models::{JobState, NodeState};
+use lazyslurm::slurm::{
+    CancelOptions, JobRequest, SignalScope, SimOutcome, SlurmExecutor, SlurmParser, SlurmSimulator,
+};
+use lazyslurm::ui::{App, AppState, View};
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("lazyslurm-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn minutes(n: u64) -> Duration {
+    Duration::from_secs(n * 60)
+}
+
+async fn states(sim: &SlurmSimulator) -> Vec<(String, JobState)> {
+    let raw = sim.squeue(None, None, None).await.unwrap();
+    SlurmParser::parse_squeue_output(&raw)
+        .unwrap()
+        .into_iter()
+        .map(|job| (job.job_id, job.state))
+        .collect()
+}
+
+#[tokio::test]
+async fn jobs_run_to_their_outcome() {
+    let sim = SlurmSimulator::new(scratch_dir("sim-outcomes")).unwrap();
+    let ok = sim.submit(JobRequest {
+        runtime: minutes(5),
+        ..JobRequest::new("ok", "alice", "debug")
+    });
+    let failing = sim.submit(JobRequest {
+        runtime: minutes(5),
+        outcome: SimOutcome::Failed(3),
+        ..JobRequest::new("failing", "alice", "debug")
+    });
+    let slow = sim.submit(JobRequest {
+        runtime: minutes(20),
+        time_limit: minutes(10),
+        ..JobRequest::new("slow", "alice", "compute")
+    });
+
+    assert_eq!(
+        states(&sim).await,
+        vec![
+            (ok.clone(), JobState::Running),
+            (failing.clone(), JobState::Running),
+            (slow.clone(), JobState::Running),
+        ]
+    );
+
+    sim.advance(Duration::from_secs(5 * 60 + 30));
+    assert_eq!(
+        states(&sim).await,
+        vec![
+            (ok.clone(), JobState::Completed),
+            (failing.clone(), JobState::Failed),
+            (slow.clone(), JobState::Running),
+        ]
+    );
+
+    sim.advance(minutes(5));
+    let raw = sim.scontrol_show_job(&slow, None).await.unwrap();
+    let fields = SlurmParser::parse_scontrol_output(&raw).unwrap();
+    assert_eq!(fields["JobState"], "TIMEOUT");
+    assert_eq!(fields["RunTime"], "00:10:00");
+
+    // Finished jobs leave squeue after a minute but stay in sacct
+    sim.advance(minutes(2));
+    assert!(states(&sim).await.is_empty());
+    let raw = sim
+        .sacct(None, None, None, Duration::from_secs(3600))
+        .await
+        .unwrap();
+    let history: Vec<_> = SlurmParser::parse_sacct_output(&raw)
+        .unwrap()
+        .into_iter()
+        .map(|job| (job.job_id, job.state, job.exit_code))
+        .collect();
+    assert_eq!(
+        history,
+        vec![
+            (ok, JobState::Completed, Some(0)),
+            (failing, JobState::Failed, Some(3)),
+            (slow, JobState::Timeout, Some(0)),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn jobs_wait_for_free_nodes() {
+    let sim = SlurmSimulator::new(scratch_dir("sim-queue")).unwrap();
+    // The debug partition has two 8-CPU nodes
+    let big = JobRequest {
+        cpus: 8,
+        runtime: minutes(10),
+        ..JobRequest::new("big", "alice", "debug")
+    };
+    let first = sim.submit(big.clone());
+    let second = sim.submit(big.clone());
+    let third = sim.submit(big.clone());
+    let fourth = sim.submit(big);
+
+    let raw = sim.scontrol_show_jobs(None).await.unwrap();
+    let details = SlurmParser::parse_scontrol_multi(&raw).unwrap();
+    assert_eq!(details[&first]["NodeList"], "sim01");
+    assert_eq!(details[&second]["NodeList"], "sim02");
+    assert_eq!(details[&third]["JobState"], "PENDING");
+    assert_eq!(details[&third]["Reason"], "Resources");
+    assert_eq!(details[&fourth]["Reason"], "Priority");
+
+    sim.advance(minutes(10));
+    assert_eq!(
+        states(&sim).await,
+        vec![
+            (first, JobState::Completed),
+            (second, JobState::Completed),
+            (third, JobState::Running),
+            (fourth, JobState::Running),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn scancel_cancels_and_frees_the_nodes() {
+    let sim = SlurmSimulator::new(scratch_dir("sim-cancel")).unwrap();
+    let whole_partition = JobRequest {
+        cpus: 16,
+        nodes: 2,
+        ..JobRequest::new("wide", "alice", "debug")
+    };
+    let running = sim.submit(whole_partition.clone());
+    let waiting = sim.submit(whole_partition);
+
+    sim.scancel(&running, None, &CancelOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(
+        states(&sim).await,
+        vec![
+            (running.clone(), JobState::Cancelled),
+            (waiting, JobState::Running),
+        ]
+    );
+
+    let err = sim
+        .scancel(&running, None, &CancelOptions::default())
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("already completing or completed"));
+    assert!(
+        sim.scancel("999999", None, &CancelOptions::default())
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn app_shows_simulated_jobs_and_their_logs() {
+    let dir = scratch_dir("sim-app");
+    let sim = Arc::new(SlurmSimulator::new(&dir).unwrap());
+    sim.submit(JobRequest::new("train", "alice", "gpu"));
+    sim.submit(JobRequest::new("other", "bob", "gpu"));
+    sim.advance(minutes(2));
+
+    let mut app = App::with_executor(sim.clone());
+    app.current_user = Some("alice".to_string());
+    app.refresh_jobs().await.unwrap();
+
+    assert!(app.error_message.is_none(), "{:?}", app.error_message);
+    assert_eq!(app.job_list.jobs.len(), 1);
+    let job = app.selected_job.as_ref().unwrap();
+    assert_eq!(job.name, "train");
+    assert_eq!(job.node_list.as_deref(), Some("sim03"));
+    assert_eq!(job.working_dir.as_deref(), dir.to_str());
+
+    let logs = &app.job_logs.as_ref().unwrap().content;
+    assert!(logs.contains("Job 1001 (train) started on sim03"), "{logs}");
+    assert!(logs.contains("step 4: loss="), "{logs}");
+
+    sim.advance(minutes(10));
+    app.switch_view(View::History);
+    app.wait_for_fetches().await;
+    assert_eq!(app.history_list.jobs[0].state, JobState::Completed);
+}
+
+#[tokio::test]
+async fn demo_starts_with_a_busy_queue() {
+    let sim = SlurmSimulator::demo("student").unwrap();
+
+    let raw = sim.squeue(Some("student"), None, None).await.unwrap();
+    let jobs = SlurmParser::parse_squeue_output(&raw).unwrap();
+    assert!(!jobs.is_empty());
+    let raw = sim
+        .sacct(None, None, None, Duration::from_secs(24 * 3600))
+        .await
+        .unwrap();
+    let history = SlurmParser::parse_sacct_output(&raw).unwrap();
+    assert!(history.iter().any(|job| job.is_finished()));
+}
+
+#[test]
+fn demo_logs_are_removed_with_the_simulator() {
+    let sim = SlurmSimulator::demo("student").unwrap();
+    let log_dir = sim.log_dir().to_path_buf();
+    assert!(log_dir.is_dir());
+
+    drop(sim);
+    assert!(!log_dir.exists());
+}
+
+#[tokio::test]
+async fn sinfo_reports_cpus_taken_by_running_jobs() {
+    let sim = SlurmSimulator::new(scratch_dir("sim-nodes")).unwrap();
+    sim.submit(JobRequest {
+        cpus: 8,
+        ..JobRequest::new("wide", "alice", "gpu")
+    });
+    sim.advance(minutes(1));
+
+    let raw = sim.sinfo(Some("gpu"), None).await.unwrap();
+    let mut nodes = SlurmParser::parse_sinfo_output(&raw).unwrap();
+    let raw = sim.scontrol_show_nodes(None).await.unwrap();
+    let mut details = SlurmParser::parse_scontrol_nodes(&raw).unwrap();
+    for node in &mut nodes {
+        SlurmParser::enhance_node_with_scontrol_data(node, details.remove(&node.name).unwrap());
+    }
+
+    let summary: Vec<_> = nodes
+        .iter()
+        .map(|n| {
+            (
+                n.name.as_str(),
+                n.state.clone(),
+                n.cpus_alloc,
+                n.memory_alloc,
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("sim03", NodeState::Mixed, 8, Some(1024)),
+            ("sim04", NodeState::Idle, 0, Some(0)),
+        ]
+    );
+    assert_eq!(nodes[0].gres.as_deref(), Some("gpu:a100:4"));
+}
+
+#[tokio::test]
+async fn partitions_report_limits_and_busy_nodes() {
+    let sim = SlurmSimulator::new(scratch_dir("sim-partitions")).unwrap();
+    sim.submit(JobRequest::new("one", "alice", "compute"));
+    sim.advance(minutes(1));
+
+    let raw = sim.sinfo_partitions(None).await.unwrap();
+    let mut partitions = SlurmParser::parse_sinfo_partitions(&raw).unwrap();
+    let raw = sim.scontrol_show_partitions(None).await.unwrap();
+    let mut details = SlurmParser::parse_scontrol_partitions(&raw).unwrap();
+    for partition in &mut partitions {
+        let fields = details.remove(&partition.name).unwrap();
+        SlurmParser::enhance_partition_with_scontrol_data(partition, fields);
+    }
+
+    let summary: Vec<_> = partitions
+        .iter()
+        .map(|p| (p.name.as_str(), p.is_default, p.nodes_alloc, p.nodes_total))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("debug", true, 0, 2),
+            ("gpu", false, 0, 2),
+            ("compute", false, 1, 4)
+        ]
+    );
+    assert_eq!(
+        partitions[2].default_memory.as_deref(),
+        Some("4000M per CPU")
+    );
+}
+
+#[tokio::test]
+async fn sstat_memory_grows_towards_the_request() {
+    let sim = SlurmSimulator::new(scratch_dir("sim-sstat")).unwrap();
+    let id = sim.submit(JobRequest {
+        memory_mb: 1000,
+        ..JobRequest::new("grow", "alice", "debug")
+    });
+    sim.advance(minutes(1));
+    let early = SlurmParser::parse_sstat_output(&sim.sstat(&id, None).await.unwrap()).unwrap();
+    sim.advance(minutes(8));
+    let late = SlurmParser::parse_sstat_output(&sim.sstat(&id, None).await.unwrap()).unwrap();
+
+    assert_eq!(early[0].step_id, "batch");
+    assert!(early[0].max_rss < late[0].max_rss);
+    assert!(late[0].max_rss.unwrap() < 1000 << 20);
+
+    // Finished jobs have no live usage
+    sim.advance(minutes(5));
+    assert!(sim.sstat(&id, None).await.is_err());
+}
+
+#[tokio::test]
+async fn squeue_start_expects_jobs_when_time_limits_run_out() {
+    let sim = SlurmSimulator::new(scratch_dir("sim-start")).unwrap();
+    // Two 8-CPU debug nodes, and every job may hold one for its full hour
+    let big = JobRequest {
+        cpus: 8,
+        ..JobRequest::new("big", "alice", "debug")
+    };
+    let ids: Vec<String> = (0..5).map(|_| sim.submit(big.clone())).collect();
+    let unfit = sim.submit(JobRequest {
+        cpus: 64,
+        ..JobRequest::new("unfit", "alice", "debug")
+    });
+
+    let raw = sim.squeue_start(None, None, None).await.unwrap();
+    let starts = SlurmParser::parse_squeue_start(&raw).unwrap();
+    let start = |id: &String| {
+        starts
+            .get(&(None, id.clone()))
+            .map(|start| (*start - sim.now()).num_minutes())
+    };
+    assert_eq!(start(&ids[0]), None);
+    assert_eq!(start(&ids[2]), Some(60));
+    assert_eq!(start(&ids[3]), Some(60));
+    assert_eq!(start(&ids[4]), Some(120));
+    assert_eq!(start(&unfit), None);
+}
+
+#[tokio::test]
+async fn heavy_users_lose_fairshare_priority() {
+    let sim = SlurmSimulator::new(scratch_dir("sim-fairshare")).unwrap();
+    let big = |user: &str| JobRequest {
+        cpus: 8,
+        runtime: minutes(30),
+        ..JobRequest::new("big", user, "debug")
+    };
+    sim.submit(big("alice"));
+    sim.submit(big("alice"));
+    sim.advance(minutes(5));
+    let alice = sim.submit(big("alice"));
+    sim.advance(minutes(5));
+    let bob = sim.submit(big("bob"));
+
+    let raw = sim.sshare(None, None).await.unwrap();
+    let shares = SlurmParser::parse_sshare_output(&raw).unwrap();
+    let factor = |user: &str| {
+        shares
+            .iter()
+            .find(|share| share.user == user)
+            .and_then(|share| share.fairshare)
+            .unwrap()
+    };
+    assert!(factor("alice") < 0.5);
+    assert_eq!(factor("bob"), 1.0);
+
+    let raw = sim.sprio(Some("debug"), None).await.unwrap();
+    let priorities = SlurmParser::parse_sprio_output(&raw).unwrap();
+    let ids: Vec<_> = priorities.iter().map(|p| p.job_id.clone()).collect();
+    assert_eq!(ids, vec![alice.clone(), bob.clone()]);
+    // alice's job waited longer, but bob has the cluster's share to spend
+    assert!(priorities[0].age > priorities[1].age);
+    assert!(priorities[1].priority > priorities[0].priority);
+}
+
+#[tokio::test]
+async fn held_jobs_wait_until_released_and_requeued_jobs_run_again() {
+    let sim = SlurmSimulator::new(scratch_dir("sim-hold")).unwrap();
+    let whole_partition = JobRequest {
+        cpus: 16,
+        nodes: 2,
+        runtime: minutes(10),
+        ..JobRequest::new("wide", "alice", "debug")
+    };
+    let running = sim.submit(whole_partition.clone());
+    let waiting = sim.submit(whole_partition);
+
+    assert!(sim.hold(&running, None).await.is_err());
+    sim.hold(&waiting, None).await.unwrap();
+    let raw = sim.scontrol_show_job(&waiting, None).await.unwrap();
+    let fields = SlurmParser::parse_scontrol_output(&raw).unwrap();
+    assert_eq!(fields["Reason"], "JobHeldUser");
+
+    // The nodes free up, but the held job stays put
+    sim.advance(minutes(10));
+    assert_eq!(
+        states(&sim).await,
+        vec![
+            (running.clone(), JobState::Completed),
+            (waiting.clone(), JobState::Pending),
+        ]
+    );
+
+    sim.release(&waiting, None).await.unwrap();
+    assert_eq!(
+        states(&sim).await,
+        vec![
+            (running.clone(), JobState::Completed),
+            (waiting.clone(), JobState::Running),
+        ]
+    );
+
+    // Requeueing the finished job puts it behind the one now running
+    sim.requeue(&running, None).await.unwrap();
+    assert_eq!(
+        states(&sim).await,
+        vec![
+            (running.clone(), JobState::Pending),
+            (waiting.clone(), JobState::Running),
+        ]
+    );
+    assert!(sim.requeue(&running, None).await.is_err());
+
+    sim.advance(minutes(10));
+    assert_eq!(
+        states(&sim).await,
+        vec![(running, JobState::Running), (waiting, JobState::Completed),]
+    );
+}
+
+#[tokio::test]
+async fn edited_job_moves_partition_and_rejections_show_the_controller_error() {
+    let sim = Arc::new(SlurmSimulator::new(scratch_dir("sim-edit")).unwrap());
+    let whole_partition = JobRequest {
+        cpus: 16,
+        nodes: 2,
+        ..JobRequest::new("wide", "alice", "debug")
+    };
+    sim.submit(whole_partition);
+    let waiting = sim.submit(JobRequest::new("small", "alice", "debug"));
+
+    let mut app = App::with_executor(sim.clone());
+    app.current_user = None;
+    app.refresh_jobs().await.unwrap();
+    app.select_next_job();
+    app.open_edit_popup();
+    app.wait_for_fetches().await;
+    let form = app.edit_form.as_mut().unwrap();
+    assert_eq!(form.job.job_id, waiting);
+    assert_eq!(form.fields[0].value, "01:00:00");
+
+    // Users may only shorten time limits
+    form.fields[0].value = "2:00:00".to_string();
+    app.apply_edit();
+    app.wait_for_fetches().await;
+    let form = app.edit_form.as_mut().unwrap();
+    assert_eq!(
+        form.error.as_deref(),
+        Some("slurm_update error: Access/permission denied")
+    );
+
+    form.fields[0].value = "01:00:00".to_string();
+    form.fields[1].value = "nope".to_string();
+    app.apply_edit();
+    app.wait_for_fetches().await;
+    let form = app.edit_form.as_mut().unwrap();
+    assert_eq!(
+        form.error.as_deref(),
+        Some("slurm_update error: Invalid partition name specified")
+    );
+
+    form.fields[1].value = "compute".to_string();
+    form.fields[5].value = "moved".to_string();
+    app.apply_edit();
+    app.wait_for_fetches().await;
+    assert!(app.edit_form.is_none());
+
+    let raw = sim.scontrol_show_job(&waiting, None).await.unwrap();
+    let fields = SlurmParser::parse_scontrol_output(&raw).unwrap();
+    assert_eq!(fields["Partition"], "compute");
+    assert_eq!(fields["JobState"], "RUNNING");
+    assert_eq!(fields["Comment"], "moved");
+
+    // Only some fields can change once the job runs
+    let err = sim
+        .update_job(&waiting, None, &[("QOS".to_string(), "high".to_string())])
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("no longer pending"), "{err}");
+}
+
+#[tokio::test]
+async fn signals_reach_the_script_and_terminating_ones_end_it() {
+    let dir = scratch_dir("sim-signal");
+    let sim = SlurmSimulator::new(&dir).unwrap();
+    let whole_partition = JobRequest {
+        cpus: 16,
+        nodes: 2,
+        ..JobRequest::new("train", "alice", "debug")
+    };
+    let running = sim.submit(whole_partition.clone());
+    let waiting = sim.submit(whole_partition);
+
+    let checkpoint = CancelOptions::signal("USR1", SignalScope::Batch);
+    sim.scancel(&running, None, &checkpoint).await.unwrap();
+    let log_path = dir.join(format!("slurm-{}.out", running));
+    let log = sim
+        .tail_file(&log_path.display().to_string(), 5)
+        .await
+        .unwrap();
+    assert!(log.contains("Caught SIGUSR1 in batch script"), "{log}");
+    let err = sim.scancel(&waiting, None, &checkpoint).await.unwrap_err();
+    assert!(err.to_string().contains("pending"), "{err}");
+    assert_eq!(
+        states(&sim).await,
+        vec![
+            (running.clone(), JobState::Running),
+            (waiting.clone(), JobState::Pending),
+        ]
+    );
+
+    let kill = CancelOptions::signal("KILL", SignalScope::Full);
+    sim.scancel(&running, None, &kill).await.unwrap();
+    let raw = sim.scontrol_show_job(&running, None).await.unwrap();
+    let fields = SlurmParser::parse_scontrol_output(&raw).unwrap();
+    assert_eq!(fields["JobState"], "CANCELLED");
+    assert_eq!(fields["ExitCode"], "0:9");
+    assert_eq!(states(&sim).await[1], (waiting, JobState::Running));
+}
+
+#[tokio::test]
+async fn bulk_cancel_reports_the_jobs_that_failed() {
+    let sim = Arc::new(SlurmSimulator::new(scratch_dir("sim-bulk")).unwrap());
+    for name in ["a", "b", "c"] {
+        sim.submit(JobRequest::new(name, "alice", "debug"));
+    }
+
+    let mut app = App::with_executor(sim.clone());
+    app.current_user = None;
+    app.refresh_jobs().await.unwrap();
+    app.toggle_mark_all();
+    app.open_cancel_popup();
+
+    // Someone else gets to the middle job first
+    let middle = app.bulk_targets[1].job_id.clone();
+    sim.scancel(&middle, None, &CancelOptions::default())
+        .await
+        .unwrap();
+    app.confirm_bulk();
+    app.wait_for_fetches().await;
+
+    assert_eq!(app.state, AppState::BulkReportPopup);
+    let report = app.bulk_report.as_ref().unwrap();
+    assert_eq!(report.succeeded, 2);
+    assert_eq!(report.failures.len(), 1);
+    assert_eq!(report.failures[0].0, middle);
+    assert!(
+        report.failures[0]
+            .1
+            .contains("already completing or completed"),
+        "{:?}",
+        report.failures
+    );
+    assert!(
+        states(&sim)
+            .await
+            .iter()
+            .all(|(_, state)| *state == JobState::Cancelled)
+    );
+
+    app.dismiss_bulk_popup();
+    assert_eq!(app.state, AppState::Normal);
+    assert!(app.bulk_report.is_none());
+}
+
+#[tokio::test]
+async fn submitted_scripts_take_their_directives_and_the_form_overrides() {
+    let dir = scratch_dir("sim-sbatch");
+    let sim = Arc::new(SlurmSimulator::new(dir.join("logs")).unwrap());
+    std::fs::write(
+        dir.join("train.sh"),
+        "#!/bin/bash\n#SBATCH --job-name=train\n#SBATCH -p gpu\n#SBATCH -t 2:00:00\nsrun train\n",
+    )
+    .unwrap();
+    std::fs::write(dir.join("broken.sh"), "srun train\n").unwrap();
+
+    let mut app = App::with_executor(sim.clone());
+    app.current_user = None;
+    app.submit_dir = dir.clone();
+    app.refresh_jobs().await.unwrap();
+    app.open_submit_popup();
+    let picker = app.file_picker.as_mut().unwrap();
+    picker.selected = picker
+        .entries
+        .iter()
+        .position(|path| path.ends_with("train.sh"))
+        .unwrap();
+    app.pick_file();
+
+    // sbatch's own checks come back into the form
+    let form = app.submit_form.as_mut().unwrap();
+    form.fields[1].value = "bigmem".to_string();
+    app.submit_job();
+    app.wait_for_fetches().await;
+    let form = app.submit_form.as_mut().unwrap();
+    assert_eq!(
+        form.error.as_deref(),
+        Some("sbatch: error: invalid partition specified: bigmem")
+    );
+
+    form.fields[1].value = "gpu".to_string();
+    form.fields[0].value = "train-small".to_string();
+    app.submit_job();
+    app.wait_for_fetches().await;
+    assert_eq!(app.state, AppState::Normal);
+
+    let job = app.selected_job.clone().unwrap();
+    assert_eq!(job.name, "train-small");
+    assert_eq!(job.partition, "gpu");
+    let fields = SlurmParser::parse_scontrol_output(
+        &sim.scontrol_show_job(&job.job_id, None).await.unwrap(),
+    )
+    .unwrap();
+    assert_eq!(fields["TimeLimit"], "02:00:00");
+
+    let err = sim
+        .sbatch(&dir.join("broken.sh").to_string_lossy(), None, &[])
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("does not look like a batch script")
+    );
+}